@@ -108,6 +108,8 @@ pub fn all_fonts() -> Result<Box<[Font]>, Error> {
                 family_name: font.family_name(),
                 font_name: font.font_name(),
                 path: font.font_path()?,
+                // Core Text does not expose the index of a font in a font collection file
+                face_index: None,
                 style: Style::from_core_text(&traits),
                 weight: Weight::from_core_text(traits.normalized_weight()),
                 stretch: Stretch::from_core_text(traits.normalized_width()),
@@ -71,6 +71,7 @@ pub fn all_fonts() -> Result<Box<[Font]>, Error> {
                 family_name: font.family_name(),
                 font_name: font.face_name(),
                 path,
+                face_index: Some(face.get_index()),
                 style: Style::from_direct_write(font.style()),
                 weight: Weight::from_direct_write(font.weight()),
                 stretch: Stretch::from_direct_write(font.stretch()),
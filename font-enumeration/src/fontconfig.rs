@@ -83,6 +83,7 @@ pub fn all_fonts() -> Result<Box<[Font]>, Error> {
     objects.add(fontconfig::FC_FAMILY);
     objects.add(fontconfig::FC_FULLNAME);
     objects.add(fontconfig::FC_FILE);
+    objects.add(fontconfig::FC_INDEX);
     objects.add(fontconfig::FC_SLANT);
     objects.add(fontconfig::FC_WEIGHT);
     objects.add(fontconfig::FC_WIDTH);
@@ -94,6 +95,8 @@ pub fn all_fonts() -> Result<Box<[Font]>, Error> {
             let family = font.get_string(fontconfig::FC_FAMILY)?;
             let name = font.get_string(fontconfig::FC_FULLNAME).unwrap_or("");
             let path = font.get_string(fontconfig::FC_FILE)?;
+            // the upper 16 bits are the named instance of variable fonts
+            let face_index = font.face_index().map(|index| index as u32 & 0xFFFF);
 
             // is it ok to assume these defaults when the value is missing?
             let slant = font.slant().unwrap_or(fontconfig::FC_SLANT_ROMAN);
@@ -104,6 +107,7 @@ pub fn all_fonts() -> Result<Box<[Font]>, Error> {
                 family_name: family.to_owned(),
                 font_name: name.to_owned(),
                 path: PathBuf::from(path),
+                face_index,
                 style: Style::from_fc(slant),
                 weight: Weight::from_fc(weight),
                 stretch: Stretch::from_fc(width),
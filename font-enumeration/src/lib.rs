@@ -197,6 +197,11 @@ pub struct Font {
     /// Path at which the font file is located.
     pub path: PathBuf,
 
+    /// Index of the font in the font file. This is non-zero for all but the first font of font
+    /// collection files, such as TrueType Collection (TTC) files. `None` if the system does not
+    /// report it.
+    pub face_index: Option<u32>,
+
     /// The font's style.
     pub style: Style,
 
@@ -1,6 +1,6 @@
 pub(crate) fn case_insensitive_match(left: &str, right: &str) -> bool {
-    let left = left.chars().map(|c| c.to_lowercase()).flatten();
-    let right = right.chars().map(|c| c.to_lowercase()).flatten();
+    let left = left.chars().flat_map(|c| c.to_lowercase());
+    let right = right.chars().flat_map(|c| c.to_lowercase());
 
     left.eq(right)
 }
@@ -0,0 +1,418 @@
+use std::io::Write;
+
+use read_fonts::tables::os2::Os2;
+use swash::{FontRef, StringId};
+
+use crate::{names, tables::read_table, FontError, Out, Outcome};
+
+/// Arguments of the audit subcommand.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct AuditArgs {
+    #[command(flatten)]
+    pub input: crate::files::PathArgs,
+}
+
+/// The embedding permissions of the `OS/2` table's `fsType` field.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Embedding {
+    pub fs_type: u16,
+    /// The usage permission: "installable", "restricted", "preview & print" or "editable". If
+    /// multiple permission bits are set, as older fonts may do, this is the least restrictive.
+    pub permission: &'static str,
+    pub no_subsetting: bool,
+    pub bitmap_only: bool,
+}
+
+impl Embedding {
+    fn from_fs_type(fs_type: u16) -> Self {
+        let permission = if fs_type & 0x0008 != 0 {
+            "editable"
+        } else if fs_type & 0x0004 != 0 {
+            "preview & print"
+        } else if fs_type & 0x0002 != 0 {
+            "restricted"
+        } else {
+            "installable"
+        };
+
+        Embedding {
+            fs_type,
+            permission,
+            no_subsetting: fs_type & 0x0100 != 0,
+            bitmap_only: fs_type & 0x0200 != 0,
+        }
+    }
+}
+
+/// A heuristic classification of a font's license.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LicenseClass {
+    Ofl,
+    Apache,
+    Ufl,
+    ProprietaryOrUnknown,
+}
+
+impl LicenseClass {
+    /// Classify a license by looking for well-known license names and URLs in the license
+    /// description, license URL and copyright notice.
+    fn classify(texts: &[Option<&str>]) -> Self {
+        let text = texts
+            .iter()
+            .flatten()
+            .map(|text| text.to_lowercase())
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        if text.contains("open font license")
+            || text.contains("openfontlicense.org")
+            || text.contains("scripts.sil.org/ofl")
+        {
+            LicenseClass::Ofl
+        } else if text.contains("apache license") || text.contains("apache.org/licenses") {
+            LicenseClass::Apache
+        } else if text.contains("ubuntu font licen") {
+            LicenseClass::Ufl
+        } else {
+            LicenseClass::ProprietaryOrUnknown
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            LicenseClass::Ofl => "OFL",
+            LicenseClass::Apache => "Apache",
+            LicenseClass::Ufl => "UFL",
+            LicenseClass::ProprietaryOrUnknown => "proprietary/unknown",
+        }
+    }
+}
+
+impl serde::Serialize for LicenseClass {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.name())
+    }
+}
+
+/// The embedding permissions and license of a font.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Audit {
+    /// Not present if the font has no `OS/2` table.
+    pub embedding: Option<Embedding>,
+    pub license_description: Option<String>,
+    pub license_url: Option<String>,
+    pub license_class: LicenseClass,
+}
+
+impl Audit {
+    pub fn from_font(font: FontRef<'_>, language: Option<&str>) -> Self {
+        let name = |id| names::localized_string(font, id, language).map(|name| name.to_string());
+        let license_description = name(StringId::License);
+        let license_url = name(StringId::LicenseUrl);
+        let copyright = name(StringId::Copyright);
+
+        Audit {
+            embedding: read_table::<Os2>(font).map(|os2| Embedding::from_fs_type(os2.fs_type())),
+            license_class: LicenseClass::classify(&[
+                license_description.as_deref(),
+                license_url.as_deref(),
+                copyright.as_deref(),
+            ]),
+            license_description,
+            license_url,
+        }
+    }
+}
+
+/// A row of the audit table.
+struct Row {
+    source: String,
+    /// Not present for errors of the whole font file.
+    font_index: Option<usize>,
+    name: String,
+    embedding: String,
+    license_class: &'static str,
+    license_url: String,
+    license_description: String,
+}
+
+/// The maximum number of characters of the license description printed in the audit table.
+const MAX_DESCRIPTION_LENGTH: usize = 60;
+
+/// Prints the embedding permissions and licenses of the fonts as a table when finished.
+pub(crate) struct AuditTable<W> {
+    write: W,
+    name_language: Option<String>,
+    rows: Vec<Row>,
+}
+
+impl<W: Write> AuditTable<W> {
+    pub fn new(write: W, name_language: Option<String>) -> Self {
+        AuditTable {
+            write,
+            name_language,
+            rows: Vec::new(),
+        }
+    }
+
+    /// Write the table, with columns aligned.
+    pub fn finish(mut self) -> anyhow::Result<()> {
+        const HEADER: [&str; 7] = [
+            "SOURCE",
+            "INDEX",
+            "FONT",
+            "EMBEDDING",
+            "LICENSE",
+            "LICENSE URL",
+            "LICENSE DESCRIPTION",
+        ];
+
+        let rows: Vec<[String; 7]> = self
+            .rows
+            .into_iter()
+            .map(|row| {
+                [
+                    row.source,
+                    row.font_index
+                        .map_or_else(|| "-".to_owned(), |font_index| font_index.to_string()),
+                    row.name,
+                    row.embedding,
+                    row.license_class.to_owned(),
+                    row.license_url,
+                    row.license_description,
+                ]
+            })
+            .collect();
+
+        let mut widths = HEADER.map(|header| header.chars().count());
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let header = HEADER.map(ToOwned::to_owned);
+        for row in std::iter::once(&header).chain(&rows) {
+            let mut line = String::new();
+            for (idx, (cell, width)) in row.iter().zip(widths).enumerate() {
+                if idx > 0 {
+                    line.push_str("  ");
+                }
+                line.push_str(cell);
+                line.extend(std::iter::repeat_n(' ', width - cell.chars().count()));
+            }
+            writeln!(self.write, "{}", line.trim_end())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<W: Write> Out for AuditTable<W> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        let language = self.name_language.as_deref();
+        let audit = Audit::from_font(font, language);
+
+        let name = match (
+            names::family_name(font, language),
+            names::subfamily_name(font, language),
+        ) {
+            (Some(family), Some(subfamily)) => format!("{family} {subfamily}"),
+            (Some(family), None) => family,
+            _ => "-".to_owned(),
+        };
+
+        let embedding = match &audit.embedding {
+            Some(embedding) => {
+                let mut description = embedding.permission.to_owned();
+                if embedding.no_subsetting {
+                    description.push_str(", no subsetting");
+                }
+                if embedding.bitmap_only {
+                    description.push_str(", bitmap only");
+                }
+                description
+            }
+            None => "no OS/2 table".to_owned(),
+        };
+
+        // the description often spans multiple lines and paragraphs
+        let description = audit
+            .license_description
+            .as_deref()
+            .map(|description| description.split_whitespace().collect::<Vec<_>>().join(" "))
+            .map(|description| {
+                if description.chars().count() > MAX_DESCRIPTION_LENGTH {
+                    let truncated: String = description
+                        .chars()
+                        .take(MAX_DESCRIPTION_LENGTH - 3)
+                        .collect();
+                    format!("{truncated}...")
+                } else {
+                    description
+                }
+            });
+
+        self.rows.push(Row {
+            source: source.to_owned(),
+            font_index: Some(font_index),
+            name,
+            embedding,
+            license_class: audit.license_class.name(),
+            license_url: audit.license_url.unwrap_or_else(|| "-".to_owned()),
+            license_description: description.unwrap_or_else(|| "-".to_owned()),
+        });
+
+        Ok(Outcome::Pass)
+    }
+
+    /// Add a row for the error, with the error message in place of the license description.
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        self.rows.push(Row {
+            source: error.source.clone(),
+            font_index: error.font_index,
+            name: "-".to_owned(),
+            embedding: format!("failed to {}", error.stage),
+            license_class: "-",
+            license_url: "-".to_owned(),
+            license_description: error.message.clone(),
+        });
+        Ok(())
+    }
+}
+
+/// Outputs the embedding permissions and licenses of the fonts as JSON.
+pub(crate) struct JsonAudit<S> {
+    pub serializer: S,
+    name_language: Option<String>,
+}
+
+impl<S> JsonAudit<S> {
+    pub fn new(serializer: S, name_language: Option<String>) -> Self {
+        JsonAudit {
+            serializer,
+            name_language,
+        }
+    }
+}
+
+impl<S: serde::ser::SerializeSeq<Error = serde_json::Error>> Out for JsonAudit<S> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Out<'m> {
+            source: &'m str,
+            font_index: usize,
+            family_name: Option<&'m str>,
+            subfamily_name: Option<&'m str>,
+            #[serde(flatten)]
+            audit: &'m Audit,
+        }
+
+        let language = self.name_language.as_deref();
+
+        serde::ser::SerializeSeq::serialize_element(
+            &mut self.serializer,
+            &Out {
+                source,
+                font_index,
+                family_name: names::family_name(font, language).as_deref(),
+                subfamily_name: names::subfamily_name(font, language).as_deref(),
+                audit: &Audit::from_font(font, language),
+            },
+        )?;
+
+        Ok(Outcome::Pass)
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        serde::ser::SerializeSeq::serialize_element(&mut self.serializer, error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_rows() {
+        let mut output = Vec::new();
+        let mut table = AuditTable::new(&mut output, None);
+        table
+            .push_error(&FontError {
+                source: "broken.ttf".to_owned(),
+                font_index: None,
+                stage: crate::Stage::Parse,
+                message: "unknown sfnt version 0x67617262".to_owned(),
+            })
+            .unwrap();
+        table.finish().unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let row = output.lines().nth(1).unwrap();
+        assert!(row.starts_with("broken.ttf  -      -     failed to parse"));
+        assert!(row.ends_with("unknown sfnt version 0x67617262"));
+    }
+
+    #[test]
+    fn test_embedding() {
+        let embedding = Embedding::from_fs_type(0);
+        assert_eq!(embedding.permission, "installable");
+        assert!(!embedding.no_subsetting && !embedding.bitmap_only);
+
+        let embedding = Embedding::from_fs_type(0x0302);
+        assert_eq!(embedding.permission, "restricted");
+        assert!(embedding.no_subsetting && embedding.bitmap_only);
+
+        // the least restrictive permission applies
+        assert_eq!(Embedding::from_fs_type(0x000C).permission, "editable");
+    }
+
+    #[test]
+    fn test_license_class() {
+        assert_eq!(
+            LicenseClass::classify(&[
+                Some(
+                    "This Font Software is licensed under the SIL Open Font License, Version 1.1."
+                ),
+                None
+            ]),
+            LicenseClass::Ofl
+        );
+        assert_eq!(
+            LicenseClass::classify(&[None, Some("http://www.apache.org/licenses/LICENSE-2.0")]),
+            LicenseClass::Apache
+        );
+        assert_eq!(
+            LicenseClass::classify(&[Some("Ubuntu Font Licence 1.0")]),
+            LicenseClass::Ufl
+        );
+        assert_eq!(
+            LicenseClass::classify(&[Some("All rights reserved."), None]),
+            LicenseClass::ProprietaryOrUnknown
+        );
+        assert_eq!(
+            LicenseClass::classify(&[]),
+            LicenseClass::ProprietaryOrUnknown
+        );
+
+        // classes are serialized by their names
+        assert_eq!(
+            serde_json::to_string(&LicenseClass::ProprietaryOrUnknown).unwrap(),
+            r#""proprietary/unknown""#
+        );
+    }
+}
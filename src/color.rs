@@ -0,0 +1,607 @@
+use std::ops::RangeInclusive;
+
+use read_fonts::{
+    tables::{cbdt::Cbdt, cblc::Cblc, colr::Colr, cpal::Cpal, sbix::Sbix},
+    FontData, FontReadWithArgs,
+};
+use swash::{FontRef, StringId};
+
+use crate::{
+    names,
+    tables::{read_table, read_u16, read_u32},
+};
+
+const SBIX: swash::Tag = swash::tag_from_bytes(b"sbix");
+const SVG: swash::Tag = swash::tag_from_bytes(b"SVG ");
+
+/// The color glyphs of a `COLR` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ColorLayers {
+    pub version: u16,
+    /// The number of glyphs with color layers (version 0) or a paint graph (version 1).
+    pub color_glyphs: usize,
+}
+
+/// A `CPAL` palette.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Palette {
+    pub index: u16,
+    pub name: Option<String>,
+    pub usable_with_light_background: bool,
+    pub usable_with_dark_background: bool,
+    /// The colors of the palette's entries as hexadecimal "#RRGGBBAA" strings.
+    pub colors: Vec<String>,
+}
+
+/// A bitmap strike of an `sbix` or `CBLC` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Strike {
+    pub ppem: u16,
+    /// The pixel density the strike was designed for. Only present for `sbix` strikes.
+    pub ppi: Option<u16>,
+}
+
+/// The color bitmaps of an `sbix` or `CBDT` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ColorBitmaps {
+    pub strikes: Vec<Strike>,
+    /// The number of glyphs with a non-empty bitmap in at least one strike.
+    pub color_glyphs: usize,
+}
+
+/// The SVG documents of an `SVG ` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SvgDocuments {
+    pub documents: usize,
+    /// The number of glyphs with an SVG document.
+    pub color_glyphs: usize,
+}
+
+/// The color font technologies a font contains.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Color {
+    pub colr: Option<ColorLayers>,
+    pub palettes: Vec<Palette>,
+    pub sbix: Option<ColorBitmaps>,
+    pub cbdt: Option<ColorBitmaps>,
+    pub svg: Option<SvgDocuments>,
+}
+
+impl Color {
+    pub fn from_font(font: FontRef<'_>, language: Option<&str>) -> Self {
+        Color {
+            colr: colr(font),
+            palettes: palettes(font, language),
+            sbix: sbix(font),
+            cbdt: cbdt(font),
+            svg: svg(font),
+        }
+    }
+
+    /// Whether the font contains any color glyphs.
+    pub fn is_color_font(&self) -> bool {
+        self.colr.is_some() || self.sbix.is_some() || self.cbdt.is_some() || self.svg.is_some()
+    }
+}
+
+/// Write the color font technologies and palettes of a font in the human-readable format.
+pub(crate) fn write_color<W: std::io::Write>(mut write: W, color: &Color) -> std::io::Result<()> {
+    write!(write, "               Color: ")?;
+    if !color.is_color_font() {
+        write!(write, "no color glyphs")?;
+    }
+    let mut technologies = 0;
+    let mut separate = |write: &mut W| -> std::io::Result<()> {
+        technologies += 1;
+        if technologies > 1 {
+            write!(write, "\n                      ")?;
+        }
+        Ok(())
+    };
+    if let Some(colr) = &color.colr {
+        separate(&mut write)?;
+        write!(
+            write,
+            "COLRv{}: {} color glyphs",
+            colr.version, colr.color_glyphs
+        )?;
+    }
+    for (table, bitmaps) in [("sbix", &color.sbix), ("CBDT", &color.cbdt)] {
+        if let Some(bitmaps) = bitmaps {
+            separate(&mut write)?;
+            write!(
+                write,
+                "{table}: {} color glyphs, strikes at",
+                bitmaps.color_glyphs
+            )?;
+            for (idx, strike) in bitmaps.strikes.iter().enumerate() {
+                if idx > 0 {
+                    write!(write, ",")?;
+                }
+                write!(write, " {} ppem", strike.ppem)?;
+                if let Some(ppi) = strike.ppi {
+                    write!(write, " ({ppi} ppi)")?;
+                }
+            }
+        }
+    }
+    if let Some(svg) = &color.svg {
+        separate(&mut write)?;
+        write!(
+            write,
+            "SVG: {} color glyphs in {} documents",
+            svg.color_glyphs, svg.documents
+        )?;
+    }
+    writeln!(write)?;
+
+    if !color.palettes.is_empty() {
+        write!(write, "            Palettes: ")?;
+        for (idx, palette) in color.palettes.iter().enumerate() {
+            if idx > 0 {
+                write!(write, "\n                      ")?;
+            }
+            write!(write, "{}", palette.index)?;
+            if let Some(name) = &palette.name {
+                write!(write, " \"{name}\"")?;
+            }
+            match (
+                palette.usable_with_light_background,
+                palette.usable_with_dark_background,
+            ) {
+                (true, true) => write!(write, " (light and dark background)")?,
+                (true, false) => write!(write, " (light background)")?,
+                (false, true) => write!(write, " (dark background)")?,
+                (false, false) => {}
+            }
+            write!(write, ": {} colors", palette.colors.len())?;
+            for (idx, color) in palette.colors.iter().enumerate() {
+                if idx % 8 == 0 {
+                    write!(write, "\n                        ")?;
+                } else {
+                    write!(write, " ")?;
+                }
+                write!(write, "{color}")?;
+            }
+        }
+        writeln!(write)?;
+    }
+
+    Ok(())
+}
+
+/// A set of glyph IDs, stored as a bitset of all glyph IDs. Its size does not depend on the
+/// tables the glyphs are read from, so ranges of glyphs from many overlapping records of a
+/// crafted table do not take unbounded memory.
+struct GlyphSet(Box<[u64; 1024]>);
+
+impl GlyphSet {
+    fn new() -> Self {
+        GlyphSet(Box::new([0; 1024]))
+    }
+
+    fn insert(&mut self, glyph_id: u16) {
+        self.0[usize::from(glyph_id / 64)] |= 1 << (glyph_id % 64);
+    }
+
+    /// Insert the glyphs of a range, a word of the bitset at a time.
+    fn insert_range(&mut self, range: RangeInclusive<u16>) {
+        let (start, end) = (usize::from(*range.start()), usize::from(*range.end()));
+        if start > end {
+            return;
+        }
+        for word in start / 64..=end / 64 {
+            let low = start.max(word * 64) - word * 64;
+            let high = end.min(word * 64 + 63) - word * 64;
+            self.0[word] |= (u64::MAX >> (63 - high)) & (u64::MAX << low);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.0.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
+
+fn colr(font: FontRef<'_>) -> Option<ColorLayers> {
+    let colr = read_table::<Colr>(font)?;
+
+    let mut glyph_ids = GlyphSet::new();
+    if let Some(Ok(records)) = colr.base_glyph_records() {
+        for record in records {
+            glyph_ids.insert(record.glyph_id().to_u16());
+        }
+    }
+    if let Some(Ok(list)) = colr.base_glyph_list() {
+        for record in list.base_glyph_paint_records() {
+            glyph_ids.insert(record.glyph_id().to_u16());
+        }
+    }
+
+    Some(ColorLayers {
+        version: colr.version(),
+        color_glyphs: glyph_ids.len(),
+    })
+}
+
+fn palettes(font: FontRef<'_>, language: Option<&str>) -> Vec<Palette> {
+    let Some(cpal) = read_table::<Cpal>(font) else {
+        return Vec::new();
+    };
+    let Some(Ok(color_records)) = cpal.color_records_array() else {
+        return Vec::new();
+    };
+    let types = cpal.palette_types_array().and_then(Result::ok);
+    let labels = cpal.palette_labels_array().and_then(Result::ok);
+    let num_entries = usize::from(cpal.num_palette_entries());
+
+    cpal.color_record_indices()
+        .iter()
+        .enumerate()
+        .map(|(idx, first_record)| {
+            let bits = types
+                .and_then(|types| types.get(idx))
+                .map_or(0, |palette_type| palette_type.get().bits());
+            let name = labels
+                .and_then(|labels| labels.get(idx))
+                .map(|label| label.get())
+                // 0xFFFF means the palette has no name
+                .filter(|&name_id| name_id != 0xFFFF)
+                .and_then(|name_id| {
+                    names::localized_string(font, StringId::from_raw(name_id), language)
+                })
+                .map(|name| name.to_string());
+
+            let first_record = usize::from(first_record.get());
+            let colors = color_records
+                .iter()
+                .skip(first_record)
+                .take(num_entries)
+                .map(|color| {
+                    format!(
+                        "#{:02X}{:02X}{:02X}{:02X}",
+                        color.red(),
+                        color.green(),
+                        color.blue(),
+                        color.alpha()
+                    )
+                })
+                .collect();
+
+            Palette {
+                index: idx as u16,
+                name,
+                usable_with_light_background: bits & 0x0001 != 0,
+                usable_with_dark_background: bits & 0x0002 != 0,
+                colors,
+            }
+        })
+        .collect()
+}
+
+fn sbix(font: FontRef<'_>) -> Option<ColorBitmaps> {
+    let num_glyphs = font.metrics(&[]).glyph_count;
+    let sbix = Sbix::read_with_args(FontData::new(font.table(SBIX)?), &num_glyphs).ok()?;
+
+    let mut strikes = Vec::new();
+    let mut glyph_ids = GlyphSet::new();
+    for strike in sbix.strikes().iter().filter_map(Result::ok) {
+        strikes.push(Strike {
+            ppem: strike.ppem(),
+            ppi: Some(strike.ppi()),
+        });
+
+        // a glyph has a bitmap if its data is not empty
+        let offsets = strike.glyph_data_offsets();
+        for (glyph_id, pair) in offsets.windows(2).enumerate() {
+            if pair[1].get() > pair[0].get() {
+                glyph_ids.insert(glyph_id as u16);
+            }
+        }
+    }
+
+    Some(ColorBitmaps {
+        strikes,
+        color_glyphs: glyph_ids.len(),
+    })
+}
+
+fn cbdt(font: FontRef<'_>) -> Option<ColorBitmaps> {
+    let cblc = read_table::<Cblc>(font)?;
+    // the bitmap data itself is not needed, but its absence means the strikes are unusable
+    read_table::<Cbdt>(font)?;
+    let last_glyph_id = font.metrics(&[]).glyph_count.checked_sub(1)?;
+
+    let mut strikes = Vec::new();
+    let mut glyph_ids = GlyphSet::new();
+    for size in cblc.bitmap_sizes() {
+        strikes.push(Strike {
+            ppem: u16::from(size.ppem_y()),
+            ppi: None,
+        });
+
+        let (start, end) = (size.start_glyph_index(), size.end_glyph_index());
+        for glyph_id in start.to_u16()..=end.to_u16().min(last_glyph_id) {
+            let has_bitmap = size
+                .location(cblc.offset_data(), glyph_id.into())
+                .is_ok_and(|location| !location.is_empty());
+            if has_bitmap {
+                glyph_ids.insert(glyph_id);
+            }
+        }
+    }
+
+    Some(ColorBitmaps {
+        strikes,
+        color_glyphs: glyph_ids.len(),
+    })
+}
+
+/// read-fonts does not parse the `SVG ` table, so its document index is read from the raw table.
+/// See the OpenType specification of the `SVG ` table.
+fn svg(font: FontRef<'_>) -> Option<SvgDocuments> {
+    let table = font.table(SVG)?;
+    let last_glyph_id = font.metrics(&[]).glyph_count.checked_sub(1)?;
+
+    let list = read_u32(table, 2)? as usize;
+    let num_entries = usize::from(read_u16(table, list)?);
+
+    let mut documents = Vec::with_capacity(num_entries);
+    let mut glyph_ids = GlyphSet::new();
+    for idx in 0..num_entries {
+        let record = list + 2 + idx * 12;
+        let start_glyph_id = read_u16(table, record)?;
+        let end_glyph_id = read_u16(table, record + 2)?;
+        // multiple records may share a document
+        documents.push(read_u32(table, record + 4)?);
+        glyph_ids.insert_range(start_glyph_id..=end_glyph_id.min(last_glyph_id));
+    }
+    documents.sort_unstable();
+    documents.dedup();
+
+    Some(SvgDocuments {
+        documents: documents.len(),
+        color_glyphs: glyph_ids.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fonts::{name_table, Data, TestFont};
+
+    /// Get the color font technologies of a font with glyphs for 'a' and 'b' and the given
+    /// tables.
+    fn color(tables: &[(&[u8; 4], Vec<u8>)]) -> Color {
+        let mut font = TestFont::new(&[('a', 500), ('b', 500)]);
+        for (tag, data) in tables {
+            font = font.table(tag, data.clone());
+        }
+        let data = font.build();
+        Color::from_font(FontRef::from_index(&data, 0).unwrap(), None)
+    }
+
+    #[test]
+    fn test_glyph_set() {
+        let mut glyph_ids = GlyphSet::new();
+        glyph_ids.insert(3);
+        glyph_ids.insert_range(0..=63);
+        assert_eq!(glyph_ids.len(), 64);
+        glyph_ids.insert_range(60..=130);
+        assert_eq!(glyph_ids.len(), 131);
+        // records of crafted tables may end before they start
+        let (start, end) = (200, 100);
+        glyph_ids.insert_range(start..=end);
+        assert_eq!(glyph_ids.len(), 131);
+
+        let mut glyph_ids = GlyphSet::new();
+        for _ in 0..1000 {
+            glyph_ids.insert_range(0..=u16::MAX);
+        }
+        assert_eq!(glyph_ids.len(), 65536);
+    }
+
+    #[test]
+    fn test_colr() {
+        // base glyphs 1 and 2, with one and two layers
+        let colr = Data::default()
+            .u16(0)
+            .u16(2)
+            .u32(14)
+            .u32(26)
+            .u16(3)
+            .u16(1)
+            .u16(0)
+            .u16(1)
+            .u16(2)
+            .u16(1)
+            .u16(2)
+            .u16(1)
+            .u16(0)
+            .u16(2)
+            .u16(1)
+            .u16(2)
+            .u16(0);
+        let color = color(&[(b"COLR", colr.0)]);
+        let colr = color.colr.unwrap();
+        assert_eq!((colr.version, colr.color_glyphs), (0, 2));
+        assert!(color.sbix.is_none() && color.cbdt.is_none() && color.svg.is_none());
+    }
+
+    #[test]
+    fn test_palettes() {
+        // a version 1 CPAL table with two palettes of two colors, with types and labels
+        let cpal = Data::default()
+            .u16(1)
+            .u16(2)
+            .u16(2)
+            .u16(4)
+            .u32(40)
+            .u16(0)
+            .u16(2)
+            .u32(28)
+            .u32(36)
+            .u32(0)
+            .u32(0x0001)
+            .u32(0x0002)
+            .u16(256)
+            .u16(0xFFFF)
+            .bytes(&[0x00, 0x00, 0xFF, 0xFF])
+            .bytes(&[0x00, 0xFF, 0x00, 0x80])
+            .bytes(&[0xFF, 0xFF, 0xFF, 0xFF])
+            .bytes(&[0x10, 0x20, 0x30, 0xFF]);
+        let name = name_table(&[(0x409, 256, "Light")]);
+        let palettes: Vec<_> = color(&[(b"CPAL", cpal.0), (b"name", name)])
+            .palettes
+            .into_iter()
+            .map(|palette| {
+                (
+                    palette.index,
+                    palette.name,
+                    palette.usable_with_light_background,
+                    palette.usable_with_dark_background,
+                    palette.colors,
+                )
+            })
+            .collect();
+        assert_eq!(
+            palettes,
+            [
+                (
+                    0,
+                    Some("Light".to_owned()),
+                    true,
+                    false,
+                    vec!["#FF0000FF".to_owned(), "#00FF0080".to_owned()]
+                ),
+                (
+                    1,
+                    None,
+                    false,
+                    true,
+                    vec!["#FFFFFFFF".to_owned(), "#302010FF".to_owned()]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sbix() {
+        // strikes with bitmaps for glyph 1, and for glyphs 1 and 2
+        let sbix = Data::default()
+            .u16(1)
+            .u16(1)
+            .u32(2)
+            .u32(16)
+            .u32(52)
+            // strike of 20 ppem at 72 ppi
+            .u16(20)
+            .u16(72)
+            .u32(20)
+            .u32(20)
+            .u32(36)
+            .u32(36)
+            .u16(0)
+            .u16(0)
+            .tag(b"png ")
+            .bytes(&[0x89; 8])
+            // strike of 40 ppem at 144 ppi
+            .u16(40)
+            .u16(144)
+            .u32(20)
+            .u32(20)
+            .u32(36)
+            .u32(52)
+            .u16(0)
+            .u16(0)
+            .tag(b"png ")
+            .bytes(&[0x89; 8])
+            .u16(0)
+            .u16(0)
+            .tag(b"png ")
+            .bytes(&[0x89; 8]);
+        let sbix = color(&[(b"sbix", sbix.0)]).sbix.unwrap();
+        let strikes: Vec<_> = sbix
+            .strikes
+            .iter()
+            .map(|strike| (strike.ppem, strike.ppi))
+            .collect();
+        assert_eq!(strikes, [(20, Some(72)), (40, Some(144))]);
+        assert_eq!(sbix.color_glyphs, 2);
+    }
+
+    #[test]
+    fn test_cbdt() {
+        // a strike of glyphs 1 and 2, of which glyph 2 has an empty bitmap
+        let cblc = Data::default()
+            .u16(3)
+            .u16(0)
+            .u32(1)
+            // BitmapSize record
+            .u32(56)
+            .u32(28)
+            .u32(1)
+            .u32(0)
+            .bytes(&[0; 24])
+            .u16(1)
+            .u16(2)
+            .bytes(&[109, 109, 32, 0x01])
+            // IndexSubtableArray record and IndexSubtable format 1
+            .u16(1)
+            .u16(2)
+            .u32(8)
+            .u16(1)
+            .u16(17)
+            .u32(4)
+            .u32(0)
+            .u32(10)
+            .u32(10);
+        let cbdt = Data::default().u16(3).u16(0).bytes(&[0; 10]);
+
+        let cbdt = color(&[(b"CBLC", cblc.0.clone()), (b"CBDT", cbdt.0)])
+            .cbdt
+            .unwrap();
+        let strikes: Vec<_> = cbdt
+            .strikes
+            .iter()
+            .map(|strike| (strike.ppem, strike.ppi))
+            .collect();
+        assert_eq!(strikes, [(109, None)]);
+        assert_eq!(cbdt.color_glyphs, 1);
+
+        // the strikes are unusable without the bitmap data
+        assert!(color(&[(b"CBLC", cblc.0)]).cbdt.is_none());
+    }
+
+    #[test]
+    fn test_svg() {
+        // glyph 0 has a document of its own, and glyph 1 and glyphs 2 to 5 share a document,
+        // of which only the glyphs up to the last glyph 2 are counted
+        let svg = Data::default()
+            .u16(0)
+            .u32(10)
+            .u32(0)
+            .u16(3)
+            .u16(0)
+            .u16(0)
+            .u32(38)
+            .u32(6)
+            .u16(1)
+            .u16(1)
+            .u32(44)
+            .u32(6)
+            .u16(2)
+            .u16(5)
+            .u32(44)
+            .u32(6)
+            .bytes(b"<svg/>")
+            .bytes(b"<svg/>");
+        let svg = color(&[(b"SVG ", svg.0)]).svg.unwrap();
+        assert_eq!((svg.documents, svg.color_glyphs), (2, 3));
+    }
+}
@@ -0,0 +1,730 @@
+use std::{collections::HashMap, sync::OnceLock};
+
+use swash::{
+    text::{Block, Category, Codepoint, Script},
+    FontRef,
+};
+
+/// An inclusive range of codepoints.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CodepointRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// The coverage of a Unicode block or script.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GroupCoverage {
+    pub name: String,
+    /// The number of codepoints in the group that are mapped by the font.
+    pub covered: usize,
+    /// The number of assigned codepoints in the group.
+    pub total: usize,
+}
+
+impl GroupCoverage {
+    pub fn percentage(&self) -> f32 {
+        if self.total == 0 {
+            0.
+        } else {
+            self.covered as f32 / self.total as f32 * 100.
+        }
+    }
+}
+
+/// The Unicode coverage of a font's character map.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Coverage {
+    /// The number of codepoints mapped by the font.
+    pub codepoints: usize,
+    pub ranges: Vec<CodepointRange>,
+    /// Coverage per Unicode block, in codepoint order. Only blocks with at least one covered
+    /// codepoint are included.
+    pub blocks: Vec<GroupCoverage>,
+    /// Coverage per Unicode script, ordered by the first codepoint of the script. Only scripts
+    /// with at least one covered codepoint are included.
+    pub scripts: Vec<GroupCoverage>,
+}
+
+impl Coverage {
+    pub fn from_font(font: FontRef<'_>) -> Self {
+        let codepoints = mapped_codepoints(font);
+        let totals = UnicodeTotals::get();
+
+        let mut blocks = vec![0; totals.blocks.len()];
+        let mut scripts = vec![0; totals.scripts.len()];
+        for ch in codepoints.iter().filter_map(|&c| char::from_u32(c)) {
+            if !is_assigned(ch) {
+                continue;
+            }
+            blocks[totals.block_indices[&ch.block()]] += 1;
+            scripts[totals.script_indices[&ch.script()]] += 1;
+        }
+
+        Coverage {
+            codepoints: codepoints.len(),
+            ranges: ranges(&codepoints),
+            blocks: totals
+                .blocks
+                .iter()
+                .zip(blocks)
+                .filter(|(_, covered)| *covered > 0)
+                .map(|(&(block, total), covered)| GroupCoverage {
+                    name: block_name(block).to_owned(),
+                    covered,
+                    total,
+                })
+                .collect(),
+            scripts: totals
+                .scripts
+                .iter()
+                .zip(scripts)
+                .filter(|(_, covered)| *covered > 0)
+                .map(|(&(script, total), covered)| GroupCoverage {
+                    name: script.name().to_owned(),
+                    covered,
+                    total,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Write the Unicode coverage of a font in the human-readable format.
+pub(crate) fn write_coverage(
+    mut write: impl std::io::Write,
+    coverage: &Coverage,
+) -> std::io::Result<()> {
+    writeln!(
+        write,
+        "            Coverage: {} codepoints in {} ranges",
+        coverage.codepoints,
+        coverage.ranges.len()
+    )?;
+
+    for (label, groups) in [
+        ("      Unicode blocks: ", &coverage.blocks),
+        ("     Unicode scripts: ", &coverage.scripts),
+    ] {
+        write!(write, "{label}")?;
+        for (idx, group) in groups.iter().enumerate() {
+            if idx > 0 {
+                write!(write, "\n                      ")?;
+            }
+            write!(
+                write,
+                "{}: {} of {} ({:.1}%)",
+                group.name,
+                group.covered,
+                group.total,
+                group.percentage()
+            )?;
+        }
+        writeln!(write)?;
+    }
+
+    Ok(())
+}
+
+/// Get the sorted codepoints that are mapped to a glyph other than `.notdef` by the font's
+/// character map.
+pub(crate) fn mapped_codepoints(font: FontRef<'_>) -> Vec<u32> {
+    let mut codepoints = Vec::new();
+    font.charmap().enumerate(|codepoint, glyph_id| {
+        if glyph_id != 0 {
+            codepoints.push(codepoint);
+        }
+    });
+    codepoints.sort_unstable();
+    codepoints.dedup();
+    codepoints
+}
+
+/// Collapse sorted, deduplicated codepoints into inclusive ranges.
+pub(crate) fn ranges(codepoints: &[u32]) -> Vec<CodepointRange> {
+    let mut ranges: Vec<CodepointRange> = Vec::new();
+    for &codepoint in codepoints {
+        match ranges.last_mut() {
+            Some(range) if range.end + 1 == codepoint => range.end = codepoint,
+            _ => ranges.push(CodepointRange {
+                start: codepoint,
+                end: codepoint,
+            }),
+        }
+    }
+    ranges
+}
+
+/// Get the name of a Unicode block, as in the Unicode Character Database's Blocks.txt.
+fn block_name(block: Block) -> &'static str {
+    match block {
+        Block::Adlam => "Adlam",
+        Block::AegeanNumbers => "Aegean Numbers",
+        Block::Ahom => "Ahom",
+        Block::AlchemicalSymbols => "Alchemical Symbols",
+        Block::AlphabeticPresentationForms => "Alphabetic Presentation Forms",
+        Block::AnatolianHieroglyphs => "Anatolian Hieroglyphs",
+        Block::AncientGreekMusicalNotation => "Ancient Greek Musical Notation",
+        Block::AncientGreekNumbers => "Ancient Greek Numbers",
+        Block::AncientSymbols => "Ancient Symbols",
+        Block::Arabic => "Arabic",
+        Block::ArabicExtendedA => "Arabic Extended-A",
+        Block::ArabicMathematicalAlphabeticSymbols => "Arabic Mathematical Alphabetic Symbols",
+        Block::ArabicPresentationFormsA => "Arabic Presentation Forms-A",
+        Block::ArabicPresentationFormsB => "Arabic Presentation Forms-B",
+        Block::ArabicSupplement => "Arabic Supplement",
+        Block::Armenian => "Armenian",
+        Block::Arrows => "Arrows",
+        Block::Avestan => "Avestan",
+        Block::Balinese => "Balinese",
+        Block::Bamum => "Bamum",
+        Block::BamumSupplement => "Bamum Supplement",
+        Block::BasicLatin => "Basic Latin",
+        Block::BassaVah => "Bassa Vah",
+        Block::Batak => "Batak",
+        Block::Bengali => "Bengali",
+        Block::Bhaiksuki => "Bhaiksuki",
+        Block::BlockElements => "Block Elements",
+        Block::Bopomofo => "Bopomofo",
+        Block::BopomofoExtended => "Bopomofo Extended",
+        Block::BoxDrawing => "Box Drawing",
+        Block::Brahmi => "Brahmi",
+        Block::BraillePatterns => "Braille Patterns",
+        Block::Buginese => "Buginese",
+        Block::Buhid => "Buhid",
+        Block::ByzantineMusicalSymbols => "Byzantine Musical Symbols",
+        Block::Carian => "Carian",
+        Block::CaucasianAlbanian => "Caucasian Albanian",
+        Block::Chakma => "Chakma",
+        Block::Cham => "Cham",
+        Block::Cherokee => "Cherokee",
+        Block::CherokeeSupplement => "Cherokee Supplement",
+        Block::ChessSymbols => "Chess Symbols",
+        Block::Chorasmian => "Chorasmian",
+        Block::CJKCompatibility => "CJK Compatibility",
+        Block::CJKCompatibilityForms => "CJK Compatibility Forms",
+        Block::CJKCompatibilityIdeographs => "CJK Compatibility Ideographs",
+        Block::CJKCompatibilityIdeographsSupplement => "CJK Compatibility Ideographs Supplement",
+        Block::CJKRadicalsSupplement => "CJK Radicals Supplement",
+        Block::CJKStrokes => "CJK Strokes",
+        Block::CJKSymbolsandPunctuation => "CJK Symbols and Punctuation",
+        Block::CJKUnifiedIdeographs => "CJK Unified Ideographs",
+        Block::CJKUnifiedIdeographsExtensionA => "CJK Unified Ideographs Extension A",
+        Block::CJKUnifiedIdeographsExtensionB => "CJK Unified Ideographs Extension B",
+        Block::CJKUnifiedIdeographsExtensionC => "CJK Unified Ideographs Extension C",
+        Block::CJKUnifiedIdeographsExtensionD => "CJK Unified Ideographs Extension D",
+        Block::CJKUnifiedIdeographsExtensionE => "CJK Unified Ideographs Extension E",
+        Block::CJKUnifiedIdeographsExtensionF => "CJK Unified Ideographs Extension F",
+        Block::CJKUnifiedIdeographsExtensionG => "CJK Unified Ideographs Extension G",
+        Block::CombiningDiacriticalMarks => "Combining Diacritical Marks",
+        Block::CombiningDiacriticalMarksExtended => "Combining Diacritical Marks Extended",
+        Block::CombiningDiacriticalMarksforSymbols => "Combining Diacritical Marks for Symbols",
+        Block::CombiningDiacriticalMarksSupplement => "Combining Diacritical Marks Supplement",
+        Block::CombiningHalfMarks => "Combining Half Marks",
+        Block::CommonIndicNumberForms => "Common Indic Number Forms",
+        Block::ControlPictures => "Control Pictures",
+        Block::Coptic => "Coptic",
+        Block::CopticEpactNumbers => "Coptic Epact Numbers",
+        Block::CountingRodNumerals => "Counting Rod Numerals",
+        Block::Cuneiform => "Cuneiform",
+        Block::CuneiformNumbersandPunctuation => "Cuneiform Numbers and Punctuation",
+        Block::CurrencySymbols => "Currency Symbols",
+        Block::CypriotSyllabary => "Cypriot Syllabary",
+        Block::Cyrillic => "Cyrillic",
+        Block::CyrillicExtendedA => "Cyrillic Extended-A",
+        Block::CyrillicExtendedB => "Cyrillic Extended-B",
+        Block::CyrillicExtendedC => "Cyrillic Extended-C",
+        Block::CyrillicSupplement => "Cyrillic Supplement",
+        Block::Deseret => "Deseret",
+        Block::Devanagari => "Devanagari",
+        Block::DevanagariExtended => "Devanagari Extended",
+        Block::Dingbats => "Dingbats",
+        Block::DivesAkuru => "Dives Akuru",
+        Block::Dogra => "Dogra",
+        Block::DominoTiles => "Domino Tiles",
+        Block::Duployan => "Duployan",
+        Block::EarlyDynasticCuneiform => "Early Dynastic Cuneiform",
+        Block::EgyptianHieroglyphFormatControls => "Egyptian Hieroglyph Format Controls",
+        Block::EgyptianHieroglyphs => "Egyptian Hieroglyphs",
+        Block::Elbasan => "Elbasan",
+        Block::Elymaic => "Elymaic",
+        Block::Emoticons => "Emoticons",
+        Block::EnclosedAlphanumericSupplement => "Enclosed Alphanumeric Supplement",
+        Block::EnclosedAlphanumerics => "Enclosed Alphanumerics",
+        Block::EnclosedCJKLettersandMonths => "Enclosed CJK Letters and Months",
+        Block::EnclosedIdeographicSupplement => "Enclosed Ideographic Supplement",
+        Block::Ethiopic => "Ethiopic",
+        Block::EthiopicExtended => "Ethiopic Extended",
+        Block::EthiopicExtendedA => "Ethiopic Extended-A",
+        Block::EthiopicSupplement => "Ethiopic Supplement",
+        Block::GeneralPunctuation => "General Punctuation",
+        Block::GeometricShapes => "Geometric Shapes",
+        Block::GeometricShapesExtended => "Geometric Shapes Extended",
+        Block::Georgian => "Georgian",
+        Block::GeorgianExtended => "Georgian Extended",
+        Block::GeorgianSupplement => "Georgian Supplement",
+        Block::Glagolitic => "Glagolitic",
+        Block::GlagoliticSupplement => "Glagolitic Supplement",
+        Block::Gothic => "Gothic",
+        Block::Grantha => "Grantha",
+        Block::GreekandCoptic => "Greek and Coptic",
+        Block::GreekExtended => "Greek Extended",
+        Block::Gujarati => "Gujarati",
+        Block::GunjalaGondi => "Gunjala Gondi",
+        Block::Gurmukhi => "Gurmukhi",
+        Block::HalfwidthandFullwidthForms => "Halfwidth and Fullwidth Forms",
+        Block::HangulCompatibilityJamo => "Hangul Compatibility Jamo",
+        Block::HangulJamo => "Hangul Jamo",
+        Block::HangulJamoExtendedA => "Hangul Jamo Extended-A",
+        Block::HangulJamoExtendedB => "Hangul Jamo Extended-B",
+        Block::HangulSyllables => "Hangul Syllables",
+        Block::HanifiRohingya => "Hanifi Rohingya",
+        Block::Hanunoo => "Hanunoo",
+        Block::Hatran => "Hatran",
+        Block::Hebrew => "Hebrew",
+        Block::HighPrivateUseSurrogates => "High Private Use Surrogates",
+        Block::HighSurrogates => "High Surrogates",
+        Block::Hiragana => "Hiragana",
+        Block::IdeographicDescriptionCharacters => "Ideographic Description Characters",
+        Block::IdeographicSymbolsandPunctuation => "Ideographic Symbols and Punctuation",
+        Block::ImperialAramaic => "Imperial Aramaic",
+        Block::IndicSiyaqNumbers => "Indic Siyaq Numbers",
+        Block::InscriptionalPahlavi => "Inscriptional Pahlavi",
+        Block::InscriptionalParthian => "Inscriptional Parthian",
+        Block::IPAExtensions => "IPA Extensions",
+        Block::Javanese => "Javanese",
+        Block::Kaithi => "Kaithi",
+        Block::KanaExtendedA => "Kana Extended-A",
+        Block::KanaSupplement => "Kana Supplement",
+        Block::Kanbun => "Kanbun",
+        Block::KangxiRadicals => "Kangxi Radicals",
+        Block::Kannada => "Kannada",
+        Block::Katakana => "Katakana",
+        Block::KatakanaPhoneticExtensions => "Katakana Phonetic Extensions",
+        Block::KayahLi => "Kayah Li",
+        Block::Kharoshthi => "Kharoshthi",
+        Block::KhitanSmallScript => "Khitan Small Script",
+        Block::Khmer => "Khmer",
+        Block::KhmerSymbols => "Khmer Symbols",
+        Block::Khojki => "Khojki",
+        Block::Khudawadi => "Khudawadi",
+        Block::Lao => "Lao",
+        Block::LatinExtendedAdditional => "Latin Extended Additional",
+        Block::LatinExtendedA => "Latin Extended-A",
+        Block::LatinExtendedB => "Latin Extended-B",
+        Block::LatinExtendedC => "Latin Extended-C",
+        Block::LatinExtendedD => "Latin Extended-D",
+        Block::LatinExtendedE => "Latin Extended-E",
+        Block::Latin1Supplement => "Latin-1 Supplement",
+        Block::Lepcha => "Lepcha",
+        Block::LetterlikeSymbols => "Letterlike Symbols",
+        Block::Limbu => "Limbu",
+        Block::LinearA => "Linear A",
+        Block::LinearBIdeograms => "Linear B Ideograms",
+        Block::LinearBSyllabary => "Linear B Syllabary",
+        Block::Lisu => "Lisu",
+        Block::LisuSupplement => "Lisu Supplement",
+        Block::LowSurrogates => "Low Surrogates",
+        Block::Lycian => "Lycian",
+        Block::Lydian => "Lydian",
+        Block::Mahajani => "Mahajani",
+        Block::MahjongTiles => "Mahjong Tiles",
+        Block::Makasar => "Makasar",
+        Block::Malayalam => "Malayalam",
+        Block::Mandaic => "Mandaic",
+        Block::Manichaean => "Manichaean",
+        Block::Marchen => "Marchen",
+        Block::MasaramGondi => "Masaram Gondi",
+        Block::MathematicalAlphanumericSymbols => "Mathematical Alphanumeric Symbols",
+        Block::MathematicalOperators => "Mathematical Operators",
+        Block::MayanNumerals => "Mayan Numerals",
+        Block::Medefaidrin => "Medefaidrin",
+        Block::MeeteiMayek => "Meetei Mayek",
+        Block::MeeteiMayekExtensions => "Meetei Mayek Extensions",
+        Block::MendeKikakui => "Mende Kikakui",
+        Block::MeroiticCursive => "Meroitic Cursive",
+        Block::MeroiticHieroglyphs => "Meroitic Hieroglyphs",
+        Block::Miao => "Miao",
+        Block::MiscellaneousMathematicalSymbolsA => "Miscellaneous Mathematical Symbols-A",
+        Block::MiscellaneousMathematicalSymbolsB => "Miscellaneous Mathematical Symbols-B",
+        Block::MiscellaneousSymbols => "Miscellaneous Symbols",
+        Block::MiscellaneousSymbolsandArrows => "Miscellaneous Symbols and Arrows",
+        Block::MiscellaneousSymbolsandPictographs => "Miscellaneous Symbols and Pictographs",
+        Block::MiscellaneousTechnical => "Miscellaneous Technical",
+        Block::Modi => "Modi",
+        Block::ModifierToneLetters => "Modifier Tone Letters",
+        Block::Mongolian => "Mongolian",
+        Block::MongolianSupplement => "Mongolian Supplement",
+        Block::Mro => "Mro",
+        Block::Multani => "Multani",
+        Block::MusicalSymbols => "Musical Symbols",
+        Block::Myanmar => "Myanmar",
+        Block::MyanmarExtendedA => "Myanmar Extended-A",
+        Block::MyanmarExtendedB => "Myanmar Extended-B",
+        Block::Nabataean => "Nabataean",
+        Block::Nandinagari => "Nandinagari",
+        Block::NewTaiLue => "New Tai Lue",
+        Block::Newa => "Newa",
+        Block::NKo => "NKo",
+        Block::NoBlock => "No Block",
+        Block::NumberForms => "Number Forms",
+        Block::Nushu => "Nushu",
+        Block::NyiakengPuachueHmong => "Nyiakeng Puachue Hmong",
+        Block::Ogham => "Ogham",
+        Block::OlChiki => "Ol Chiki",
+        Block::OldHungarian => "Old Hungarian",
+        Block::OldItalic => "Old Italic",
+        Block::OldNorthArabian => "Old North Arabian",
+        Block::OldPermic => "Old Permic",
+        Block::OldPersian => "Old Persian",
+        Block::OldSogdian => "Old Sogdian",
+        Block::OldSouthArabian => "Old South Arabian",
+        Block::OldTurkic => "Old Turkic",
+        Block::OpticalCharacterRecognition => "Optical Character Recognition",
+        Block::Oriya => "Oriya",
+        Block::OrnamentalDingbats => "Ornamental Dingbats",
+        Block::Osage => "Osage",
+        Block::Osmanya => "Osmanya",
+        Block::OttomanSiyaqNumbers => "Ottoman Siyaq Numbers",
+        Block::PahawhHmong => "Pahawh Hmong",
+        Block::Palmyrene => "Palmyrene",
+        Block::PauCinHau => "Pau Cin Hau",
+        Block::Phagspa => "Phags-pa",
+        Block::PhaistosDisc => "Phaistos Disc",
+        Block::Phoenician => "Phoenician",
+        Block::PhoneticExtensions => "Phonetic Extensions",
+        Block::PhoneticExtensionsSupplement => "Phonetic Extensions Supplement",
+        Block::PlayingCards => "Playing Cards",
+        Block::PrivateUseArea => "Private Use Area",
+        Block::PsalterPahlavi => "Psalter Pahlavi",
+        Block::Rejang => "Rejang",
+        Block::RumiNumeralSymbols => "Rumi Numeral Symbols",
+        Block::Runic => "Runic",
+        Block::Samaritan => "Samaritan",
+        Block::Saurashtra => "Saurashtra",
+        Block::Sharada => "Sharada",
+        Block::Shavian => "Shavian",
+        Block::ShorthandFormatControls => "Shorthand Format Controls",
+        Block::Siddham => "Siddham",
+        Block::Sinhala => "Sinhala",
+        Block::SinhalaArchaicNumbers => "Sinhala Archaic Numbers",
+        Block::SmallFormVariants => "Small Form Variants",
+        Block::SmallKanaExtension => "Small Kana Extension",
+        Block::Sogdian => "Sogdian",
+        Block::SoraSompeng => "Sora Sompeng",
+        Block::Soyombo => "Soyombo",
+        Block::SpacingModifierLetters => "Spacing Modifier Letters",
+        Block::Specials => "Specials",
+        Block::Sundanese => "Sundanese",
+        Block::SundaneseSupplement => "Sundanese Supplement",
+        Block::SuperscriptsandSubscripts => "Superscripts and Subscripts",
+        Block::SupplementalArrowsA => "Supplemental Arrows-A",
+        Block::SupplementalArrowsB => "Supplemental Arrows-B",
+        Block::SupplementalArrowsC => "Supplemental Arrows-C",
+        Block::SupplementalMathematicalOperators => "Supplemental Mathematical Operators",
+        Block::SupplementalPunctuation => "Supplemental Punctuation",
+        Block::SupplementalSymbolsandPictographs => "Supplemental Symbols and Pictographs",
+        Block::SupplementaryPrivateUseAreaA => "Supplementary Private Use Area-A",
+        Block::SupplementaryPrivateUseAreaB => "Supplementary Private Use Area-B",
+        Block::SuttonSignWriting => "Sutton SignWriting",
+        Block::SylotiNagri => "Syloti Nagri",
+        Block::SymbolsandPictographsExtendedA => "Symbols and Pictographs Extended-A",
+        Block::SymbolsforLegacyComputing => "Symbols for Legacy Computing",
+        Block::Syriac => "Syriac",
+        Block::SyriacSupplement => "Syriac Supplement",
+        Block::Tagalog => "Tagalog",
+        Block::Tagbanwa => "Tagbanwa",
+        Block::Tags => "Tags",
+        Block::TaiLe => "Tai Le",
+        Block::TaiTham => "Tai Tham",
+        Block::TaiViet => "Tai Viet",
+        Block::TaiXuanJingSymbols => "Tai Xuan Jing Symbols",
+        Block::Takri => "Takri",
+        Block::Tamil => "Tamil",
+        Block::TamilSupplement => "Tamil Supplement",
+        Block::Tangut => "Tangut",
+        Block::TangutComponents => "Tangut Components",
+        Block::TangutSupplement => "Tangut Supplement",
+        Block::Telugu => "Telugu",
+        Block::Thaana => "Thaana",
+        Block::Thai => "Thai",
+        Block::Tibetan => "Tibetan",
+        Block::Tifinagh => "Tifinagh",
+        Block::Tirhuta => "Tirhuta",
+        Block::TransportandMapSymbols => "Transport and Map Symbols",
+        Block::Ugaritic => "Ugaritic",
+        Block::UnifiedCanadianAboriginalSyllabics => "Unified Canadian Aboriginal Syllabics",
+        Block::UnifiedCanadianAboriginalSyllabicsExtended => {
+            "Unified Canadian Aboriginal Syllabics Extended"
+        }
+        Block::Vai => "Vai",
+        Block::VariationSelectors => "Variation Selectors",
+        Block::VariationSelectorsSupplement => "Variation Selectors Supplement",
+        Block::VedicExtensions => "Vedic Extensions",
+        Block::VerticalForms => "Vertical Forms",
+        Block::Wancho => "Wancho",
+        Block::WarangCiti => "Warang Citi",
+        Block::Yezidi => "Yezidi",
+        Block::YiRadicals => "Yi Radicals",
+        Block::YiSyllables => "Yi Syllables",
+        Block::YijingHexagramSymbols => "Yijing Hexagram Symbols",
+        Block::ZanabazarSquare => "Zanabazar Square",
+    }
+}
+
+/// Whether the codepoint is assigned a character. swash categorizes unassigned codepoints as
+/// [`Category::Other`].
+fn is_assigned(ch: char) -> bool {
+    !matches!(ch.category(), Category::Other | Category::Unassigned)
+}
+
+/// The number of assigned codepoints in each Unicode block and script.
+struct UnicodeTotals {
+    blocks: Vec<(Block, usize)>,
+    block_indices: HashMap<Block, usize>,
+    scripts: Vec<(Script, usize)>,
+    script_indices: HashMap<Script, usize>,
+}
+
+impl UnicodeTotals {
+    fn get() -> &'static Self {
+        static TOTALS: OnceLock<UnicodeTotals> = OnceLock::new();
+
+        TOTALS.get_or_init(|| {
+            let mut totals = UnicodeTotals {
+                blocks: Vec::new(),
+                block_indices: HashMap::new(),
+                scripts: Vec::new(),
+                script_indices: HashMap::new(),
+            };
+
+            for ch in (0..=char::MAX as u32).filter_map(char::from_u32) {
+                if !is_assigned(ch) {
+                    continue;
+                }
+
+                let block = ch.block();
+                let idx = *totals.block_indices.entry(block).or_insert_with(|| {
+                    totals.blocks.push((block, 0));
+                    totals.blocks.len() - 1
+                });
+                totals.blocks[idx].1 += 1;
+
+                let script = ch.script();
+                let idx = *totals.script_indices.entry(script).or_insert_with(|| {
+                    totals.scripts.push((script, 0));
+                    totals.scripts.len() - 1
+                });
+                totals.scripts[idx].1 += 1;
+            }
+
+            totals
+        })
+    }
+}
+
+/// A set of codepoints to check a font's coverage of.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Codepoints(pub Vec<u32>);
+
+impl Codepoints {
+    /// Get the codepoints of the characters in the text. Control characters, such as newlines,
+    /// are skipped.
+    pub fn from_text(text: &str) -> Self {
+        Codepoints(
+            text.chars()
+                .filter(|c| !c.is_control())
+                .map(u32::from)
+                .collect(),
+        )
+    }
+
+    /// Parse a comma or whitespace separated list of codepoints and codepoint ranges, such as
+    /// "U+0100-U+017F, U+20AC". Arguments not starting with "U+" are interpreted as text, of
+    /// which the characters are taken.
+    pub fn parse(arg: &str) -> Result<Self, String> {
+        let is_codepoint_list = arg
+            .get(..2)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("U+"));
+        if !is_codepoint_list {
+            return Ok(Self::from_text(arg));
+        }
+
+        let parse_codepoint = |codepoint: &str| -> Result<u32, String> {
+            let hex = codepoint
+                .get(..2)
+                .filter(|prefix| prefix.eq_ignore_ascii_case("U+"))
+                .map_or(codepoint, |_| &codepoint[2..]);
+            u32::from_str_radix(hex, 16)
+                .ok()
+                .filter(|&codepoint| codepoint <= char::MAX as u32)
+                .ok_or_else(|| format!("'{codepoint}' is not a valid codepoint"))
+        };
+
+        let mut codepoints = Vec::new();
+        for token in arg
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|token| !token.is_empty())
+        {
+            match token.split_once('-') {
+                Some((start, end)) => {
+                    let start = parse_codepoint(start)?;
+                    let end = parse_codepoint(end)?;
+                    if start > end {
+                        return Err(format!("'{token}' is not a valid codepoint range"));
+                    }
+                    codepoints.extend(start..=end);
+                }
+                None => codepoints.push(parse_codepoint(token)?),
+            }
+        }
+
+        Ok(Codepoints(codepoints))
+    }
+}
+
+/// A codepoint that is not mapped by a font's character map.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MissingCodepoint {
+    pub codepoint: u32,
+    /// The character, unless the codepoint is a surrogate.
+    pub character: Option<char>,
+}
+
+/// The result of checking whether a font covers a set of codepoints.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CoverageCheck {
+    /// The number of distinct codepoints checked.
+    pub requested: usize,
+    pub missing: Vec<MissingCodepoint>,
+}
+
+impl CoverageCheck {
+    /// Check the font covers the given sorted and deduplicated codepoints.
+    pub fn from_font(font: FontRef<'_>, codepoints: &[u32]) -> Self {
+        let charmap = font.charmap();
+        CoverageCheck {
+            requested: codepoints.len(),
+            missing: codepoints
+                .iter()
+                .filter(|&&codepoint| charmap.map(codepoint) == 0)
+                .map(|&codepoint| MissingCodepoint {
+                    codepoint,
+                    character: char::from_u32(codepoint),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn passed(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Write the result of a coverage check in the human-readable format, listing the missing
+/// codepoints.
+pub(crate) fn write_coverage_check(
+    mut write: impl std::io::Write,
+    check: &CoverageCheck,
+) -> std::io::Result<()> {
+    writeln!(
+        write,
+        "              Covers: {} of {} requested codepoints",
+        check.requested - check.missing.len(),
+        check.requested
+    )?;
+
+    if !check.missing.is_empty() {
+        write!(write, "  Missing codepoints: ")?;
+        for (idx, missing) in check.missing.iter().enumerate() {
+            if idx > 0 {
+                write!(write, "\n                      ")?;
+            }
+            write!(write, "U+{:04X}", missing.codepoint)?;
+            if let Some(c) = missing.character.filter(|c| !c.is_control()) {
+                write!(write, " {c}")?;
+            }
+        }
+        writeln!(write)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ranges() {
+        assert_eq!(
+            ranges(&[0x20, 0x21, 0x22, 0x41, 0x43, 0x44]),
+            [
+                CodepointRange {
+                    start: 0x20,
+                    end: 0x22
+                },
+                CodepointRange {
+                    start: 0x41,
+                    end: 0x41
+                },
+                CodepointRange {
+                    start: 0x43,
+                    end: 0x44
+                },
+            ]
+        );
+        assert!(ranges(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_parse_codepoints() {
+        assert_eq!(
+            Codepoints::parse("ab c"),
+            Ok(Codepoints(vec![0x61, 0x62, 0x20, 0x63]))
+        );
+        assert_eq!(
+            Codepoints::parse("U+0100-U+0102, u+20ac"),
+            Ok(Codepoints(vec![0x100, 0x101, 0x102, 0x20ac]))
+        );
+        assert_eq!(
+            Codepoints::parse("U+41-5A"),
+            Ok(Codepoints((0x41..=0x5a).collect()))
+        );
+        assert!(Codepoints::parse("U+017F-U+0100").is_err());
+        assert!(Codepoints::parse("U+110000").is_err());
+        assert!(Codepoints::parse("U+00G1").is_err());
+    }
+
+    #[test]
+    fn test_block_name() {
+        assert_eq!(block_name(Block::BasicLatin), "Basic Latin");
+        assert_eq!(block_name(Block::LatinExtendedA), "Latin Extended-A");
+        assert_eq!(block_name(Block::Latin1Supplement), "Latin-1 Supplement");
+        assert_eq!(
+            block_name(Block::ArabicPresentationFormsB),
+            "Arabic Presentation Forms-B"
+        );
+        assert_eq!(
+            block_name(Block::SupplementaryPrivateUseAreaA),
+            "Supplementary Private Use Area-A"
+        );
+        assert_eq!(block_name(Block::Phagspa), "Phags-pa");
+        assert_eq!(block_name(Block::NKo), "NKo");
+        assert_eq!(block_name(Block::IPAExtensions), "IPA Extensions");
+        assert_eq!(block_name(Block::GreekandCoptic), "Greek and Coptic");
+        assert_eq!(
+            block_name(Block::CombiningDiacriticalMarksforSymbols),
+            "Combining Diacritical Marks for Symbols"
+        );
+        assert_eq!(
+            block_name(Block::CJKUnifiedIdeographsExtensionB),
+            "CJK Unified Ideographs Extension B"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+use std::{io::Write, path::Path};
+
+use anyhow::Context;
+use swash::{FontRef, Tag};
+
+use crate::{settings::tag_to_string, Options, Out, Outcome};
+
+/// Get the raw bytes of a table of the font.
+fn table_data<'a>(font: FontRef<'a>, tag: Tag, source: &str) -> anyhow::Result<&'a [u8]> {
+    font.table(tag).ok_or_else(|| {
+        anyhow::anyhow!(
+            "The font in '{source}' has no '{}' table",
+            tag_to_string(tag)
+        )
+    })
+}
+
+/// Write the raw bytes of a table of the font to a file.
+pub(crate) fn write_table(
+    source: &str,
+    font_index: usize,
+    font: FontRef<'_>,
+    tag: Tag,
+    path: &Path,
+) -> anyhow::Result<()> {
+    let data = table_data(font, tag, source)?;
+    log::info!(
+        "Writing {} bytes of the '{}' table of font {font_index} of '{source}' to '{}'",
+        data.len(),
+        tag_to_string(tag),
+        path.to_string_lossy()
+    );
+    std::fs::write(path, data)
+        .with_context(|| format!("Failed writing table to '{}'", path.to_string_lossy()))
+}
+
+/// Prints a hex dump of a table of each font.
+pub(crate) struct HexDump<W> {
+    write: W,
+    tag: Tag,
+    options: Options,
+    /// the number of fonts already written
+    fonts_written: usize,
+}
+
+impl<W> HexDump<W> {
+    pub fn new(write: W, tag: Tag, options: Options) -> Self {
+        HexDump {
+            write,
+            tag,
+            options,
+            fonts_written: 0,
+        }
+    }
+}
+
+impl<W: Write> Out for HexDump<W> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        let data = table_data(font, self.tag, source)?;
+
+        self.fonts_written += 1;
+        crate::write_font_header(
+            &mut self.write,
+            self.fonts_written,
+            source,
+            font_index,
+            font,
+            self.options.name_language.as_deref(),
+        )?;
+        writeln!(
+            self.write,
+            "               Table: {} ({} bytes)",
+            tag_to_string(self.tag),
+            data.len()
+        )?;
+        hexdump(&mut self.write, data)?;
+
+        Ok(Outcome::Pass)
+    }
+}
+
+/// Write a hex dump of the data in the canonical hex and ASCII format of `hexdump -C`.
+fn hexdump(mut write: impl Write, data: &[u8]) -> std::io::Result<()> {
+    for (line, chunk) in data.chunks(16).enumerate() {
+        write!(write, "{:08x} ", line * 16)?;
+        for idx in 0..16 {
+            if idx % 8 == 0 {
+                write!(write, " ")?;
+            }
+            match chunk.get(idx) {
+                Some(byte) => write!(write, "{byte:02x} ")?,
+                None => write!(write, "   ")?,
+            }
+        }
+        write!(write, " |")?;
+        for &byte in chunk {
+            let c = if byte.is_ascii_graphic() || byte == b' ' {
+                byte as char
+            } else {
+                '.'
+            };
+            write!(write, "{c}")?;
+        }
+        writeln!(write, "|")?;
+    }
+    writeln!(write, "{:08x}", data.len())?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hexdump() {
+        let mut out = Vec::new();
+        hexdump(&mut out, b"\x00\x01OS/2 table data\xff").unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "00000000  00 01 4f 53 2f 32 20 74  61 62 6c 65 20 64 61 74  |..OS/2 table dat|\n\
+             00000010  61 ff                                             |a.|\n\
+             00000012\n"
+        );
+    }
+}
@@ -0,0 +1,388 @@
+use read_fonts::tables::{gpos::Gpos, gsub::Gsub, layout::FeatureParams};
+use swash::{FontRef, StringId, Tag};
+
+use crate::{names, settings::tag_to_string, tables::read_table};
+
+/// The features registered in the OpenType feature tag registry, except the numbered character
+/// variants and stylistic sets, with their names and descriptions.
+const REGISTRY: [(&[u8; 4], &str, &str); 124] = [
+    (b"aalt", "Access All Alternates", "Makes all variations of a selected character accessible."),
+    (b"abvf", "Above-base Forms", "Substitutes the above-base form of a vowel in Indic scripts."),
+    (b"abvm", "Above-base Mark Positioning", "Positions marks above base glyphs in Indic scripts."),
+    (b"abvs", "Above-base Substitutions", "Substitutes ligatures of base glyphs and above-base marks in Indic scripts."),
+    (b"afrc", "Alternative Fractions", "Replaces figures separated by a slash with alternative fraction forms, such as vertical fractions."),
+    (b"akhn", "Akhand", "Substitutes the preferred form of akhand ligatures in Indic scripts."),
+    (b"apkn", "Kerning for Alternate Proportional Widths", "Adjusts the spacing of glyphs with alternate proportional widths."),
+    (b"blwf", "Below-base Forms", "Substitutes the below-base form of a consonant in Indic scripts."),
+    (b"blwm", "Below-base Mark Positioning", "Positions marks below base glyphs in Indic scripts."),
+    (b"blws", "Below-base Substitutions", "Substitutes ligatures of base glyphs and below-base forms in Indic scripts."),
+    (b"c2pc", "Petite Capitals From Capitals", "Turns capital characters into petite capitals."),
+    (b"c2sc", "Small Capitals From Capitals", "Turns capital characters into small capitals."),
+    (b"calt", "Contextual Alternates", "Replaces glyphs with alternates that fit better with the surrounding glyphs."),
+    (b"case", "Case-Sensitive Forms", "Shifts punctuation and other glyphs to fit with all-capital text."),
+    (b"ccmp", "Glyph Composition / Decomposition", "Composes or decomposes characters into glyphs for better glyph handling."),
+    (b"cfar", "Conjunct Form After Ro", "Substitutes alternate forms of letters following the letter ro in Khmer."),
+    (b"chws", "Contextual Half-width Spacing", "Adjusts the spacing of CJK punctuation depending on the surrounding glyphs."),
+    (b"cjct", "Conjunct Forms", "Substitutes conjunct ligatures in Indic scripts."),
+    (b"clig", "Contextual Ligatures", "Replaces sequences of glyphs with ligatures depending on the surrounding glyphs."),
+    (b"cpct", "Centered CJK Punctuation", "Centers punctuation in fonts for Chinese, Japanese and Korean."),
+    (b"cpsp", "Capital Spacing", "Adds space between capital glyphs for all-capital text."),
+    (b"cswh", "Contextual Swash", "Replaces glyphs with swash forms depending on the surrounding glyphs."),
+    (b"curs", "Cursive Positioning", "Connects glyphs of cursive scripts such as Arabic."),
+    (b"dist", "Distances", "Adjusts the spacing of glyphs in Indic scripts."),
+    (b"dlig", "Discretionary Ligatures", "Replaces sequences of characters with optional decorative ligatures."),
+    (b"dnom", "Denominators", "Replaces figures with denominator forms."),
+    (b"dtls", "Dotless Forms", "Replaces letters with dotless forms for mathematical layout."),
+    (b"expt", "Expert Forms", "Replaces standard Japanese forms with expert forms."),
+    (b"falt", "Final Glyph on Line Alternates", "Replaces the final glyph on a line with an alternate form."),
+    (b"fin2", "Terminal Forms #2", "Substitutes alternate final forms in Syriac."),
+    (b"fin3", "Terminal Forms #3", "Substitutes alternate final forms in Syriac."),
+    (b"fina", "Terminal Forms", "Substitutes the forms of letters at the end of words."),
+    (b"flac", "Flattened Accent Forms", "Replaces accents with flatter forms for mathematical layout."),
+    (b"frac", "Fractions", "Replaces figures separated by a slash with diagonal fractions."),
+    (b"fwid", "Full Widths", "Replaces glyphs with full-width forms."),
+    (b"half", "Half Forms", "Substitutes the half forms of consonants in Indic scripts."),
+    (b"haln", "Halant Forms", "Substitutes the halant forms of consonants in Indic scripts."),
+    (b"halt", "Alternate Half Widths", "Adjusts full-width glyphs to half widths."),
+    (b"hist", "Historical Forms", "Replaces glyphs with historical forms, such as the long s."),
+    (b"hkna", "Horizontal Kana Alternates", "Replaces kana with forms designed for horizontal writing."),
+    (b"hlig", "Historical Ligatures", "Replaces sequences of characters with historical ligatures."),
+    (b"hngl", "Hangul", "Replaces hanja with the corresponding hangul."),
+    (b"hojo", "Hojo Kanji Forms", "Replaces kanji with the forms of the JIS X 0212-1990 standard."),
+    (b"hwid", "Half Widths", "Replaces glyphs with half-width forms."),
+    (b"init", "Initial Forms", "Substitutes the forms of letters at the start of words."),
+    (b"isol", "Isolated Forms", "Substitutes the forms of letters standing alone."),
+    (b"ital", "Italics", "Replaces glyphs with italic forms."),
+    (b"jalt", "Justification Alternates", "Replaces glyphs with wider or narrower alternates for justification."),
+    (b"jp04", "JIS2004 Forms", "Replaces kanji with the forms of the JIS X 0213:2004 standard."),
+    (b"jp78", "JIS78 Forms", "Replaces kanji with the forms of the JIS C 6226-1978 standard."),
+    (b"jp83", "JIS83 Forms", "Replaces kanji with the forms of the JIS X 0208-1983 standard."),
+    (b"jp90", "JIS90 Forms", "Replaces kanji with the forms of the JIS X 0208-1990 standard."),
+    (b"kern", "Kerning", "Adjusts the spacing between pairs of glyphs."),
+    (b"lfbd", "Left Bounds", "Aligns glyphs at the start of lines with the margin."),
+    (b"liga", "Standard Ligatures", "Replaces sequences of characters, such as \"fi\", with ligatures."),
+    (b"ljmo", "Leading Jamo Forms", "Substitutes the leading jamo forms of Korean syllables."),
+    (b"lnum", "Lining Figures", "Replaces figures with lining figures, which align with capitals."),
+    (b"locl", "Localized Forms", "Replaces glyphs with the forms preferred in the text's language."),
+    (b"ltra", "Left-to-right Alternates", "Replaces glyphs with forms for left-to-right text."),
+    (b"ltrm", "Left-to-right Mirrored Forms", "Replaces glyphs with mirrored forms for left-to-right text."),
+    (b"mark", "Mark Positioning", "Positions marks relative to base glyphs."),
+    (b"med2", "Medial Forms #2", "Substitutes alternate medial forms in Syriac."),
+    (b"medi", "Medial Forms", "Substitutes the forms of letters in the middle of words."),
+    (b"mgrk", "Mathematical Greek", "Replaces Greek letters with mathematical symbols."),
+    (b"mkmk", "Mark to Mark Positioning", "Positions marks relative to other marks."),
+    (b"mset", "Mark Positioning via Substitution", "Positions Arabic marks by substituting glyphs."),
+    (b"nalt", "Alternate Annotation Forms", "Replaces glyphs with annotation forms, such as circled figures."),
+    (b"nlck", "NLC Kanji Forms", "Replaces kanji with the forms recommended by the National Language Council."),
+    (b"nukt", "Nukta Forms", "Substitutes the nukta forms of consonants in Indic scripts."),
+    (b"numr", "Numerators", "Replaces figures with numerator forms."),
+    (b"onum", "Oldstyle Figures", "Replaces figures with oldstyle figures, which fit with lowercase text."),
+    (b"opbd", "Optical Bounds", "Aligns glyphs at the edges of lines with the margins."),
+    (b"ordn", "Ordinals", "Replaces letters following figures with ordinal forms."),
+    (b"ornm", "Ornaments", "Replaces the bullet and other characters with ornaments."),
+    (b"palt", "Proportional Alternate Widths", "Adjusts full-width glyphs to proportional widths."),
+    (b"pcap", "Petite Capitals", "Turns lowercase characters into petite capitals."),
+    (b"pkna", "Proportional Kana", "Replaces kana with proportional-width forms."),
+    (b"pnum", "Proportional Figures", "Replaces figures with proportional-width figures."),
+    (b"pref", "Pre-base Forms", "Substitutes the pre-base forms of consonants in Indic scripts."),
+    (b"pres", "Pre-base Substitutions", "Substitutes ligatures of pre-base forms in Indic scripts."),
+    (b"pstf", "Post-base Forms", "Substitutes the post-base forms of consonants in Indic scripts."),
+    (b"psts", "Post-base Substitutions", "Substitutes ligatures of post-base forms in Indic scripts."),
+    (b"pwid", "Proportional Widths", "Replaces glyphs with proportional-width forms."),
+    (b"qwid", "Quarter Widths", "Replaces glyphs with quarter-width forms."),
+    (b"rand", "Randomize", "Replaces glyphs with randomly chosen alternates."),
+    (b"rclt", "Required Contextual Alternates", "Replaces glyphs with alternates required by the surrounding glyphs."),
+    (b"rkrf", "Rakar Forms", "Substitutes rakar ligatures in Indic scripts."),
+    (b"rlig", "Required Ligatures", "Replaces sequences of characters with ligatures required by the script."),
+    (b"rphf", "Reph Form", "Substitutes the reph form of ra in Indic scripts."),
+    (b"rtbd", "Right Bounds", "Aligns glyphs at the end of lines with the margin."),
+    (b"rtla", "Right-to-left Alternates", "Replaces glyphs with forms for right-to-left text."),
+    (b"rtlm", "Right-to-left Mirrored Forms", "Replaces glyphs with mirrored forms for right-to-left text."),
+    (b"ruby", "Ruby Notation Forms", "Replaces glyphs with forms for ruby annotations."),
+    (b"rvrn", "Required Variation Alternates", "Replaces glyphs with alternates at certain variation coordinates."),
+    (b"salt", "Stylistic Alternates", "Replaces glyphs with stylistic alternates."),
+    (b"sinf", "Scientific Inferiors", "Replaces figures with scientific inferior forms, as in chemical formulas."),
+    (b"size", "Optical Size", "Provides the design size and the range of sizes the font is intended for."),
+    (b"smcp", "Small Capitals", "Turns lowercase characters into small capitals."),
+    (b"smpl", "Simplified Forms", "Replaces traditional Chinese or Japanese forms with simplified forms."),
+    (b"ssty", "Math Script-style Alternates", "Replaces glyphs with forms for superscripts and subscripts in mathematical layout."),
+    (b"stch", "Stretching Glyph Decomposition", "Decomposes glyphs that stretch to fit, as in Syriac."),
+    (b"subs", "Subscript", "Replaces glyphs with subscript forms."),
+    (b"sups", "Superscript", "Replaces glyphs with superscript forms."),
+    (b"swsh", "Swash", "Replaces glyphs with swash forms."),
+    (b"titl", "Titling", "Replaces glyphs with forms designed for large sizes."),
+    (b"tjmo", "Trailing Jamo Forms", "Substitutes the trailing jamo forms of Korean syllables."),
+    (b"tnam", "Traditional Name Forms", "Replaces kanji with the traditional forms used in names."),
+    (b"tnum", "Tabular Figures", "Replaces figures with tabular figures, which have the same width."),
+    (b"trad", "Traditional Forms", "Replaces simplified Chinese or Japanese forms with traditional forms."),
+    (b"twid", "Third Widths", "Replaces glyphs with third-width forms."),
+    (b"unic", "Unicase", "Replaces uppercase and lowercase glyphs with forms of a single case."),
+    (b"valt", "Alternate Vertical Metrics", "Adjusts glyphs to fit in vertical text."),
+    (b"vapk", "Kerning for Alternate Proportional Vertical Metrics", "Adjusts the vertical spacing of glyphs with proportional vertical metrics."),
+    (b"vatu", "Vattu Variants", "Substitutes vattu ligatures in Indic scripts."),
+    (b"vchw", "Vertical Contextual Half-width Spacing", "Adjusts the spacing of CJK punctuation in vertical text depending on the surrounding glyphs."),
+    (b"vert", "Vertical Alternates", "Replaces glyphs with forms for vertical text."),
+    (b"vhal", "Alternate Vertical Half Metrics", "Adjusts full-height glyphs to half heights in vertical text."),
+    (b"vjmo", "Vowel Jamo Forms", "Substitutes the vowel jamo forms of Korean syllables."),
+    (b"vkna", "Vertical Kana Alternates", "Replaces kana with forms designed for vertical writing."),
+    (b"vkrn", "Vertical Kerning", "Adjusts the vertical spacing between pairs of glyphs."),
+    (b"vpal", "Proportional Alternate Vertical Metrics", "Adjusts full-height glyphs to proportional heights in vertical text."),
+    (b"vrt2", "Vertical Alternates and Rotation", "Replaces glyphs with forms for vertical text, rotating them where needed."),
+    (b"vrtr", "Vertical Alternates for Rotation", "Replaces glyphs with forms rotated for vertical text."),
+    (b"zero", "Slashed Zero", "Replaces the zero with a slashed zero."),
+];
+
+/// Get the features of the font and whether they substitute, adjust or attach glyphs. swash omits
+/// features it does not know, such as most character variants, so the GSUB and GPOS features it
+/// omits follow its features.
+pub(crate) fn font_features(font: FontRef<'_>) -> Vec<(Tag, swash::Action)> {
+    let mut features: Vec<(Tag, swash::Action)> = font
+        .features()
+        .map(|feature| (feature.tag(), feature.action()))
+        .collect();
+
+    let feature_lists = [
+        (
+            read_table::<Gsub>(font).and_then(|gsub| gsub.feature_list().ok()),
+            swash::Action::Substitution,
+        ),
+        (
+            read_table::<Gpos>(font).and_then(|gpos| gpos.feature_list().ok()),
+            swash::Action::Adjustment,
+        ),
+    ];
+    for (feature_list, action) in feature_lists {
+        for record in feature_list.iter().flat_map(|list| list.feature_records()) {
+            let tag = u32::from_be_bytes(record.feature_tag().to_be_bytes());
+            if !features.iter().any(|&(listed, _)| listed == tag) {
+                features.push((tag, action));
+            }
+        }
+    }
+
+    features
+}
+
+/// The name and description of a feature in the OpenType feature tag registry.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct RegisteredFeature {
+    pub name: String,
+    pub description: &'static str,
+}
+
+/// Look up a feature tag in the OpenType feature tag registry.
+pub(crate) fn registered_feature(tag: Tag) -> Option<RegisteredFeature> {
+    let bytes = tag.to_be_bytes();
+    let number = || {
+        std::str::from_utf8(&bytes[2..])
+            .ok()
+            .filter(|digits| digits.bytes().all(|b| b.is_ascii_digit()))
+            .and_then(|digits| digits.parse::<u8>().ok())
+    };
+
+    match &bytes[..2] {
+        b"cv" => number()
+            .filter(|number| (1..=99).contains(number))
+            .map(|number| RegisteredFeature {
+                name: format!("Character Variant {number}"),
+                description: "Replaces specific characters with variant forms.",
+            }),
+        b"ss" => number()
+            .filter(|number| (1..=20).contains(number))
+            .map(|number| RegisteredFeature {
+                name: format!("Stylistic Set {number}"),
+                description: "Replaces a set of characters with stylistic variants.",
+            }),
+        _ => REGISTRY
+            .iter()
+            .find(|(registered, _, _)| **registered == bytes)
+            .map(|&(_, name, description)| RegisteredFeature {
+                name: name.to_owned(),
+                description,
+            }),
+    }
+}
+
+/// A font feature, with its registered name and the names and parameters the font provides for
+/// stylistic sets and character variants.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FeatureDescription {
+    pub tag: String,
+    /// The name in the OpenType feature tag registry.
+    pub registered_name: Option<String>,
+    pub description: Option<&'static str>,
+    /// The name of a stylistic set, or the label of a character variant, from the font.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ui_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tooltip: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sample_text: Option<String>,
+    /// The labels of the variants a character variant feature selects between.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub parameter_labels: Vec<String>,
+    /// The characters a character variant feature applies to.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub characters: Vec<char>,
+}
+
+impl FeatureDescription {
+    /// Describe the feature with the given tag. The feature parameters are read from the first
+    /// GSUB or GPOS feature record with the tag that has them.
+    pub fn from_font(font: FontRef<'_>, tag: Tag, language: Option<&str>) -> Self {
+        let registered = registered_feature(tag);
+        let mut description = FeatureDescription {
+            tag: tag_to_string(tag),
+            description: registered.as_ref().map(|registered| registered.description),
+            registered_name: registered.map(|registered| registered.name),
+            ui_name: None,
+            tooltip: None,
+            sample_text: None,
+            parameter_labels: Vec::new(),
+            characters: Vec::new(),
+        };
+
+        let name = |id: u16| {
+            (id != 0)
+                .then(|| names::localized_string(font, StringId::from_raw(id), language))
+                .flatten()
+                .map(|name| name.to_string())
+        };
+
+        let feature_lists = [
+            read_table::<Gsub>(font).and_then(|gsub| gsub.feature_list().ok()),
+            read_table::<Gpos>(font).and_then(|gpos| gpos.feature_list().ok()),
+        ];
+        let params = feature_lists.iter().flatten().find_map(|feature_list| {
+            feature_list
+                .feature_records()
+                .iter()
+                .filter(|record| u32::from_be_bytes(record.feature_tag().to_be_bytes()) == tag)
+                .filter_map(|record| record.feature(feature_list.offset_data()).ok())
+                .find_map(|feature| feature.feature_params()?.ok())
+        });
+
+        match params {
+            Some(FeatureParams::StylisticSet(params)) => {
+                description.ui_name = name(params.ui_name_id().to_u16());
+            }
+            Some(FeatureParams::CharacterVariant(params)) => {
+                description.ui_name = name(params.feat_ui_label_name_id().to_u16());
+                description.tooltip = name(params.feat_ui_tooltip_text_name_id().to_u16());
+                description.sample_text = name(params.sample_text_name_id().to_u16());
+                let first = params.first_param_ui_label_name_id().to_u16();
+                description.parameter_labels = (0..params.num_named_parameters())
+                    .filter_map(|idx| name(first.checked_add(idx)?))
+                    .collect();
+                description.characters = params
+                    .character()
+                    .iter()
+                    .filter_map(|c| char::from_u32(c.get().to_u32()))
+                    .collect();
+            }
+            Some(FeatureParams::Size(_)) | None => {}
+        }
+
+        description
+    }
+
+    /// The name to show users: the font's name for stylistic sets and character variants, else
+    /// the registered name.
+    pub fn display_name(&self) -> Option<&str> {
+        self.ui_name.as_deref().or(self.registered_name.as_deref())
+    }
+}
+
+/// Write the features of a font with their descriptions in the human-readable format. Names
+/// from the font are given in the preferred language, if available.
+pub(crate) fn write_features(
+    mut write: impl std::io::Write,
+    font: FontRef<'_>,
+    language: Option<&str>,
+) -> std::io::Result<()> {
+    write!(write, "            Features: ")?;
+    for (idx, (tag, action)) in font_features(font).into_iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        let description = FeatureDescription::from_font(font, tag, language);
+        write!(
+            write,
+            "{}: {} ({action:?}",
+            description.tag,
+            description.display_name().unwrap_or("unregistered")
+        )?;
+        if let (Some(_), Some(registered_name)) =
+            (&description.ui_name, &description.registered_name)
+        {
+            write!(write, ", {registered_name}")?;
+        }
+        write!(write, ")")?;
+        if let Some(text) = description.description {
+            write!(write, " - {text}")?;
+        }
+        let details = [
+            ("Tooltip", description.tooltip.clone()),
+            ("Sample text", description.sample_text.clone()),
+            (
+                "Variants",
+                (!description.parameter_labels.is_empty())
+                    .then(|| description.parameter_labels.join(", ")),
+            ),
+            (
+                "Characters",
+                (!description.characters.is_empty()).then(|| {
+                    description
+                        .characters
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(" ")
+                }),
+            ),
+        ];
+        for (label, value) in details {
+            if let Some(value) = value {
+                write!(write, "\n                            {label}: {value}")?;
+            }
+        }
+    }
+    writeln!(write)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registered_feature() {
+        let tag = |tag: &[u8; 4]| Tag::from_be_bytes(*tag);
+        assert_eq!(
+            registered_feature(tag(b"liga")).map(|feature| feature.name),
+            Some("Standard Ligatures".to_owned())
+        );
+        assert_eq!(
+            registered_feature(tag(b"zero")).map(|feature| feature.name),
+            Some("Slashed Zero".to_owned())
+        );
+        assert_eq!(
+            registered_feature(tag(b"ss02")).map(|feature| feature.name),
+            Some("Stylistic Set 2".to_owned())
+        );
+        assert_eq!(
+            registered_feature(tag(b"cv99")).map(|feature| feature.name),
+            Some("Character Variant 99".to_owned())
+        );
+        assert_eq!(registered_feature(tag(b"ss21")), None);
+        assert_eq!(registered_feature(tag(b"cv00")), None);
+        assert_eq!(registered_feature(tag(b"xxxx")), None);
+    }
+
+    #[test]
+    fn test_registry_is_sorted() {
+        assert!(REGISTRY.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+}
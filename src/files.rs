@@ -0,0 +1,302 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+/// The extensions of font files, compared case-insensitively.
+const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "ttc", "otc"];
+
+/// Whether the path has the extension of a font file.
+pub(crate) fn has_font_extension(path: &Path) -> bool {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .is_some_and(|extension| {
+            FONT_EXTENSIONS
+                .iter()
+                .any(|font_extension| extension.eq_ignore_ascii_case(font_extension))
+        })
+}
+
+/// The font files and directories given to a subcommand.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub(crate) struct PathArgs {
+    /// The font files or directories to inspect. Directories are searched for font files,
+    /// including their subdirectories.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Which files to find when searching directories for font files.
+#[derive(Debug, Default)]
+pub(crate) struct FileFilter {
+    /// The number of directory levels to search. 1 only finds the files directly in the
+    /// directory. Unlimited if `None`.
+    pub max_depth: Option<usize>,
+    /// Only find files matching any of these patterns. If empty, font files are found by their
+    /// extension.
+    pub include: Vec<glob::Pattern>,
+    /// Skip the files and directories matching any of these patterns.
+    pub exclude: Vec<glob::Pattern>,
+}
+
+impl FileFilter {
+    /// Whether a glob pattern matches the path relative to the searched directory. Patterns
+    /// without a path separator are matched against the file name only.
+    fn pattern_matches(pattern: &glob::Pattern, relative_path: &Path) -> bool {
+        let options = glob::MatchOptions {
+            case_sensitive: true,
+            require_literal_separator: true,
+            require_literal_leading_dot: false,
+        };
+        if pattern.as_str().contains('/') {
+            pattern.matches_path_with(relative_path, options)
+        } else {
+            relative_path
+                .file_name()
+                .is_some_and(|file_name| pattern.matches_path_with(Path::new(file_name), options))
+        }
+    }
+
+    fn is_excluded(&self, relative_path: &Path) -> bool {
+        self.exclude
+            .iter()
+            .any(|pattern| Self::pattern_matches(pattern, relative_path))
+    }
+
+    fn is_included(&self, relative_path: &Path) -> bool {
+        if self.include.is_empty() {
+            has_font_extension(relative_path)
+        } else {
+            self.include
+                .iter()
+                .any(|pattern| Self::pattern_matches(pattern, relative_path))
+        }
+    }
+}
+
+/// A font file found when searching for font files, or a directory that could not be read. An
+/// unreadable directory does not stop the search, so it can be reported in place of its files.
+#[derive(Debug)]
+pub(crate) enum FoundFile {
+    Font(PathBuf),
+    UnreadableDirectory(PathBuf, std::io::Error),
+}
+
+impl FoundFile {
+    pub fn path(&self) -> &Path {
+        match self {
+            FoundFile::Font(path) | FoundFile::UnreadableDirectory(path, _) => path,
+        }
+    }
+
+    /// Get the font file, or the error of the directory that could not be read.
+    pub fn into_font_file(self) -> anyhow::Result<PathBuf> {
+        match self {
+            FoundFile::Font(path) => Ok(path),
+            FoundFile::UnreadableDirectory(_, err) => {
+                Err(anyhow::Error::new(err).context("Failed reading directory"))
+            }
+        }
+    }
+}
+
+/// Find the font files in a directory tree, sorted by path. Symbolic links to files are
+/// followed, symbolic links to directories are not.
+pub(crate) fn find_font_files(directory: &Path, filter: &FileFilter) -> Vec<FoundFile> {
+    let mut found = Vec::new();
+    // the directories to search with their depth
+    let mut directories = vec![(directory.to_owned(), 1)];
+
+    while let Some((subdirectory, depth)) = directories.pop() {
+        let entries = match std::fs::read_dir(&subdirectory) {
+            Ok(entries) => entries,
+            Err(err) => {
+                found.push(FoundFile::UnreadableDirectory(subdirectory, err));
+                continue;
+            }
+        };
+        for entry in entries {
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(err) => {
+                    found.push(FoundFile::UnreadableDirectory(subdirectory.clone(), err));
+                    break;
+                }
+            };
+            let path = entry.path();
+            let relative_path = path.strip_prefix(directory).unwrap_or(&path);
+            if filter.is_excluded(relative_path) {
+                continue;
+            }
+            if entry.file_type().is_ok_and(|file_type| file_type.is_dir()) {
+                if filter.max_depth.is_none_or(|max_depth| depth < max_depth) {
+                    directories.push((path, depth + 1));
+                }
+            } else if path.is_file() && filter.is_included(relative_path) {
+                found.push(FoundFile::Font(path));
+            }
+        }
+    }
+
+    found.sort_unstable_by(|found, other| found.path().cmp(other.path()));
+    found
+}
+
+/// Get the font files to inspect from files and directories, in the order they are given.
+/// Directories are searched for font files, files are always included.
+pub(crate) fn collect_font_files(paths: &[PathBuf], filter: &FileFilter) -> Vec<FoundFile> {
+    let mut found = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            found.extend(find_font_files(path, filter));
+        } else {
+            found.push(FoundFile::Font(path.clone()));
+        }
+    }
+    found
+}
+
+/// Read a list of files and directories from a file, or from stdin if the path is '-'. The
+/// entries are separated by newlines, or by NUL characters if the list contains any, such as the
+/// output of 'find -print0'.
+pub(crate) fn read_file_list(path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut list = Vec::new();
+    if path == Path::new("-") {
+        log::info!("Reading the list of font files from stdin");
+        std::io::stdin()
+            .lock()
+            .read_to_end(&mut list)
+            .context("Failed reading the list of font files from stdin")?;
+    } else {
+        log::info!(
+            "Reading the list of font files '{}'",
+            path.to_string_lossy()
+        );
+        list = std::fs::read(path).with_context(|| {
+            format!(
+                "Failed reading the list of font files '{}'",
+                path.to_string_lossy()
+            )
+        })?;
+    }
+    parse_file_list(&list)
+}
+
+/// Parse a list of files separated by newlines or NUL characters, skipping empty entries.
+fn parse_file_list(list: &[u8]) -> anyhow::Result<Vec<PathBuf>> {
+    let separator = if list.contains(&0) { b'\0' } else { b'\n' };
+    list.split(|&byte| byte == separator)
+        .map(|entry| {
+            if separator == b'\n' {
+                entry.strip_suffix(b"\r").unwrap_or(entry)
+            } else {
+                entry
+            }
+        })
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            std::str::from_utf8(entry)
+                .map(PathBuf::from)
+                .with_context(|| {
+                    format!(
+                        "The list of font files contains a path that is not UTF-8: '{}'",
+                        String::from_utf8_lossy(entry)
+                    )
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_has_font_extension() {
+        assert!(has_font_extension(Path::new("fonts/DejaVuSans.ttf")));
+        assert!(has_font_extension(Path::new("NotoSansCJK.TTC")));
+        assert!(!has_font_extension(Path::new("fonts/LICENSE.txt")));
+        assert!(!has_font_extension(Path::new("ttf")));
+    }
+
+    #[test]
+    fn test_filter() {
+        let filter = FileFilter {
+            max_depth: None,
+            include: vec!["*.otf".parse().unwrap(), "static/**/*.ttf".parse().unwrap()],
+            exclude: vec!["*-Test.*".parse().unwrap()],
+        };
+        assert!(filter.is_included(Path::new("sans/Sans-Bold.otf")));
+        assert!(filter.is_included(Path::new("static/sans/Sans-Bold.ttf")));
+        assert!(!filter.is_included(Path::new("sans/Sans-Bold.ttf")));
+        assert!(filter.is_excluded(Path::new("sans/Sans-Test.otf")));
+        assert!(!filter.is_excluded(Path::new("sans/Sans-Bold.otf")));
+
+        let filter = FileFilter::default();
+        assert!(filter.is_included(Path::new("sans/Sans-Bold.ttf")));
+        assert!(!filter.is_included(Path::new("sans/OFL.txt")));
+    }
+
+    #[test]
+    fn test_find_font_files() {
+        let directory = std::env::temp_dir().join(format!("font-info-test-{}", std::process::id()));
+        std::fs::create_dir_all(directory.join("sub/build")).unwrap();
+        for file in ["a.ttf", "sub/b.OTF", "sub/LICENSE.txt", "sub/build/c.ttf"] {
+            std::fs::write(directory.join(file), b"").unwrap();
+        }
+
+        let paths = |found: Vec<FoundFile>| -> Vec<PathBuf> {
+            found
+                .into_iter()
+                .map(|found| match found {
+                    FoundFile::Font(path) => path.strip_prefix(&directory).unwrap().to_owned(),
+                    FoundFile::UnreadableDirectory(path, _) => panic!("failed reading {path:?}"),
+                })
+                .collect()
+        };
+        assert_eq!(
+            paths(find_font_files(&directory, &FileFilter::default())),
+            [
+                PathBuf::from("a.ttf"),
+                PathBuf::from("sub/b.OTF"),
+                PathBuf::from("sub/build/c.ttf")
+            ]
+        );
+        let filter = FileFilter {
+            max_depth: Some(2),
+            exclude: vec!["a.*".parse().unwrap()],
+            ..FileFilter::default()
+        };
+        assert_eq!(
+            paths(find_font_files(&directory, &filter)),
+            [PathBuf::from("sub/b.OTF")]
+        );
+
+        std::fs::remove_dir_all(&directory).unwrap();
+
+        // an unreadable directory is reported instead of stopping the search
+        let found = find_font_files(&directory, &FileFilter::default());
+        assert!(matches!(
+            found.as_slice(),
+            [FoundFile::UnreadableDirectory(path, _)] if path == &directory
+        ));
+    }
+
+    #[test]
+    fn test_parse_file_list() {
+        assert_eq!(
+            parse_file_list(b"a.ttf\r\nfonts/\n\nb c.otf\n").unwrap(),
+            [
+                PathBuf::from("a.ttf"),
+                PathBuf::from("fonts/"),
+                PathBuf::from("b c.otf")
+            ]
+        );
+        assert_eq!(
+            parse_file_list(b"a\nb.ttf\0c.ttf\0").unwrap(),
+            [PathBuf::from("a\nb.ttf"), PathBuf::from("c.ttf")]
+        );
+        assert!(parse_file_list(b"\xff.ttf").is_err());
+    }
+}
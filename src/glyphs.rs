@@ -0,0 +1,418 @@
+use read_fonts::{
+    tables::{
+        cff::Cff,
+        post::Post,
+        postscript::{
+            dict::{self, Entry},
+            StringId,
+        },
+    },
+    types::Version16Dot16,
+};
+use swash::{scale::ScaleContext, FontRef, GlyphId, NormalizedCoord};
+
+use crate::tables::read_table;
+
+/// A glyph to inspect, selected either by character or by glyph ID.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum GlyphSelector {
+    Char(char),
+    Id(GlyphId),
+}
+
+impl GlyphSelector {
+    /// Parse a character, either given literally, e.g., "A", or as a codepoint, e.g., "U+0041".
+    pub fn parse_char(arg: &str) -> Result<Self, String> {
+        let mut chars = arg.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => return Ok(GlyphSelector::Char(c)),
+            (None, _) => return Err("expected a character".to_owned()),
+            _ => {}
+        }
+
+        arg.get(..2)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("U+"))
+            .and_then(|_| u32::from_str_radix(&arg[2..], 16).ok())
+            .and_then(char::from_u32)
+            .map(GlyphSelector::Char)
+            .ok_or_else(|| {
+                format!("'{arg}' is not a single character or a codepoint such as 'U+0041'")
+            })
+    }
+
+    /// Parse a glyph ID.
+    pub fn parse_id(arg: &str) -> Result<Self, String> {
+        arg.parse::<GlyphId>()
+            .map(GlyphSelector::Id)
+            .map_err(|_| format!("'{arg}' is not a valid glyph ID"))
+    }
+}
+
+/// The bounding box of a glyph's outline.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct BoundingBox {
+    pub x_min: f32,
+    pub y_min: f32,
+    pub x_max: f32,
+    pub y_max: f32,
+}
+
+/// Metrics of a single glyph, in font units unless a size is given.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GlyphInfo {
+    /// The codepoint the glyph was selected by, if it was selected by character.
+    pub codepoint: Option<u32>,
+    pub glyph_id: GlyphId,
+    pub name: Option<String>,
+    pub advance_width: f32,
+    pub left_side_bearing: f32,
+    /// Only present if the font has vertical metrics.
+    pub advance_height: Option<f32>,
+    /// Only present if the font has vertical metrics.
+    pub top_side_bearing: Option<f32>,
+    /// Not present if the glyph has no outline, such as for bitmap glyphs.
+    pub bounding_box: Option<BoundingBox>,
+}
+
+impl GlyphInfo {
+    /// Inspect the selected glyphs at the given variation coordinates. If a size in pixels per em
+    /// is given, the metrics are scaled to that size.
+    pub fn from_font(
+        font: FontRef<'_>,
+        selectors: &[GlyphSelector],
+        coords: &[NormalizedCoord],
+        size: Option<f32>,
+    ) -> Vec<Self> {
+        let charmap = font.charmap();
+        let glyph_metrics = font.glyph_metrics(coords);
+        let glyph_names = GlyphNames::new(font);
+        let scale = match size {
+            Some(size) if glyph_metrics.units_per_em() != 0 => {
+                size / glyph_metrics.units_per_em() as f32
+            }
+            _ => 1.,
+        };
+
+        let mut context = ScaleContext::new();
+        let mut scaler = context
+            .builder(font)
+            .normalized_coords(coords)
+            .hint(false)
+            .build();
+
+        selectors
+            .iter()
+            .map(|selector| {
+                let (codepoint, glyph_id) = match *selector {
+                    GlyphSelector::Char(c) => (Some(u32::from(c)), charmap.map(c)),
+                    GlyphSelector::Id(glyph_id) => (None, glyph_id),
+                };
+
+                let vertical = glyph_metrics.has_vertical_metrics();
+                let bounding_box = scaler
+                    .scale_outline(glyph_id)
+                    .map(|outline| outline.bounds())
+                    .filter(|bounds| !bounds.is_empty())
+                    .map(|bounds| BoundingBox {
+                        x_min: bounds.min.x * scale,
+                        y_min: bounds.min.y * scale,
+                        x_max: bounds.max.x * scale,
+                        y_max: bounds.max.y * scale,
+                    });
+
+                GlyphInfo {
+                    codepoint,
+                    glyph_id,
+                    name: glyph_names.get(glyph_id),
+                    advance_width: glyph_metrics.advance_width(glyph_id) * scale,
+                    left_side_bearing: glyph_metrics.lsb(glyph_id) * scale,
+                    advance_height: vertical
+                        .then(|| glyph_metrics.advance_height(glyph_id) * scale),
+                    top_side_bearing: vertical.then(|| glyph_metrics.tsb(glyph_id) * scale),
+                    bounding_box,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Write the metrics of the inspected glyphs in the human-readable format.
+pub(crate) fn write_glyphs(
+    mut write: impl std::io::Write,
+    glyphs: &[GlyphInfo],
+) -> std::io::Result<()> {
+    write!(write, "              Glyphs: ")?;
+    for (idx, glyph) in glyphs.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        if let Some(codepoint) = glyph.codepoint {
+            write!(write, "U+{codepoint:04X} -> ")?;
+        }
+        write!(write, "glyph {}", glyph.glyph_id)?;
+        if let Some(name) = &glyph.name {
+            write!(write, " \"{name}\"")?;
+        }
+        write!(
+            write,
+            ": advance width {}, left side bearing {}",
+            glyph.advance_width, glyph.left_side_bearing
+        )?;
+        if let (Some(advance_height), Some(top_side_bearing)) =
+            (glyph.advance_height, glyph.top_side_bearing)
+        {
+            write!(
+                write,
+                ", advance height {advance_height}, top side bearing {top_side_bearing}"
+            )?;
+        }
+        if let Some(bounding_box) = &glyph.bounding_box {
+            write!(
+                write,
+                ", bounding box ({}, {}) to ({}, {})",
+                bounding_box.x_min, bounding_box.y_min, bounding_box.x_max, bounding_box.y_max
+            )?;
+        }
+    }
+    writeln!(write)?;
+
+    Ok(())
+}
+
+/// Get the charset offset of a CFF table's top DICT, and whether the font is CID-keyed.
+fn cff_charset(cff: &Cff<'_>) -> Option<(usize, bool)> {
+    let top_dict = cff.top_dicts().get(0).ok()?;
+
+    let mut charset_offset = 0;
+    let mut is_cid_keyed = false;
+    for entry in dict::entries(top_dict, None).filter_map(Result::ok) {
+        match entry {
+            Entry::Charset(offset) => charset_offset = offset,
+            Entry::Ros { .. } => is_cid_keyed = true,
+            _ => {}
+        }
+    }
+    Some((charset_offset, is_cid_keyed))
+}
+
+/// Get the name of a glyph from the string ID the CFF charset maps it to.
+fn cff_name(cff: &Cff<'_>, is_cid_keyed: bool, sid: u16) -> Option<String> {
+    if is_cid_keyed {
+        // in CID-keyed fonts the charset maps glyph IDs to CIDs rather than to names
+        Some(format!("cid{sid:05}"))
+    } else {
+        Some(cff.string(StringId::new(sid))?.chars().collect())
+    }
+}
+
+/// The glyph names of a font, from the `post` table or, for fonts with CFF outlines, from the CFF
+/// charset. The `post` and CFF tables are parsed once, and the strings of the `post` table and
+/// the CFF charset are read once, so looking up the names of all glyphs takes linear time.
+pub(crate) struct GlyphNames<'a> {
+    glyph_count: u16,
+    post: Option<Post<'a>>,
+    /// The names in the string data of a version 2.0 `post` table, in order.
+    post_strings: Vec<Option<&'a str>>,
+    cff: Option<CffNames<'a>>,
+}
+
+/// The parsed CFF table and charset of [`GlyphNames`].
+struct CffNames<'a> {
+    cff: Cff<'a>,
+    is_cid_keyed: bool,
+    /// The string IDs, or CIDs, the charset maps the glyphs to, by glyph ID. Empty if the charset
+    /// is not supported.
+    sids: Vec<Option<u16>>,
+}
+
+impl<'a> GlyphNames<'a> {
+    pub fn new(font: FontRef<'a>) -> Self {
+        let glyph_count = font.metrics(&[]).glyph_count;
+
+        let post = read_table::<Post>(font);
+        let post_strings = post
+            .as_ref()
+            .filter(|post| post.version() == Version16Dot16::VERSION_2_0)
+            .and_then(|post| post.string_data())
+            .map(|strings| {
+                strings
+                    .iter()
+                    .map(|string| string.ok().map(|string| string.as_str()))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let cff = read_table::<Cff>(font).and_then(|cff| {
+            let (charset_offset, is_cid_keyed) = cff_charset(&cff)?;
+            let sids = match charset_offset {
+                0 => (0..glyph_count).map(Some).collect(),
+                1 | 2 => Vec::new(),
+                offset => charset_sids(cff.offset_data().as_bytes().get(offset..)?, glyph_count),
+            };
+            Some(CffNames {
+                cff,
+                is_cid_keyed,
+                sids,
+            })
+        });
+
+        GlyphNames {
+            glyph_count,
+            post,
+            post_strings,
+            cff,
+        }
+    }
+
+    /// Get the name of a glyph.
+    pub fn get(&self, glyph_id: GlyphId) -> Option<String> {
+        if glyph_id >= self.glyph_count {
+            return None;
+        }
+
+        if let Some(name) = self.post_name(glyph_id) {
+            return Some(name.to_owned());
+        }
+
+        let CffNames {
+            cff,
+            is_cid_keyed,
+            sids,
+        } = self.cff.as_ref()?;
+        if glyph_id == 0 {
+            return Some(".notdef".to_owned());
+        }
+        cff_name(cff, *is_cid_keyed, (*sids.get(usize::from(glyph_id))?)?)
+    }
+
+    fn post_name(&self, glyph_id: GlyphId) -> Option<&str> {
+        let post = self.post.as_ref()?;
+        if post.version() != Version16Dot16::VERSION_2_0 {
+            return post.glyph_name(glyph_id.into());
+        }
+
+        let name_index = usize::from(post.glyph_name_index()?.get(usize::from(glyph_id))?.get());
+        match name_index.checked_sub(STANDARD_GLYPH_NAME_COUNT) {
+            // the standard Macintosh glyph names are looked up without reading the string data
+            None => post.glyph_name(glyph_id.into()),
+            Some(idx) => self.post_strings.get(idx).copied().flatten(),
+        }
+    }
+}
+
+/// The number of standard Macintosh glyph names of the `post` table.
+const STANDARD_GLYPH_NAME_COUNT: usize = 258;
+
+/// Get the string IDs a CFF charset maps the glyphs to, by glyph ID. The string ID of glyph 0
+/// (.notdef) is not in the charset and is 0. See "Charsets" in the CFF specification.
+fn charset_sids(charset: &[u8], glyph_count: u16) -> Vec<Option<u16>> {
+    let read_u16 = |offset: usize| -> Option<u16> {
+        charset
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+    };
+
+    let glyph_count = usize::from(glyph_count);
+    let mut sids = Vec::with_capacity(glyph_count);
+    sids.push(Some(0));
+    match charset.first() {
+        Some(0) => {
+            for glyph_id in 1..glyph_count {
+                sids.push(read_u16(1 + (glyph_id - 1) * 2));
+            }
+        }
+        Some(&format @ (1 | 2)) => {
+            let range_size = if format == 1 { 3 } else { 4 };
+            let mut offset = 1;
+            while sids.len() < glyph_count {
+                let (Some(first_sid), Some(n_left)) = (
+                    read_u16(offset),
+                    if format == 1 {
+                        charset.get(offset + 2).copied().map(u16::from)
+                    } else {
+                        read_u16(offset + 2)
+                    },
+                ) else {
+                    break;
+                };
+                for sid in u32::from(first_sid)..=u32::from(first_sid) + u32::from(n_left) {
+                    if sids.len() == glyph_count {
+                        break;
+                    }
+                    sids.push(u16::try_from(sid).ok());
+                }
+                offset += range_size;
+            }
+        }
+        _ => {}
+    }
+    sids.resize(glyph_count, None);
+    sids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_glyph_selector() {
+        assert_eq!(GlyphSelector::parse_char("A"), Ok(GlyphSelector::Char('A')));
+        assert_eq!(GlyphSelector::parse_char("U"), Ok(GlyphSelector::Char('U')));
+        assert_eq!(
+            GlyphSelector::parse_char("U+00E9"),
+            Ok(GlyphSelector::Char('é'))
+        );
+        assert!(GlyphSelector::parse_char("AB").is_err());
+        assert!(GlyphSelector::parse_char("").is_err());
+        assert_eq!(GlyphSelector::parse_id("42"), Ok(GlyphSelector::Id(42)));
+        assert!(GlyphSelector::parse_id("-1").is_err());
+    }
+
+    #[test]
+    fn test_charset_sids() {
+        let charset = [0, 0, 34, 0, 35, 0x01, 0x90];
+        assert_eq!(
+            charset_sids(&charset, 5),
+            [Some(0), Some(34), Some(35), Some(400), None]
+        );
+
+        let charset = [1, 0, 10, 2, 0, 100, 0];
+        assert_eq!(
+            charset_sids(&charset, 6),
+            [Some(0), Some(10), Some(11), Some(12), Some(100), None]
+        );
+
+        // format 2 with a range of 0x1_0000 string IDs, which ends at the glyph count
+        let charset = [2, 0, 1, 0xFF, 0xFF];
+        assert_eq!(charset_sids(&charset, 3), [Some(0), Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_glyph_names() {
+        use crate::test_fonts::{font_file, head_table, maxp_table, Data};
+
+        // glyph 1 has the standard name of index 36, glyph 2 the first name of the string data
+        let post = Data::default()
+            .u32(0x0002_0000)
+            .bytes(&[0; 28])
+            .u16(3)
+            .u16(0)
+            .u16(36)
+            .u16(258)
+            .bytes(b"\x03foo");
+        let data = font_file(&[
+            (b"head", &head_table(1000)),
+            (b"maxp", &maxp_table(3)),
+            (b"post", &post.0),
+        ]);
+        let font = FontRef::from_index(&data, 0).unwrap();
+
+        let names = GlyphNames::new(font);
+        assert_eq!(names.get(0).as_deref(), Some(".notdef"));
+        assert_eq!(names.get(1).as_deref(), Some("A"));
+        assert_eq!(names.get(2).as_deref(), Some("foo"));
+        assert_eq!(names.get(3), None);
+    }
+}
@@ -0,0 +1,864 @@
+use read_fonts::{
+    tables::gpos::{Gpos, PairPos, PositionSubtables, ValueRecord},
+    types::GlyphId as ReadGlyphId,
+};
+use swash::{FontRef, GlyphId};
+
+use crate::{
+    glyphs::GlyphNames,
+    names,
+    tables::{read_table, read_u16, read_u32},
+    FontError, Out, Outcome,
+};
+
+const KERN: swash::Tag = swash::tag_from_bytes(b"kern");
+
+/// Pairs of characters to print the kerning of, taken from adjacent characters of a text.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct KernPairs(pub Vec<[char; 2]>);
+
+impl KernPairs {
+    /// Parse a text, such as "AV" or "To", into its pairs of adjacent characters.
+    pub fn parse(arg: &str) -> Result<Self, String> {
+        let chars: Vec<char> = arg.chars().filter(|c| !c.is_control()).collect();
+        if chars.len() < 2 {
+            return Err(format!("'{arg}' must contain at least two characters"));
+        }
+        Ok(KernPairs(
+            chars.windows(2).map(|pair| [pair[0], pair[1]]).collect(),
+        ))
+    }
+}
+
+/// The positioning adjustment of a glyph in a pair, in font units. Adjustments that are not
+/// given by the font are not present.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Adjustment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x_placement: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y_placement: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x_advance: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y_advance: Option<i16>,
+}
+
+impl Adjustment {
+    fn from_value_record(record: &ValueRecord) -> Self {
+        Adjustment {
+            x_placement: record.x_placement(),
+            y_placement: record.y_placement(),
+            x_advance: record.x_advance(),
+            y_advance: record.y_advance(),
+        }
+    }
+
+    fn is_zero(&self) -> bool {
+        [
+            self.x_placement,
+            self.y_placement,
+            self.x_advance,
+            self.y_advance,
+        ]
+        .iter()
+        .all(|value| value.unwrap_or(0) == 0)
+    }
+}
+
+/// The positioning adjustments of both glyphs of a pair.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PairAdjustment {
+    pub first: Adjustment,
+    pub second: Adjustment,
+}
+
+impl PairAdjustment {
+    /// The change in the horizontal distance between the glyphs: the advance adjustment of the
+    /// first glyph plus the placement adjustment of the second.
+    fn kerning(&self) -> i32 {
+        i32::from(self.first.x_advance.unwrap_or(0))
+            + i32::from(self.second.x_placement.unwrap_or(0))
+    }
+
+    fn is_zero(&self) -> bool {
+        self.first.is_zero() && self.second.is_zero()
+    }
+}
+
+/// The indices of the lookups of the GPOS `kern` feature of any script and language system, in
+/// lookup order.
+fn kern_lookups(gpos: &Gpos<'_>) -> Vec<u16> {
+    let Ok(feature_list) = gpos.feature_list() else {
+        return Vec::new();
+    };
+
+    let mut lookups: Vec<u16> = feature_list
+        .feature_records()
+        .iter()
+        .filter(|record| record.feature_tag() == read_fonts::types::Tag::new(b"kern"))
+        .filter_map(|record| record.feature(feature_list.offset_data()).ok())
+        .flat_map(|feature| {
+            feature
+                .lookup_list_indices()
+                .iter()
+                .map(|idx| idx.get())
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    lookups.sort_unstable();
+    lookups.dedup();
+    lookups
+}
+
+/// The pair positioning subtables of the GPOS `kern` feature, with their lookup and subtable
+/// indices.
+fn pair_subtables<'a>(gpos: &Gpos<'a>) -> Vec<(u16, usize, PairPos<'a>)> {
+    let Ok(lookup_list) = gpos.lookup_list() else {
+        return Vec::new();
+    };
+
+    let mut subtables = Vec::new();
+    for lookup_index in kern_lookups(gpos) {
+        let Ok(lookup) = lookup_list.lookups().get(usize::from(lookup_index)) else {
+            continue;
+        };
+        if let Ok(PositionSubtables::Pair(pairs)) = lookup.subtables() {
+            for (subtable_index, subtable) in pairs.iter().enumerate() {
+                if let Ok(subtable) = subtable {
+                    subtables.push((lookup_index, subtable_index, subtable));
+                }
+            }
+        }
+    }
+    subtables
+}
+
+/// Look up the adjustment of a pair in a pair positioning subtable. Returns `None` if the
+/// subtable does not apply to the pair, in which case the next subtable of the lookup applies.
+/// Class-based subtables apply to any pair of which the first glyph is covered, and also return
+/// the classes of the glyphs.
+fn subtable_adjustment(
+    subtable: &PairPos<'_>,
+    first: ReadGlyphId,
+    second: ReadGlyphId,
+) -> Option<(PairAdjustment, Option<(u16, u16)>)> {
+    match subtable {
+        PairPos::Format1(subtable) => {
+            let coverage_index = subtable.coverage().ok()?.get(first)?;
+            let pair_set = subtable.pair_sets().get(usize::from(coverage_index)).ok()?;
+            pair_set
+                .pair_value_records()
+                .iter()
+                .filter_map(Result::ok)
+                .find(|record| record.second_glyph() == second)
+                .map(|record| {
+                    let adjustment = PairAdjustment {
+                        first: Adjustment::from_value_record(record.value_record1()),
+                        second: Adjustment::from_value_record(record.value_record2()),
+                    };
+                    (adjustment, None)
+                })
+        }
+        PairPos::Format2(subtable) => {
+            subtable.coverage().ok()?.get(first)?;
+            let class1 = subtable.class_def1().ok()?.get(first);
+            let class2 = subtable.class_def2().ok()?.get(second);
+            let record = subtable
+                .class1_records()
+                .get(usize::from(class1))
+                .ok()?
+                .class2_records()
+                .get(usize::from(class2))
+                .ok()?;
+            let adjustment = PairAdjustment {
+                first: Adjustment::from_value_record(record.value_record1()),
+                second: Adjustment::from_value_record(record.value_record2()),
+            };
+            Some((adjustment, Some((class1, class2))))
+        }
+    }
+}
+
+/// A horizontal kerning subtable of format 0 of the legacy `kern` table.
+struct LegacySubtable<'a> {
+    /// The pairs, sorted by the first and second glyph ID, followed by the value.
+    pairs: &'a [u8],
+}
+
+impl LegacySubtable<'_> {
+    fn pairs(&self) -> impl Iterator<Item = (u16, u16, i16)> + '_ {
+        self.pairs.chunks_exact(6).map(|pair| {
+            (
+                u16::from_be_bytes([pair[0], pair[1]]),
+                u16::from_be_bytes([pair[2], pair[3]]),
+                i16::from_be_bytes([pair[4], pair[5]]),
+            )
+        })
+    }
+
+    fn get(&self, first: u16, second: u16) -> Option<i16> {
+        let key = (u32::from(first) << 16) | u32::from(second);
+        let num_pairs = self.pairs.len() / 6;
+        let (mut low, mut high) = (0, num_pairs);
+        while low < high {
+            let mid = (low + high) / 2;
+            let pair_key = read_u32(self.pairs, mid * 6)?;
+            match pair_key.cmp(&key) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => {
+                    return read_u16(self.pairs, mid * 6 + 4).map(|value| value as i16)
+                }
+            }
+        }
+        None
+    }
+}
+
+/// read-fonts does not parse the legacy `kern` table, so its horizontal format 0 subtables are
+/// read from the raw table. Both the OpenType (version 0) and the Apple (version 1) headers are
+/// supported; subtables of other formats, and vertical, cross-stream and minimum value
+/// subtables are skipped. See the OpenType and Apple TrueType specifications of `kern`.
+fn legacy_subtables(font: FontRef<'_>) -> Vec<(usize, LegacySubtable<'_>)> {
+    let Some(table) = font.table(KERN) else {
+        return Vec::new();
+    };
+
+    let mut subtables = Vec::new();
+    let apple = read_u16(table, 0) == Some(1);
+    let (num_tables, mut offset) = if apple {
+        (read_u32(table, 4).unwrap_or(0) as usize, 8)
+    } else {
+        (usize::from(read_u16(table, 2).unwrap_or(0)), 4)
+    };
+
+    for idx in 0..num_tables {
+        let (length, format, horizontal, header_size) = if apple {
+            let (Some(length), Some(coverage)) =
+                (read_u32(table, offset), read_u16(table, offset + 4))
+            else {
+                break;
+            };
+            // vertical, cross-stream and variation subtables
+            (length as usize, coverage & 0xFF, coverage & 0xE000 == 0, 8)
+        } else {
+            let (Some(length), Some(coverage)) =
+                (read_u16(table, offset + 2), read_u16(table, offset + 4))
+            else {
+                break;
+            };
+            // horizontal, not minimum values and not cross-stream
+            (
+                usize::from(length),
+                coverage >> 8,
+                coverage & 0x0007 == 0x0001,
+                6,
+            )
+        };
+
+        if format == 0 && horizontal {
+            let data = offset + header_size;
+            let num_pairs = usize::from(read_u16(table, data).unwrap_or(0));
+            let pairs = data + 8;
+            if let Some(pairs) = table.get(pairs..pairs + num_pairs * 6) {
+                subtables.push((idx, LegacySubtable { pairs }));
+            }
+        } else {
+            log::info!("Skipping unsupported 'kern' subtable {idx} of format {format}");
+        }
+
+        // the pairs are read using their count rather than the subtable length, as the 16-bit
+        // length of OpenType subtables overflows for large subtables
+        if length == 0 {
+            break;
+        }
+        offset += length;
+    }
+
+    subtables
+}
+
+/// The kerning of a pair of characters.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CharPairKerning {
+    pub first: char,
+    pub second: char,
+    /// Not present if the font does not map the character to a glyph.
+    pub first_glyph_id: Option<GlyphId>,
+    pub second_glyph_id: Option<GlyphId>,
+    /// The change in the horizontal distance between the glyphs, in font units.
+    pub kerning: i32,
+    /// Descriptions of the subtables the kerning was found in, e.g., "GPOS lookup 3, subtable
+    /// 1, classes 2 and 5". Empty if the pair is not kerned.
+    pub sources: Vec<String>,
+}
+
+impl CharPairKerning {
+    /// Look up the kerning of each pair in the GPOS `kern` feature of the font, or the legacy
+    /// `kern` table if the font has no GPOS `kern` feature. Variable fonts are inspected at
+    /// their default coordinates.
+    pub fn from_font(font: FontRef<'_>, pairs: &[[char; 2]]) -> Vec<Self> {
+        let gpos = read_table::<Gpos>(font).filter(|gpos| !kern_lookups(gpos).is_empty());
+        let gpos_subtables = gpos.as_ref().map(pair_subtables);
+        let legacy = if gpos_subtables.is_none() {
+            legacy_subtables(font)
+        } else {
+            Vec::new()
+        };
+        let charmap = font.charmap();
+
+        pairs
+            .iter()
+            .map(|&[first, second]| {
+                let glyph_id = |c: char| Some(charmap.map(c)).filter(|&glyph_id| glyph_id != 0);
+                let (first_glyph_id, second_glyph_id) = (glyph_id(first), glyph_id(second));
+                let mut kerning = 0;
+                let mut sources = Vec::new();
+
+                if let (Some(first_glyph), Some(second_glyph)) = (first_glyph_id, second_glyph_id) {
+                    match &gpos_subtables {
+                        Some(subtables) => {
+                            let (first_glyph, second_glyph) = (
+                                ReadGlyphId::new(first_glyph),
+                                ReadGlyphId::new(second_glyph),
+                            );
+                            // the first subtable of each lookup that applies to the pair is used
+                            let mut applied_lookup = None;
+                            for (lookup_index, subtable_index, subtable) in subtables {
+                                if applied_lookup == Some(*lookup_index) {
+                                    continue;
+                                }
+                                let Some((adjustment, classes)) =
+                                    subtable_adjustment(subtable, first_glyph, second_glyph)
+                                else {
+                                    continue;
+                                };
+                                applied_lookup = Some(*lookup_index);
+                                if adjustment.is_zero() {
+                                    continue;
+                                }
+                                kerning += adjustment.kerning();
+                                let mut source = format!(
+                                    "GPOS lookup {lookup_index}, subtable {subtable_index}"
+                                );
+                                if let Some((class1, class2)) = classes {
+                                    source.push_str(&format!(", classes {class1} and {class2}"));
+                                }
+                                sources.push(source);
+                            }
+                        }
+                        None => {
+                            for (subtable_index, subtable) in &legacy {
+                                if let Some(value) = subtable.get(first_glyph, second_glyph) {
+                                    kerning += i32::from(value);
+                                    sources.push(format!("kern subtable {subtable_index}"));
+                                }
+                            }
+                        }
+                    }
+                }
+
+                CharPairKerning {
+                    first,
+                    second,
+                    first_glyph_id,
+                    second_glyph_id,
+                    kerning,
+                    sources,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Write the kerning of character pairs in the human-readable format, with the lookups and
+/// subtables each value comes from.
+pub(crate) fn write_char_pair_kerning(
+    mut write: impl std::io::Write,
+    pairs: &[CharPairKerning],
+) -> std::io::Result<()> {
+    write!(write, "             Kerning: ")?;
+    for (idx, pair) in pairs.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        write!(write, "{}{}: ", pair.first, pair.second)?;
+        match (pair.first_glyph_id, pair.second_glyph_id) {
+            (Some(first), Some(second)) => {
+                write!(write, "glyphs {first} and {second}: {}", pair.kerning)?;
+                if !pair.sources.is_empty() {
+                    write!(write, " ({})", pair.sources.join("; "))?;
+                }
+            }
+            _ => write!(write, "not mapped to glyphs")?,
+        }
+    }
+    writeln!(write)?;
+
+    Ok(())
+}
+
+/// A glyph in the kerning export.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KernGlyph {
+    pub id: GlyphId,
+    pub name: Option<String>,
+}
+
+impl KernGlyph {
+    fn new(names: &GlyphNames<'_>, id: GlyphId) -> Self {
+        KernGlyph {
+            id,
+            name: names.get(id),
+        }
+    }
+}
+
+/// An explicitly kerned pair of glyphs.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GlyphPair {
+    pub first: KernGlyph,
+    pub second: KernGlyph,
+    pub adjustment: PairAdjustment,
+}
+
+/// A class of glyphs of class-based kerning.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GlyphClass {
+    pub class: u16,
+    pub glyphs: Vec<KernGlyph>,
+}
+
+/// A kerned pair of glyph classes.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ClassPair {
+    pub first_class: u16,
+    pub second_class: u16,
+    pub adjustment: PairAdjustment,
+}
+
+/// The kerning of a subtable.
+#[derive(Debug, serde::Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub(crate) enum SubtableKerning {
+    /// Explicitly kerned glyph pairs.
+    #[serde(rename_all = "camelCase")]
+    Pairs { pairs: Vec<GlyphPair> },
+    /// Class-based kerning. Class 0 of the second glyphs contains all glyphs that are not in
+    /// another class, and is listed without glyphs. Class pairs without an adjustment are
+    /// omitted.
+    #[serde(rename_all = "camelCase")]
+    Classes {
+        first_classes: Vec<GlyphClass>,
+        second_classes: Vec<GlyphClass>,
+        pairs: Vec<ClassPair>,
+    },
+}
+
+/// A kerning subtable of the GPOS `kern` feature or the legacy `kern` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct KernSubtable {
+    /// "GPOS" or "kern".
+    pub table: &'static str,
+    /// The index of the GPOS lookup of the subtable. Not present for `kern` subtables.
+    pub lookup_index: Option<u16>,
+    pub subtable_index: usize,
+    #[serde(flatten)]
+    pub kerning: SubtableKerning,
+}
+
+/// Get all kerning the font defines in the GPOS `kern` feature, or in the legacy `kern` table if
+/// the font has no GPOS `kern` feature.
+pub(crate) fn export(font: FontRef<'_>) -> Vec<KernSubtable> {
+    let names = GlyphNames::new(font);
+    let Some(gpos) = read_table::<Gpos>(font).filter(|gpos| !kern_lookups(gpos).is_empty()) else {
+        return legacy_subtables(font)
+            .into_iter()
+            .map(|(subtable_index, subtable)| KernSubtable {
+                table: "kern",
+                lookup_index: None,
+                subtable_index,
+                kerning: SubtableKerning::Pairs {
+                    pairs: subtable
+                        .pairs()
+                        .map(|(first, second, value)| GlyphPair {
+                            first: KernGlyph::new(&names, first),
+                            second: KernGlyph::new(&names, second),
+                            adjustment: PairAdjustment {
+                                first: Adjustment {
+                                    x_advance: Some(value),
+                                    ..Adjustment::default()
+                                },
+                                second: Adjustment::default(),
+                            },
+                        })
+                        .collect(),
+                },
+            })
+            .collect();
+    };
+
+    pair_subtables(&gpos)
+        .into_iter()
+        .filter_map(|(lookup_index, subtable_index, subtable)| {
+            let kerning = match subtable {
+                PairPos::Format1(subtable) => {
+                    let coverage = subtable.coverage().ok()?;
+                    let mut pairs = Vec::new();
+                    for (first, pair_set) in coverage.iter().zip(subtable.pair_sets().iter()) {
+                        let Ok(pair_set) = pair_set else { continue };
+                        for record in pair_set.pair_value_records().iter().filter_map(Result::ok) {
+                            pairs.push(GlyphPair {
+                                first: KernGlyph::new(&names, first.to_u16()),
+                                second: KernGlyph::new(&names, record.second_glyph().to_u16()),
+                                adjustment: PairAdjustment {
+                                    first: Adjustment::from_value_record(record.value_record1()),
+                                    second: Adjustment::from_value_record(record.value_record2()),
+                                },
+                            });
+                        }
+                    }
+                    SubtableKerning::Pairs { pairs }
+                }
+                PairPos::Format2(subtable) => {
+                    let class_def1 = subtable.class_def1().ok()?;
+                    let class_def2 = subtable.class_def2().ok()?;
+
+                    // class 0 of the first glyphs contains the covered glyphs without a class
+                    let mut first_classes: Vec<GlyphClass> = (0..subtable.class1_count())
+                        .map(|class| GlyphClass {
+                            class,
+                            glyphs: Vec::new(),
+                        })
+                        .collect();
+                    for glyph in subtable.coverage().ok()?.iter() {
+                        let class = class_def1.get(glyph);
+                        if let Some(first_class) = first_classes.get_mut(usize::from(class)) {
+                            first_class
+                                .glyphs
+                                .push(KernGlyph::new(&names, glyph.to_u16()));
+                        }
+                    }
+
+                    let mut second_classes: Vec<GlyphClass> = (0..subtable.class2_count())
+                        .map(|class| GlyphClass {
+                            class,
+                            glyphs: Vec::new(),
+                        })
+                        .collect();
+                    for (glyph, class) in class_def2.iter() {
+                        if let Some(second_class) = second_classes.get_mut(usize::from(class)) {
+                            if class != 0 {
+                                second_class
+                                    .glyphs
+                                    .push(KernGlyph::new(&names, glyph.to_u16()));
+                            }
+                        }
+                    }
+
+                    let mut pairs = Vec::new();
+                    for (first_class, record) in subtable.class1_records().iter().enumerate() {
+                        let Ok(record) = record else { continue };
+                        for (second_class, record) in record.class2_records().iter().enumerate() {
+                            let Ok(record) = record else { continue };
+                            let adjustment = PairAdjustment {
+                                first: Adjustment::from_value_record(record.value_record1()),
+                                second: Adjustment::from_value_record(record.value_record2()),
+                            };
+                            if !adjustment.is_zero() {
+                                pairs.push(ClassPair {
+                                    first_class: first_class as u16,
+                                    second_class: second_class as u16,
+                                    adjustment,
+                                });
+                            }
+                        }
+                    }
+
+                    SubtableKerning::Classes {
+                        first_classes,
+                        second_classes,
+                        pairs,
+                    }
+                }
+            };
+
+            Some(KernSubtable {
+                table: "GPOS",
+                lookup_index: Some(lookup_index),
+                subtable_index,
+                kerning,
+            })
+        })
+        .collect()
+}
+
+/// Outputs all kerning of each font as JSON.
+pub(crate) struct JsonKerningExport<S> {
+    pub serializer: S,
+    name_language: Option<String>,
+}
+
+impl<S> JsonKerningExport<S> {
+    pub fn new(serializer: S, name_language: Option<String>) -> Self {
+        JsonKerningExport {
+            serializer,
+            name_language,
+        }
+    }
+}
+
+impl<S: serde::ser::SerializeSeq<Error = serde_json::Error>> Out for JsonKerningExport<S> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Out<'m> {
+            source: &'m str,
+            font_index: usize,
+            family_name: Option<&'m str>,
+            subfamily_name: Option<&'m str>,
+            kerning: &'m [KernSubtable],
+        }
+
+        let language = self.name_language.as_deref();
+
+        serde::ser::SerializeSeq::serialize_element(
+            &mut self.serializer,
+            &Out {
+                source,
+                font_index,
+                family_name: names::family_name(font, language).as_deref(),
+                subfamily_name: names::subfamily_name(font, language).as_deref(),
+                kerning: &export(font),
+            },
+        )?;
+
+        Ok(Outcome::Pass)
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        serde::ser::SerializeSeq::serialize_element(&mut self.serializer, error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fonts::{Data, TestFont};
+
+    /// A font with glyphs 1 to 5 for 'A', 'T', 'V', 'o' and 'x'.
+    fn font() -> TestFont {
+        TestFont::new(&[('A', 600), ('T', 600), ('V', 600), ('o', 500), ('x', 500)])
+    }
+
+    /// Build a GPOS table with a `kern` feature of two lookups: glyph pair A V kerned by -80,
+    /// and class pair T o kerned by -60.
+    fn gpos_table() -> Vec<u8> {
+        let pair_pos_1 = Data::default()
+            .u16(1)
+            .u16(12)
+            .u16(0x0004)
+            .u16(0)
+            .u16(1)
+            .u16(18)
+            // coverage of A
+            .u16(1)
+            .u16(1)
+            .u16(1)
+            // pair set of A with V
+            .u16(1)
+            .u16(3)
+            .i16(-80);
+        let pair_pos_2 = Data::default()
+            .u16(2)
+            .u16(24)
+            .u16(0x0004)
+            .u16(0)
+            .u16(30)
+            .u16(38)
+            .u16(2)
+            .u16(2)
+            // the class 1 to class 1 pair is kerned
+            .i16(0)
+            .i16(0)
+            .i16(0)
+            .i16(-60)
+            // coverage of T
+            .u16(1)
+            .u16(1)
+            .u16(2)
+            // class 1 contains T in the first and o in the second class definition
+            .u16(1)
+            .u16(2)
+            .u16(1)
+            .u16(1)
+            .u16(1)
+            .u16(4)
+            .u16(1)
+            .u16(1);
+        let lookup = |subtable: Data| {
+            Data::default()
+                .u16(2)
+                .u16(0)
+                .u16(1)
+                .u16(8)
+                .bytes(&subtable.0)
+        };
+        let lookup_1 = lookup(pair_pos_1);
+        let lookup_2 = lookup(pair_pos_2);
+
+        Data::default()
+            .u16(1)
+            .u16(0)
+            .u16(10)
+            .u16(12)
+            .u16(28)
+            // empty script list
+            .u16(0)
+            // feature list with the `kern` feature
+            .u16(1)
+            .tag(b"kern")
+            .u16(8)
+            .u16(0)
+            .u16(2)
+            .u16(0)
+            .u16(1)
+            // lookup list
+            .u16(2)
+            .u16(6)
+            .u16(6 + lookup_1.len() as u16)
+            .bytes(&lookup_1.0)
+            .bytes(&lookup_2.0)
+            .0
+    }
+
+    fn kerning(font: &TestFont, pairs: &str) -> Vec<(i32, Vec<String>)> {
+        let data = font.build();
+        let font = FontRef::from_index(&data, 0).unwrap();
+        CharPairKerning::from_font(font, &KernPairs::parse(pairs).unwrap().0)
+            .into_iter()
+            .map(|pair| (pair.kerning, pair.sources))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_kern_pairs() {
+        assert_eq!(KernPairs::parse("AV"), Ok(KernPairs(vec![['A', 'V']])));
+        assert_eq!(
+            KernPairs::parse("Tov"),
+            Ok(KernPairs(vec![['T', 'o'], ['o', 'v']]))
+        );
+        assert!(KernPairs::parse("A").is_err());
+    }
+
+    #[test]
+    fn test_legacy_subtable() {
+        // pairs (1, 2) -> -50 and (1, 3) -> 20, sorted by glyph IDs
+        let pairs = [0, 1, 0, 2, 0xFF, 0xCE, 0, 1, 0, 3, 0, 20];
+        let subtable = LegacySubtable { pairs: &pairs };
+        assert_eq!(subtable.get(1, 2), Some(-50));
+        assert_eq!(subtable.get(1, 3), Some(20));
+        assert_eq!(subtable.get(2, 1), None);
+        assert_eq!(subtable.pairs().count(), 2);
+    }
+
+    #[test]
+    fn test_gpos_kerning() {
+        let font = font().table(b"GPOS", gpos_table());
+        assert_eq!(
+            kerning(&font, "AVTox"),
+            [
+                (-80, vec!["GPOS lookup 0, subtable 0".to_owned()]),
+                (0, vec![]),
+                (
+                    -60,
+                    vec!["GPOS lookup 1, subtable 0, classes 1 and 1".to_owned()]
+                ),
+                (0, vec![]),
+            ]
+        );
+
+        // the legacy `kern` table is only used without a GPOS `kern` feature
+        let kern = Data::default()
+            .u16(0)
+            .u16(1)
+            .u16(0)
+            .u16(6 + 8 + 6)
+            .u16(0x0001)
+            .u16(1)
+            .bytes(&[0; 6])
+            .u16(1)
+            .u16(3)
+            .i16(-70);
+        let font = font.table(b"kern", kern.0);
+        assert_eq!(kerning(&font, "AV")[0].0, -80);
+        let font = font.table(b"GPOS", vec![]);
+        assert_eq!(
+            kerning(&font, "AV"),
+            [(-70, vec!["kern subtable 0".to_owned()])]
+        );
+    }
+
+    #[test]
+    fn test_export() {
+        let data = font().table(b"GPOS", gpos_table()).build();
+        let subtables = export(FontRef::from_index(&data, 0).unwrap());
+        assert_eq!(subtables.len(), 2);
+        assert_eq!(
+            (subtables[0].table, subtables[0].lookup_index),
+            ("GPOS", Some(0))
+        );
+
+        let SubtableKerning::Pairs { pairs } = &subtables[0].kerning else {
+            panic!("expected glyph pairs");
+        };
+        let pairs: Vec<_> = pairs
+            .iter()
+            .map(|pair| (pair.first.id, pair.second.id, pair.adjustment.kerning()))
+            .collect();
+        assert_eq!(pairs, [(1, 3, -80)]);
+
+        let SubtableKerning::Classes {
+            first_classes,
+            second_classes,
+            pairs,
+        } = &subtables[1].kerning
+        else {
+            panic!("expected class pairs");
+        };
+        let glyphs = |classes: &[GlyphClass]| -> Vec<Vec<GlyphId>> {
+            classes
+                .iter()
+                .map(|class| class.glyphs.iter().map(|glyph| glyph.id).collect())
+                .collect()
+        };
+        assert_eq!(glyphs(first_classes), [vec![], vec![2]]);
+        assert_eq!(glyphs(second_classes), [vec![], vec![4]]);
+        let pairs: Vec<_> = pairs
+            .iter()
+            .map(|pair| {
+                (
+                    pair.first_class,
+                    pair.second_class,
+                    pair.adjustment.kerning(),
+                )
+            })
+            .collect();
+        assert_eq!(pairs, [(1, 1, -60)]);
+    }
+}
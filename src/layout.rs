@@ -0,0 +1,265 @@
+use read_fonts::{
+    tables::{
+        gpos::Gpos,
+        gsub::Gsub,
+        layout::{FeatureList, LangSys, ScriptList},
+    },
+    types::Tag as ReadTag,
+    ReadError,
+};
+use swash::FontRef;
+
+use crate::{settings::tag_to_string, tables::read_table};
+
+/// The required feature index of language systems without a required feature.
+const NO_REQUIRED_FEATURE: u16 = 0xFFFF;
+
+fn read_tag_to_string(tag: ReadTag) -> String {
+    tag_to_string(u32::from_be_bytes(tag.to_be_bytes()))
+}
+
+/// A feature of a language system.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LayoutFeature {
+    /// The index in the feature list.
+    pub index: u16,
+    /// Not present if the index is out of bounds of the feature list.
+    pub tag: Option<String>,
+    pub lookup_count: usize,
+}
+
+/// A language system of a script and its features.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LayoutLanguageSystem {
+    /// The language system tag, or `None` for the script's default language system.
+    pub language: Option<String>,
+    pub required_feature: Option<LayoutFeature>,
+    pub features: Vec<LayoutFeature>,
+}
+
+/// A script and its language systems, starting with the default language system.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LayoutScript {
+    pub script: String,
+    pub language_systems: Vec<LayoutLanguageSystem>,
+}
+
+/// The scripts of a GSUB or GPOS table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LayoutTable {
+    pub scripts: Vec<LayoutScript>,
+}
+
+impl LayoutTable {
+    fn new(
+        script_list: Result<ScriptList<'_>, ReadError>,
+        feature_list: Result<FeatureList<'_>, ReadError>,
+    ) -> Self {
+        let feature_list = feature_list.ok();
+        let feature = |index: u16| {
+            let record = feature_list
+                .as_ref()
+                .and_then(|list| Some((list, list.feature_records().get(usize::from(index))?)));
+            LayoutFeature {
+                index,
+                tag: record.map(|(_, record)| read_tag_to_string(record.feature_tag())),
+                lookup_count: record
+                    .and_then(|(list, record)| record.feature(list.offset_data()).ok())
+                    .map_or(0, |feature| feature.lookup_list_indices().len()),
+            }
+        };
+        let language_system = |language: Option<String>, lang_sys: LangSys<'_>| {
+            let required = lang_sys.required_feature_index();
+            LayoutLanguageSystem {
+                language,
+                required_feature: (required != NO_REQUIRED_FEATURE).then(|| feature(required)),
+                features: lang_sys
+                    .feature_indices()
+                    .iter()
+                    .map(|index| feature(index.get()))
+                    .collect(),
+            }
+        };
+
+        let mut scripts = Vec::new();
+        if let Ok(script_list) = script_list {
+            for record in script_list.script_records() {
+                let Ok(script) = record.script(script_list.offset_data()) else {
+                    continue;
+                };
+                let default = script
+                    .default_lang_sys()
+                    .and_then(Result::ok)
+                    .map(|lang_sys| language_system(None, lang_sys));
+                let others = script.lang_sys_records().iter().filter_map(|record| {
+                    let lang_sys = record.lang_sys(script.offset_data()).ok()?;
+                    Some(language_system(
+                        Some(read_tag_to_string(record.lang_sys_tag())),
+                        lang_sys,
+                    ))
+                });
+                scripts.push(LayoutScript {
+                    script: read_tag_to_string(record.script_tag()),
+                    language_systems: default.into_iter().chain(others).collect(),
+                });
+            }
+        }
+
+        LayoutTable { scripts }
+    }
+
+    /// Format the scripts, language systems and features as the lines of a tree.
+    pub fn tree(&self) -> Vec<String> {
+        fn branch(last: bool) -> (&'static str, &'static str) {
+            if last {
+                ("└── ", "    ")
+            } else {
+                ("├── ", "│   ")
+            }
+        }
+
+        let mut lines = Vec::new();
+        for script in &self.scripts {
+            lines.push(script.script.clone());
+            for (idx, language_system) in script.language_systems.iter().enumerate() {
+                let (language_branch, indent) = branch(idx + 1 == script.language_systems.len());
+                let language = language_system.language.as_deref().unwrap_or("default");
+                lines.push(format!("{language_branch}{language}"));
+
+                let features: Vec<String> = language_system
+                    .required_feature
+                    .iter()
+                    .map(|feature| format!("required: {feature}"))
+                    .chain(language_system.features.iter().map(ToString::to_string))
+                    .collect();
+                if features.is_empty() {
+                    lines.push(format!("{indent}└── no features"));
+                }
+                for (idx, feature) in features.iter().enumerate() {
+                    let (feature_branch, _) = branch(idx + 1 == features.len());
+                    lines.push(format!("{indent}{feature_branch}{feature}"));
+                }
+            }
+        }
+        lines
+    }
+}
+
+impl std::fmt::Display for LayoutFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.tag {
+            Some(tag) => write!(f, "{tag}")?,
+            None => write!(f, "feature index {} out of bounds", self.index)?,
+        }
+        let plural = if self.lookup_count == 1 { "" } else { "s" };
+        write!(f, " ({} lookup{plural})", self.lookup_count)
+    }
+}
+
+/// The scripts, language systems and features of the GSUB and GPOS tables.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Layout {
+    /// Not present if the font has no GSUB table.
+    pub gsub: Option<LayoutTable>,
+    /// Not present if the font has no GPOS table.
+    pub gpos: Option<LayoutTable>,
+}
+
+impl Layout {
+    pub fn from_font(font: FontRef<'_>) -> Self {
+        Layout {
+            gsub: read_table::<Gsub>(font)
+                .map(|gsub| LayoutTable::new(gsub.script_list(), gsub.feature_list())),
+            gpos: read_table::<Gpos>(font)
+                .map(|gpos| LayoutTable::new(gpos.script_list(), gpos.feature_list())),
+        }
+    }
+}
+
+/// Write the GSUB and GPOS features of a font as a tree of scripts, language systems and
+/// features in the human-readable format.
+pub(crate) fn write_layout(mut write: impl std::io::Write, layout: &Layout) -> std::io::Result<()> {
+    for (name, table) in [("GSUB", &layout.gsub), ("GPOS", &layout.gpos)] {
+        write!(write, "         {name} layout: ")?;
+        match table {
+            Some(table) if !table.scripts.is_empty() => {
+                for (idx, line) in table.tree().iter().enumerate() {
+                    if idx > 0 {
+                        write!(write, "\n                      ")?;
+                    }
+                    write!(write, "{line}")?;
+                }
+                writeln!(write)?;
+            }
+            Some(_) => writeln!(write, "no scripts")?,
+            None => writeln!(write, "no {name} table")?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree() {
+        let feature = |index, tag: &str, lookup_count| LayoutFeature {
+            index,
+            tag: Some(tag.to_owned()),
+            lookup_count,
+        };
+        let table = LayoutTable {
+            scripts: vec![
+                LayoutScript {
+                    script: "DFLT".to_owned(),
+                    language_systems: vec![LayoutLanguageSystem {
+                        language: None,
+                        required_feature: None,
+                        features: vec![],
+                    }],
+                },
+                LayoutScript {
+                    script: "arab".to_owned(),
+                    language_systems: vec![
+                        LayoutLanguageSystem {
+                            language: None,
+                            required_feature: Some(feature(0, "rlig", 1)),
+                            features: vec![feature(1, "liga", 2)],
+                        },
+                        LayoutLanguageSystem {
+                            language: Some("URD ".to_owned()),
+                            required_feature: None,
+                            features: vec![LayoutFeature {
+                                index: 7,
+                                tag: None,
+                                lookup_count: 0,
+                            }],
+                        },
+                    ],
+                },
+            ],
+        };
+
+        assert_eq!(
+            table.tree(),
+            [
+                "DFLT",
+                "└── default",
+                "    └── no features",
+                "arab",
+                "├── default",
+                "│   ├── required: rlig (1 lookup)",
+                "│   └── liga (2 lookups)",
+                "└── URD ",
+                "    └── feature index 7 out of bounds (0 lookups)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,1090 @@
+use std::{collections::HashMap, io::Write, path::Path};
+
+use serde::{ser::SerializeSeq, Serializer};
+
+use read_fonts::tables::{
+    gdef::Gdef,
+    head::{Head, MacStyle},
+    os2::{Os2, SelectionFlags},
+    post::Post,
+};
+use swash::{
+    scale::ScaleContext,
+    tag_from_bytes,
+    text::{Category, Codepoint},
+    FontRef, GlyphId,
+};
+
+use crate::{
+    coverage,
+    glyphs::GlyphNames,
+    names,
+    report::{count, Severity},
+    tables::read_table,
+    vertical::MetricSet,
+    FontError, Out, Outcome,
+};
+
+/// Arguments of the lint subcommand.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct LintArgs {
+    #[command(flatten)]
+    pub input: crate::files::PathArgs,
+
+    /// The lowest severity of findings that makes the exit status non-zero.
+    #[arg(long, default_value = "error")]
+    fail_on: Severity,
+}
+
+/// A lint rule. The ids are stable, so they can be used to filter findings.
+#[derive(Debug)]
+pub(crate) struct Rule {
+    pub id: &'static str,
+    pub severity: Severity,
+    pub description: &'static str,
+}
+
+const NOTDEF_MISSING: Rule = Rule {
+    id: "notdef-missing",
+    severity: Severity::Error,
+    description: "The first glyph must be the .notdef glyph.",
+};
+const NOTDEF_EMPTY: Rule = Rule {
+    id: "notdef-empty",
+    severity: Severity::Warning,
+    description: "The .notdef glyph should have an outline, so missing characters are visible.",
+};
+const SPACE_MISSING: Rule = Rule {
+    id: "space-missing",
+    severity: Severity::Error,
+    description: "The font must map U+0020 SPACE to a glyph.",
+};
+const NBSP_MISSING: Rule = Rule {
+    id: "nbsp-missing",
+    severity: Severity::Warning,
+    description: "The font should map U+00A0 NO-BREAK SPACE to a glyph.",
+};
+const WEIGHT_CLASS_MISMATCH: Rule = Rule {
+    id: "weight-class-mismatch",
+    severity: Severity::Warning,
+    description: "The OS/2 usWeightClass should match the weight in the subfamily name.",
+};
+const ITALIC_FLAGS_MISMATCH: Rule = Rule {
+    id: "italic-flags-mismatch",
+    severity: Severity::Error,
+    description: "The OS/2 fsSelection ITALIC bit and the head macStyle italic bit must agree.",
+};
+const ITALIC_ANGLE_MISMATCH: Rule = Rule {
+    id: "italic-angle-mismatch",
+    severity: Severity::Warning,
+    description:
+        "The post italic angle should be non-zero exactly if the font is flagged italic or oblique.",
+};
+const DUPLICATE_GLYPH_NAMES: Rule = Rule {
+    id: "duplicate-glyph-names",
+    severity: Severity::Error,
+    description: "Glyph names must be unique.",
+};
+const ZERO_ADVANCE_BASE_GLYPH: Rule = Rule {
+    id: "zero-advance-base-glyph",
+    severity: Severity::Warning,
+    description:
+        "Glyphs of letters, numbers, punctuation and symbols should have a non-zero advance width.",
+};
+const CMAP_EMPTY_GLYPH: Rule = Rule {
+    id: "cmap-empty-glyph",
+    severity: Severity::Warning,
+    description: "Letters, numbers, punctuation and symbols should not be mapped to glyphs without an outline.",
+};
+const FAMILY_VERTICAL_METRICS_MISMATCH: Rule = Rule {
+    id: "family-vertical-metrics-mismatch",
+    severity: Severity::Warning,
+    description: "The vertical metrics of the fonts in a family should be the same, so line heights do not change between styles.",
+};
+
+const FONT_ERROR: Rule = Rule {
+    id: "font-error",
+    severity: Severity::Error,
+    description: "The font file or font could not be read, parsed or inspected.",
+};
+
+/// All lint rules.
+pub(crate) const RULES: [&Rule; 12] = [
+    &NOTDEF_MISSING,
+    &NOTDEF_EMPTY,
+    &SPACE_MISSING,
+    &NBSP_MISSING,
+    &WEIGHT_CLASS_MISMATCH,
+    &ITALIC_FLAGS_MISMATCH,
+    &ITALIC_ANGLE_MISMATCH,
+    &DUPLICATE_GLYPH_NAMES,
+    &ZERO_ADVANCE_BASE_GLYPH,
+    &CMAP_EMPTY_GLYPH,
+    &FAMILY_VERTICAL_METRICS_MISMATCH,
+    &FONT_ERROR,
+];
+
+/// The maximum number of characters or glyphs listed in the message of a finding.
+const MAX_LISTED: usize = 10;
+
+/// Characters that are letters or symbols, but that are commonly blank, such as the Hangul
+/// fillers, the blank Braille pattern and the object replacement character.
+const BLANK_CHARACTERS: [u32; 6] = [0x115F, 0x1160, 0x2800, 0x3164, 0xFFA0, 0xFFFC];
+
+/// The GDEF glyph class of marks.
+const MARK_GLYPH_CLASS: u16 = 3;
+
+/// Weight names, as they appear in subfamily names, and their weight classes. Names containing
+/// other names come first.
+const WEIGHT_NAMES: [(&str, u16); 18] = [
+    ("extralight", 200),
+    ("ultralight", 200),
+    ("semilight", 350),
+    ("demilight", 350),
+    ("semibold", 600),
+    ("demibold", 600),
+    ("extrabold", 800),
+    ("ultrabold", 800),
+    ("extrablack", 950),
+    ("hairline", 100),
+    ("thin", 100),
+    ("light", 300),
+    ("regular", 400),
+    ("book", 400),
+    ("medium", 500),
+    ("bold", 700),
+    ("black", 900),
+    ("heavy", 900),
+];
+
+/// The largest difference between the weight class and the weight implied by the subfamily name
+/// that is not reported.
+const WEIGHT_TOLERANCE: u16 = 50;
+
+/// Get the weight class implied by a subfamily name, such as 700 for "Bold Italic". Subfamily
+/// names consisting of only "Italic" or "Oblique" imply the regular weight.
+fn weight_from_subfamily(subfamily: &str) -> Option<u16> {
+    let normalized: String = subfamily
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '-' | '_'))
+        .flat_map(char::to_lowercase)
+        .collect();
+
+    WEIGHT_NAMES
+        .iter()
+        .find(|(name, _)| normalized.contains(name))
+        .map(|&(_, weight)| weight)
+        .or_else(|| matches!(normalized.as_str(), "italic" | "oblique").then_some(400))
+}
+
+/// Whether a character of the given category is expected to have a visible glyph with an advance:
+/// letters, numbers, punctuation and symbols.
+fn is_base_category(category: Category) -> bool {
+    !matches!(
+        category,
+        Category::Other
+            | Category::Control
+            | Category::Format
+            | Category::Unassigned
+            | Category::PrivateUse
+            | Category::Surrogate
+            | Category::Mark
+            | Category::SpacingMark
+            | Category::EnclosingMark
+            | Category::NonspacingMark
+            | Category::Separator
+            | Category::LineSeparator
+            | Category::ParagraphSeparator
+            | Category::SpaceSeparator
+    )
+}
+
+/// Format a list of items, listing at most `MAX_LISTED` of them.
+fn list(items: &[String]) -> String {
+    let mut list = items
+        .iter()
+        .take(MAX_LISTED)
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(", ");
+    if items.len() > MAX_LISTED {
+        list.push_str(&format!(" and {} more", items.len() - MAX_LISTED));
+    }
+    list
+}
+
+fn describe_char(c: char) -> String {
+    format!("U+{:04X} '{c}'", u32::from(c))
+}
+
+/// Get the URI reference of a font file for SARIF logs: a `file` URI for absolute paths, and a
+/// relative reference for relative paths. Path separators become slashes and other characters
+/// are percent-encoded.
+fn file_uri(source: &str) -> String {
+    let is_absolute = Path::new(source).is_absolute();
+    let path = source.replace(std::path::MAIN_SEPARATOR, "/");
+
+    let mut uri = String::new();
+    if is_absolute {
+        uri.push_str("file://");
+        // such as "C:/Windows/Fonts"
+        if !path.starts_with('/') {
+            uri.push('/');
+        }
+    }
+    for byte in path.bytes() {
+        // a colon is only kept in absolute paths, as it would start a URI scheme otherwise
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) || (byte == b':' && is_absolute)
+        {
+            uri.push(char::from(byte));
+        } else {
+            uri.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    uri
+}
+
+/// A violation of a lint rule.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Finding {
+    pub rule_id: &'static str,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn new(rule: &Rule, message: impl Into<String>) -> Self {
+        Finding {
+            rule_id: rule.id,
+            severity: rule.severity,
+            message: message.into(),
+        }
+    }
+}
+
+/// Run the checks of a single font.
+pub(crate) fn lint_font(font: FontRef<'_>, language: Option<&str>) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let charmap = font.charmap();
+    let glyph_count = font.metrics(&[]).glyph_count;
+    let glyph_metrics = font.glyph_metrics(&[]);
+    let glyph_names = GlyphNames::new(font);
+    let has_outlines = [b"glyf", b"CFF ", b"CFF2"]
+        .iter()
+        .any(|tag| font.table(tag_from_bytes(tag)).is_some());
+
+    let mut context = ScaleContext::new();
+    let mut scaler = context.builder(font).hint(false).build();
+    let mut has_outline = |glyph_id: GlyphId| {
+        scaler
+            .scale_outline(glyph_id)
+            .is_some_and(|outline| !outline.bounds().is_empty())
+    };
+
+    if glyph_count == 0 {
+        findings.push(Finding::new(&NOTDEF_MISSING, "the font has no glyphs"));
+    } else {
+        match glyph_names.get(0) {
+            Some(name) if name != ".notdef" => findings.push(Finding::new(
+                &NOTDEF_MISSING,
+                format!("glyph 0 is named '{name}' rather than '.notdef'"),
+            )),
+            _ => {}
+        }
+        if has_outlines && !has_outline(0) {
+            findings.push(Finding::new(&NOTDEF_EMPTY, "glyph 0 has no outline"));
+        }
+    }
+
+    if charmap.map(' ') == 0 {
+        findings.push(Finding::new(
+            &SPACE_MISSING,
+            "U+0020 SPACE is not mapped to a glyph",
+        ));
+    }
+    if charmap.map('\u{A0}') == 0 {
+        findings.push(Finding::new(
+            &NBSP_MISSING,
+            "U+00A0 NO-BREAK SPACE is not mapped to a glyph",
+        ));
+    }
+
+    let os2 = read_table::<Os2>(font);
+    if let (Some(os2), Some(subfamily)) = (&os2, names::subfamily_name(font, language)) {
+        let weight_class = os2.us_weight_class();
+        if let Some(weight) = weight_from_subfamily(&subfamily) {
+            if weight_class.abs_diff(weight) > WEIGHT_TOLERANCE {
+                findings.push(Finding::new(
+                    &WEIGHT_CLASS_MISMATCH,
+                    format!(
+                        "usWeightClass is {weight_class}, but subfamily name '{subfamily}' implies {weight}"
+                    ),
+                ));
+            }
+        }
+    }
+
+    let fs_selection = os2.as_ref().map(|os2| os2.fs_selection());
+    let mac_style = read_table::<Head>(font).map(|head| head.mac_style());
+    if let (Some(fs_selection), Some(mac_style)) = (fs_selection, mac_style) {
+        let fs_selection_italic = fs_selection.contains(SelectionFlags::ITALIC);
+        let mac_style_italic = mac_style.contains(MacStyle::ITALIC);
+        if fs_selection_italic != mac_style_italic {
+            findings.push(Finding::new(
+                &ITALIC_FLAGS_MISMATCH,
+                format!(
+                    "the fsSelection ITALIC bit is {}, but the macStyle italic bit is {}",
+                    if fs_selection_italic {
+                        "set"
+                    } else {
+                        "not set"
+                    },
+                    if mac_style_italic { "set" } else { "not set" },
+                ),
+            ));
+        }
+    }
+
+    if let Some(post) = read_table::<Post>(font) {
+        let italic_angle = post.italic_angle().to_f64();
+        let flagged = fs_selection.is_some_and(|fs_selection| {
+            fs_selection.intersects(SelectionFlags::ITALIC | SelectionFlags::OBLIQUE)
+        }) || mac_style.is_some_and(|mac_style| mac_style.contains(MacStyle::ITALIC));
+        if italic_angle != 0. && !flagged {
+            findings.push(Finding::new(
+                &ITALIC_ANGLE_MISMATCH,
+                format!("the italic angle is {italic_angle}, but the font is not flagged italic or oblique"),
+            ));
+        } else if italic_angle == 0. && flagged {
+            findings.push(Finding::new(
+                &ITALIC_ANGLE_MISMATCH,
+                "the font is flagged italic or oblique, but the italic angle is 0",
+            ));
+        }
+    }
+
+    let mut glyph_ids_by_name: HashMap<String, Vec<GlyphId>> = HashMap::new();
+    for glyph_id in 0..glyph_count {
+        if let Some(name) = glyph_names.get(glyph_id) {
+            glyph_ids_by_name.entry(name).or_default().push(glyph_id);
+        }
+    }
+    let mut duplicates: Vec<(&String, &Vec<GlyphId>)> = glyph_ids_by_name
+        .iter()
+        .filter(|(_, glyph_ids)| glyph_ids.len() > 1)
+        .collect();
+    duplicates.sort_unstable_by_key(|(_, glyph_ids)| glyph_ids[0]);
+    if !duplicates.is_empty() {
+        let duplicates: Vec<String> = duplicates
+            .iter()
+            .map(|(name, glyph_ids)| {
+                let glyph_ids: Vec<String> = glyph_ids.iter().map(ToString::to_string).collect();
+                format!("'{name}' (glyphs {})", glyph_ids.join(", "))
+            })
+            .collect();
+        findings.push(Finding::new(
+            &DUPLICATE_GLYPH_NAMES,
+            format!(
+                "{} used more than once: {}",
+                count(duplicates.len(), "glyph name"),
+                list(&duplicates)
+            ),
+        ));
+    }
+
+    let glyph_classes = read_table::<Gdef>(font).and_then(|gdef| gdef.glyph_class_def()?.ok());
+    let is_mark = |glyph_id: GlyphId| {
+        glyph_classes.as_ref().is_some_and(|classes| {
+            classes.get(read_fonts::types::GlyphId::new(glyph_id)) == MARK_GLYPH_CLASS
+        })
+    };
+
+    let mut zero_advance = Vec::new();
+    let mut empty = Vec::new();
+    for codepoint in coverage::mapped_codepoints(font) {
+        let Some(c) = char::from_u32(codepoint) else {
+            continue;
+        };
+        if !is_base_category(c.category()) || BLANK_CHARACTERS.contains(&codepoint) {
+            continue;
+        }
+        let glyph_id = charmap.map(c);
+        if glyph_id == 0 || is_mark(glyph_id) {
+            continue;
+        }
+        if glyph_metrics.advance_width(glyph_id) == 0. {
+            zero_advance.push(describe_char(c));
+        }
+        if has_outlines && !has_outline(glyph_id) {
+            empty.push(describe_char(c));
+        }
+    }
+    if !zero_advance.is_empty() {
+        findings.push(Finding::new(
+            &ZERO_ADVANCE_BASE_GLYPH,
+            format!(
+                "{} mapped to glyphs with a zero advance width: {}",
+                count(zero_advance.len(), "character"),
+                list(&zero_advance)
+            ),
+        ));
+    }
+    if !empty.is_empty() {
+        findings.push(Finding::new(
+            &CMAP_EMPTY_GLYPH,
+            format!(
+                "{} mapped to glyphs without an outline: {}",
+                count(empty.len(), "character"),
+                list(&empty)
+            ),
+        ));
+    }
+
+    findings
+}
+
+/// The lint findings of a font.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct LintedFont {
+    source: String,
+    font_index: usize,
+    family_name: Option<String>,
+    subfamily_name: Option<String>,
+    findings: Vec<Finding>,
+    #[serde(skip)]
+    vertical_metrics: [(&'static str, Option<MetricSet>); 3],
+}
+
+impl LintedFont {
+    fn name(&self) -> String {
+        match (&self.family_name, &self.subfamily_name) {
+            (Some(family), Some(subfamily)) => format!("{family} {subfamily}"),
+            (Some(family), None) => family.clone(),
+            _ => "<unknown>".to_owned(),
+        }
+    }
+}
+
+/// Lints fonts. The findings are written when finished, after the fonts of each family are
+/// compared with each other.
+pub(crate) struct Linter {
+    fail_on: Severity,
+    name_language: Option<String>,
+    fonts: Vec<LintedFont>,
+    /// the font files and fonts that could not be linted, reported as `font-error` findings
+    errors: Vec<FontError>,
+}
+
+impl Linter {
+    pub fn new(args: &LintArgs, name_language: Option<String>) -> Self {
+        Linter {
+            fail_on: args.fail_on,
+            name_language,
+            fonts: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    /// The message of the `font-error` finding of an error.
+    fn error_message(error: &FontError) -> String {
+        format!("failed to {}: {}", error.stage, error.message)
+    }
+
+    /// The location of an error, such as "font.ttf" or "font.ttc#1".
+    fn error_location(error: &FontError) -> String {
+        match error.font_index {
+            Some(font_index) => format!("{}#{font_index}", error.source),
+            None => error.source.clone(),
+        }
+    }
+
+    /// Compare the vertical metrics of the fonts in each family with those of the first font of
+    /// the family.
+    fn check_families(&mut self) {
+        let mut first_of_family: HashMap<String, usize> = HashMap::new();
+        for idx in 0..self.fonts.len() {
+            let Some(family) = self.fonts[idx].family_name.clone() else {
+                continue;
+            };
+            let first = *first_of_family.entry(family).or_insert(idx);
+            if first == idx {
+                continue;
+            }
+
+            let differing: Vec<&str> = self.fonts[idx]
+                .vertical_metrics
+                .iter()
+                .zip(&self.fonts[first].vertical_metrics)
+                .filter(|((_, metrics), (_, first_metrics))| metrics != first_metrics)
+                .map(|((name, _), _)| *name)
+                .collect();
+            if !differing.is_empty() {
+                let message = format!(
+                    "the {} metrics differ from those of {} ({}#{})",
+                    differing.join(", "),
+                    self.fonts[first].name(),
+                    self.fonts[first].source,
+                    self.fonts[first].font_index
+                );
+                self.fonts[idx]
+                    .findings
+                    .push(Finding::new(&FAMILY_VERTICAL_METRICS_MISMATCH, message));
+            }
+        }
+    }
+
+    /// Check the families and get whether any finding is at least as severe as the severity to
+    /// fail on.
+    fn finish(&mut self) -> Outcome {
+        self.check_families();
+        let failed = self
+            .fonts
+            .iter()
+            .flat_map(|font| &font.findings)
+            .any(|finding| finding.severity >= self.fail_on)
+            || (!self.errors.is_empty() && FONT_ERROR.severity >= self.fail_on);
+        if failed {
+            Outcome::Fail
+        } else {
+            Outcome::Pass
+        }
+    }
+
+    /// Write the findings as lines such as "error[space-missing]: font.ttf#0 (Family Regular):
+    /// U+0020 SPACE is not mapped to a glyph", followed by a summary.
+    pub fn write_human_readable(mut self, mut write: impl Write) -> anyhow::Result<Outcome> {
+        let outcome = self.finish();
+
+        let mut counts = [0; 2];
+        for font in &self.fonts {
+            for finding in &font.findings {
+                counts[finding.severity as usize] += 1;
+                writeln!(
+                    write,
+                    "{}[{}]: {}#{} ({}): {}",
+                    finding.severity.name(),
+                    finding.rule_id,
+                    font.source,
+                    font.font_index,
+                    font.name(),
+                    finding.message
+                )?;
+            }
+        }
+        for error in &self.errors {
+            counts[FONT_ERROR.severity as usize] += 1;
+            writeln!(
+                write,
+                "{}[{}]: {}: {}",
+                FONT_ERROR.severity.name(),
+                FONT_ERROR.id,
+                Self::error_location(error),
+                Self::error_message(error)
+            )?;
+        }
+        let [warnings, errors] = counts;
+        writeln!(
+            write,
+            "{} and {} in {}",
+            count(errors, "error"),
+            count(warnings, "warning"),
+            count(self.fonts.len(), "font")
+        )?;
+
+        Ok(outcome)
+    }
+
+    /// Write the fonts and their findings as JSON, followed by the error records of the font
+    /// files and fonts that could not be linted.
+    pub fn write_json(mut self, mut write: impl Write) -> anyhow::Result<Outcome> {
+        let outcome = self.finish();
+
+        let mut serializer = serde_json::Serializer::new(&mut write);
+        let mut seq = serializer.serialize_seq(Some(self.fonts.len() + self.errors.len()))?;
+        for font in &self.fonts {
+            seq.serialize_element(font)?;
+        }
+        for error in &self.errors {
+            seq.serialize_element(error)?;
+        }
+        seq.end()?;
+        writeln!(write)?;
+
+        Ok(outcome)
+    }
+
+    /// Write the findings as a SARIF 2.1.0 log, for code scanning tools.
+    pub fn write_sarif(mut self, mut write: impl Write) -> anyhow::Result<Outcome> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Text<T> {
+            text: T,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Configuration {
+            level: &'static str,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SarifRule<'m> {
+            id: &'static str,
+            short_description: Text<&'m str>,
+            default_configuration: Configuration,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct ArtifactLocation {
+            uri: String,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct PhysicalLocation {
+            artifact_location: ArtifactLocation,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Location {
+            physical_location: PhysicalLocation,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Properties {
+            #[serde(skip_serializing_if = "Option::is_none")]
+            font_index: Option<usize>,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct SarifResult {
+            rule_id: &'static str,
+            level: &'static str,
+            message: Text<String>,
+            locations: [Location; 1],
+            properties: Properties,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Driver<'m> {
+            name: &'static str,
+            version: &'static str,
+            rules: Vec<SarifRule<'m>>,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Tool<'m> {
+            driver: Driver<'m>,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Run<'m> {
+            tool: Tool<'m>,
+            results: Vec<SarifResult>,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Log<'m> {
+            #[serde(rename = "$schema")]
+            schema: &'static str,
+            version: &'static str,
+            runs: [Run<'m>; 1],
+        }
+
+        let outcome = self.finish();
+
+        let rules = RULES
+            .iter()
+            .map(|rule| SarifRule {
+                id: rule.id,
+                short_description: Text {
+                    text: rule.description,
+                },
+                default_configuration: Configuration {
+                    level: rule.severity.name(),
+                },
+            })
+            .collect();
+        let font_results = self.fonts.iter().flat_map(|font| {
+            font.findings.iter().map(|finding| SarifResult {
+                rule_id: finding.rule_id,
+                level: finding.severity.name(),
+                message: Text {
+                    text: finding.message.clone(),
+                },
+                locations: [Location {
+                    physical_location: PhysicalLocation {
+                        artifact_location: ArtifactLocation {
+                            uri: file_uri(&font.source),
+                        },
+                    },
+                }],
+                properties: Properties {
+                    font_index: Some(font.font_index),
+                },
+            })
+        });
+        let error_results = self.errors.iter().map(|error| SarifResult {
+            rule_id: FONT_ERROR.id,
+            level: FONT_ERROR.severity.name(),
+            message: Text {
+                text: Self::error_message(error),
+            },
+            locations: [Location {
+                physical_location: PhysicalLocation {
+                    artifact_location: ArtifactLocation {
+                        uri: file_uri(&error.source),
+                    },
+                },
+            }],
+            properties: Properties {
+                font_index: error.font_index,
+            },
+        });
+        let results = font_results.chain(error_results).collect();
+
+        serde_json::to_writer(
+            &mut write,
+            &Log {
+                schema: "https://json.schemastore.org/sarif-2.1.0.json",
+                version: "2.1.0",
+                runs: [Run {
+                    tool: Tool {
+                        driver: Driver {
+                            name: env!("CARGO_PKG_NAME"),
+                            version: env!("CARGO_PKG_VERSION"),
+                            rules,
+                        },
+                    },
+                    results,
+                }],
+            },
+        )?;
+        writeln!(write)?;
+
+        Ok(outcome)
+    }
+}
+
+impl Out for Linter {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        let language = self.name_language.as_deref();
+        let vertical_metrics = crate::vertical::VerticalMetrics::from_font(font);
+
+        self.fonts.push(LintedFont {
+            source: source.to_owned(),
+            font_index,
+            family_name: names::family_name(font, language),
+            subfamily_name: names::subfamily_name(font, language),
+            findings: lint_font(font, language),
+            vertical_metrics: [
+                ("hhea", vertical_metrics.hhea),
+                ("typo", vertical_metrics.typo),
+                ("win", vertical_metrics.win),
+            ],
+        });
+
+        // the outcome depends on all fonts, see `finish`
+        Ok(Outcome::Pass)
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        self.errors.push(error.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fonts::{head_table, name_table, Data, TestFont};
+
+    /// Build a version 2 `post` table naming each glyph.
+    fn post_table(glyph_names: &[&str]) -> Vec<u8> {
+        let mut post = Data::default()
+            .u32(0x0002_0000)
+            .bytes(&[0; 28])
+            .u16(glyph_names.len() as u16);
+        let mut strings = Data::default();
+        let mut index = 258;
+        for name in glyph_names {
+            if *name == ".notdef" {
+                post = post.u16(0);
+            } else {
+                post = post.u16(index);
+                index += 1;
+                strings = strings.bytes(&[name.len() as u8]).bytes(name.as_bytes());
+            }
+        }
+        post.bytes(&strings.0).0
+    }
+
+    /// Build a version 0 `OS/2` table with the given weight class and fsSelection flags.
+    fn os2_table(weight_class: u16, fs_selection: u16) -> Vec<u8> {
+        Data::default()
+            .u16(0)
+            .u16(500)
+            .u16(weight_class)
+            .u16(5)
+            .u16(0)
+            .bytes(&[0; 32])
+            .bytes(&[0; 16])
+            .tag(b"TEST")
+            .u16(fs_selection)
+            .u16(0x20)
+            .u16(0x7A)
+            .i16(800)
+            .i16(-200)
+            .i16(0)
+            .u16(800)
+            .u16(200)
+            .0
+    }
+
+    /// Lint a font and get the messages of the findings of a rule.
+    fn findings(font: &TestFont, rule: &Rule) -> Vec<String> {
+        let data = font.build();
+        let font = FontRef::from_index(&data, 0).unwrap();
+        lint_font(font, None)
+            .into_iter()
+            .filter(|finding| finding.rule_id == rule.id)
+            .map(|finding| finding.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_weight_from_subfamily() {
+        assert_eq!(weight_from_subfamily("Regular"), Some(400));
+        assert_eq!(weight_from_subfamily("Bold Italic"), Some(700));
+        assert_eq!(weight_from_subfamily("Extra Bold"), Some(800));
+        assert_eq!(weight_from_subfamily("SemiBold"), Some(600));
+        assert_eq!(weight_from_subfamily("ExtraLight Oblique"), Some(200));
+        assert_eq!(weight_from_subfamily("Italic"), Some(400));
+        assert_eq!(weight_from_subfamily("Condensed"), None);
+    }
+
+    #[test]
+    fn test_errors_are_findings() {
+        let args = LintArgs {
+            input: Default::default(),
+            fail_on: Severity::Error,
+        };
+        let mut linter = Linter::new(&args, None);
+        linter
+            .push_error(&FontError {
+                source: "broken.ttf".to_owned(),
+                font_index: None,
+                stage: crate::Stage::Parse,
+                message: "unknown sfnt version 0x67617262".to_owned(),
+            })
+            .unwrap();
+
+        let mut output = Vec::new();
+        let outcome = linter.write_human_readable(&mut output).unwrap();
+        assert_eq!(outcome, Outcome::Fail);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "error[font-error]: broken.ttf: failed to parse: unknown sfnt version 0x67617262\n\
+             1 error and 0 warnings in 0 fonts\n"
+        );
+    }
+
+    #[test]
+    fn test_file_uri() {
+        assert_eq!(file_uri("fonts/Sans.ttf"), "fonts/Sans.ttf");
+        assert_eq!(
+            file_uri("My Fonts/Sans #2.ttf"),
+            "My%20Fonts/Sans%20%232.ttf"
+        );
+        assert_eq!(
+            file_uri("a:b/Zażółć.otf"),
+            "a%3Ab/Za%C5%BC%C3%B3%C5%82%C4%87.otf"
+        );
+        if cfg!(windows) {
+            assert_eq!(
+                file_uri(r"C:\Windows\Fonts\arial.ttf"),
+                "file:///C:/Windows/Fonts/arial.ttf"
+            );
+        } else {
+            assert_eq!(
+                file_uri("/usr/share/fonts/Noto Sans.ttf"),
+                "file:///usr/share/fonts/Noto%20Sans.ttf"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rule_ids_are_unique() {
+        for (idx, rule) in RULES.iter().enumerate() {
+            assert!(RULES[idx + 1..].iter().all(|other| other.id != rule.id));
+        }
+    }
+
+    #[test]
+    fn test_notdef_missing() {
+        let font = TestFont::new(&[(' ', 250)]);
+        let misnamed = font.table(b"post", post_table(&["space", "space"]));
+        assert_eq!(
+            findings(&misnamed, &NOTDEF_MISSING),
+            ["glyph 0 is named 'space' rather than '.notdef'"]
+        );
+
+        let font = TestFont::new(&[(' ', 250)]).table(b"post", post_table(&[".notdef", "space"]));
+        assert!(findings(&font, &NOTDEF_MISSING).is_empty());
+    }
+
+    #[test]
+    fn test_space_missing() {
+        assert_eq!(
+            findings(&TestFont::new(&[('a', 500)]), &SPACE_MISSING),
+            ["U+0020 SPACE is not mapped to a glyph"]
+        );
+        assert!(findings(&TestFont::new(&[(' ', 250)]), &SPACE_MISSING).is_empty());
+    }
+
+    #[test]
+    fn test_italic_flags_mismatch() {
+        let font = TestFont::new(&[(' ', 250)]).table(b"OS/2", os2_table(400, 0x01));
+        assert_eq!(
+            findings(&font, &ITALIC_FLAGS_MISMATCH),
+            ["the fsSelection ITALIC bit is set, but the macStyle italic bit is not set"]
+        );
+
+        // macStyle is at offset 44 of the head table, with italic as bit 1
+        let mut head = head_table(1000);
+        head[45] = 0x02;
+        let font = font.table(b"head", head);
+        assert!(findings(&font, &ITALIC_FLAGS_MISMATCH).is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_glyph_names() {
+        let font = TestFont::new(&[('a', 500), ('b', 500), ('c', 500)])
+            .table(b"post", post_table(&[".notdef", "a", "a", "c"]));
+        assert_eq!(
+            findings(&font, &DUPLICATE_GLYPH_NAMES),
+            ["1 glyph name used more than once: 'a' (glyphs 1, 2)"]
+        );
+    }
+
+    #[test]
+    fn test_zero_advance_base_glyph() {
+        // a space with a zero advance is not a letter, number, punctuation or symbol
+        let font = TestFont::new(&[(' ', 0), ('1', 0), ('a', 0), ('b', 500)]);
+        assert_eq!(
+            findings(&font, &ZERO_ADVANCE_BASE_GLYPH),
+            ["2 characters mapped to glyphs with a zero advance width: U+0031 '1', U+0061 'a'"]
+        );
+    }
+
+    #[test]
+    fn test_cmap_empty_glyph() {
+        // the glyph of 'a' is a triangle, while the .notdef glyph and the glyphs of ' ' and 'b'
+        // are empty
+        let triangle = Data::default()
+            .i16(1)
+            .i16(0)
+            .i16(0)
+            .i16(500)
+            .i16(500)
+            .u16(2)
+            .u16(0)
+            .bytes(&[0x01; 3])
+            .i16(0)
+            .i16(500)
+            .i16(-250)
+            .i16(0)
+            .i16(0)
+            .i16(500)
+            .bytes(&[0]);
+        let end = triangle.len() as u16 / 2;
+        let loca = Data::default().u16(0).u16(0).u16(0).u16(end).u16(end);
+        let font = TestFont::new(&[(' ', 250), ('a', 500), ('b', 500)])
+            .table(b"glyf", triangle.0)
+            .table(b"loca", loca.0);
+
+        assert_eq!(
+            findings(&font, &CMAP_EMPTY_GLYPH),
+            ["1 character mapped to glyphs without an outline: U+0062 'b'"]
+        );
+        assert_eq!(findings(&font, &NOTDEF_EMPTY), ["glyph 0 has no outline"]);
+
+        // fonts without outlines, such as bitmap fonts, are not checked
+        assert!(findings(&TestFont::new(&[('b', 500)]), &CMAP_EMPTY_GLYPH).is_empty());
+    }
+
+    #[test]
+    fn test_family_vertical_metrics_mismatch() {
+        let font = |family: &str, subfamily: &str, ascender: i16| {
+            let hhea = Data::default()
+                .u32(0x0001_0000)
+                .i16(ascender)
+                .i16(-200)
+                .i16(0)
+                .bytes(&[0; 24])
+                .u16(2);
+            TestFont::new(&[(' ', 250)])
+                .table(b"hhea", hhea.0)
+                .table(
+                    b"name",
+                    name_table(&[(0x409, 1, family), (0x409, 2, subfamily)]),
+                )
+                .build()
+        };
+        let args = LintArgs {
+            input: Default::default(),
+            fail_on: Severity::Error,
+        };
+        let mut linter = Linter::new(&args, None);
+        for (idx, data) in [
+            font("Sans", "Regular", 800),
+            font("Sans", "Bold", 900),
+            font("Serif", "Regular", 900),
+            font("Sans", "Italic", 800),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let font = FontRef::from_index(data, 0).unwrap();
+            linter.push_font(&format!("{idx}.ttf"), 0, font).unwrap();
+        }
+
+        assert_eq!(linter.finish(), Outcome::Pass);
+        let findings: Vec<Vec<&str>> = linter
+            .fonts
+            .iter()
+            .map(|font| {
+                font.findings
+                    .iter()
+                    .filter(|finding| finding.rule_id == FAMILY_VERTICAL_METRICS_MISMATCH.id)
+                    .map(|finding| finding.message.as_str())
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            findings,
+            [
+                vec![],
+                vec!["the hhea metrics differ from those of Sans Regular (0.ttf#0)"],
+                vec![],
+                vec![],
+            ]
+        );
+    }
+}
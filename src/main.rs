@@ -1,38 +1,128 @@
 use std::{
+    collections::HashMap,
     io::{IsTerminal, Read, Write},
+    panic::AssertUnwindSafe,
     path::{Path, PathBuf},
+    process::ExitCode,
 };
 
 use anyhow::{anyhow, Context};
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
 use serde::{ser::SerializeSeq, Serializer};
-use swash::{FontDataRef, FontRef};
-
+use swash::{FontDataRef, FontRef, NormalizedCoord, Setting};
+
+mod audit;
+mod color;
+mod coverage;
+mod dump;
+mod features;
+mod files;
+mod glyphs;
+mod kerning;
+mod layout;
+mod lint;
 mod logger;
+mod measure;
+mod names;
+mod os2;
+mod render;
+mod report;
+mod settings;
+mod shaping;
+mod substitutions;
+mod tables;
+mod technical;
+#[cfg(test)]
+mod test_fonts;
+mod validate;
+mod variations;
+mod vertical;
+
+use coverage::{Codepoints, CoverageCheck};
+use glyphs::{GlyphInfo, GlyphSelector};
+use variations::{Location, Variations};
 
 #[derive(Copy, Clone, clap::ValueEnum, Debug)]
 enum OutputFormat {
     HumanReadable,
     Json,
+    /// SARIF 2.1.0, only supported by the lint subcommand.
+    Sarif,
 }
 
 /// Print font information and metrics.
 #[derive(Parser)]
 struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Print verbose debug output.
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
 
-    /// Load all fonts in the given font file.
-    #[arg(long, group = "input")]
-    font_file: Option<PathBuf>,
+    /// The font files or directories to inspect. Directories are searched for font files,
+    /// including their subdirectories.
+    #[arg(conflicts_with_all = ["family_name", "all_installed"])]
+    paths: Vec<PathBuf>,
+
+    /// Load all fonts in the given font file. Can be given multiple times, and also accepts
+    /// directories to search for font files.
+    #[arg(long, group = "input", global = true)]
+    font_file: Vec<PathBuf>,
 
     /// Find all fonts belonging to a font family using system font loading utilities.
-    #[arg(long, group = "input")]
+    #[arg(long, group = "input", global = true)]
     family_name: Option<String>,
 
+    /// Inspect all fonts installed on the system using system font loading utilities. Each font
+    /// file is inspected once, in order of their paths, and the names the system reports for the
+    /// fonts are included. When the output is redirected, the progress is shown on stderr.
+    #[arg(long, group = "input", global = true)]
+    all_installed: bool,
+
+    /// Read the font files and directories to inspect from a file, or from stdin if this is '-'.
+    /// The entries are separated by newlines, or by NUL characters if the list contains any, such
+    /// as the output of 'find -print0'.
+    #[arg(
+        long,
+        global = true,
+        value_name = "FILE",
+        conflicts_with_all = ["family_name", "all_installed"]
+    )]
+    files_from: Option<PathBuf>,
+
+    /// Search directories at most this many levels deep. 1 only finds the font files directly in
+    /// the given directories.
+    #[arg(long, global = true, value_parser = clap::value_parser!(u64).range(1..))]
+    max_depth: Option<u64>,
+
+    /// Only inspect the files found in directories that match the glob pattern, e.g., '*.otf' or
+    /// 'static/**/*.ttf'. Patterns without a '/' are matched against the file name, others
+    /// against the path relative to the searched directory. Can be given multiple times. By
+    /// default, files with the extension of a font file are inspected.
+    #[arg(long, global = true, value_name = "PATTERN")]
+    include: Vec<glob::Pattern>,
+
+    /// Skip the files and directories found in directories that match the glob pattern, e.g.,
+    /// 'build' or '*-Test.ttf'. Patterns are matched like '--include'. Can be given multiple
+    /// times.
+    #[arg(long, global = true, value_name = "PATTERN")]
+    exclude: Vec<glob::Pattern>,
+
+    /// Only inspect the font with the given index in font collections, such as TrueType
+    /// Collection (TTC) files. The index of a font is printed as "Font index in source".
+    #[arg(long, global = true)]
+    font_index: Option<usize>,
+
+    /// Keep going when a font file cannot be read, parsed or inspected, and output an error
+    /// record for it instead. This is the default when inspecting multiple font files, such as
+    /// the fonts of a family, and can be turned off with '--keep-going=false'. The exit status is
+    /// non-zero if any error occurred.
+    #[arg(long, global = true, num_args = 0..=1, default_missing_value = "true")]
+    keep_going: Option<bool>,
+
     /// The format of the output.
-    #[arg(long, default_value = "human-readable")]
+    #[arg(long, default_value = "human-readable", global = true)]
     format: OutputFormat,
 
     /// Print a listing of font features.
@@ -42,6 +132,251 @@ struct Cli {
     /// Print a listing of supported writing systems.
     #[arg(long)]
     list_writing_systems: bool,
+
+    /// Print the scripts, language systems and features of the GSUB and GPOS tables as a tree,
+    /// with the required feature and the number of lookups of each feature.
+    #[arg(long)]
+    layout_tree: bool,
+
+    /// Print a report of the Unicode characters covered by the font's character map, grouped by
+    /// Unicode block and script.
+    #[arg(long)]
+    coverage: bool,
+
+    /// Print a report of the color font technologies the font contains, such as COLR layers,
+    /// CPAL palettes and color bitmaps, with the number of color glyphs for each.
+    #[arg(long)]
+    color: bool,
+
+    /// Check whether fonts cover the given characters, e.g., "Zażółć gęślą jaźń", or codepoints,
+    /// e.g., "U+0100-U+017F". The exit status is non-zero if any font does not cover all
+    /// requested characters. Can be given multiple times.
+    #[arg(long, value_parser = Codepoints::parse)]
+    covers: Vec<Codepoints>,
+
+    /// Check whether fonts cover the characters in the given file. See '--covers'.
+    #[arg(long)]
+    covers_file: Vec<PathBuf>,
+
+    /// Print metrics of the glyph the given character maps to, e.g., "A" or "U+00E9". Can be
+    /// given multiple times.
+    #[arg(long, value_parser = GlyphSelector::parse_char)]
+    glyph: Vec<GlyphSelector>,
+
+    /// Print metrics of the glyph with the given glyph ID. Can be given multiple times.
+    #[arg(long, value_parser = GlyphSelector::parse_id)]
+    glyph_id: Vec<GlyphSelector>,
+
+    /// Print the kerning between each pair of adjacent characters of the given text, e.g., "AV",
+    /// from the GPOS 'kern' feature, or from the legacy 'kern' table if the font has no GPOS
+    /// 'kern' feature. Can be given multiple times.
+    #[arg(long, value_parser = kerning::KernPairs::parse)]
+    kern: Vec<kerning::KernPairs>,
+
+    /// Print all kerned glyph pairs and class-based kerning the fonts define as JSON.
+    #[arg(long)]
+    kern_export: bool,
+
+    /// Print the GSUB lookups of the feature with the given tag, e.g., "liga", and the
+    /// substitutions they make, with glyph names and the characters glyphs are mapped from. Can be
+    /// given multiple times.
+    #[arg(long, value_parser = settings::parse_tag)]
+    explain_feature: Vec<swash::Tag>,
+
+    /// Scale glyph metrics to the given font size in pixels per em, rather than printing them in
+    /// font units.
+    #[arg(long)]
+    size: Option<f32>,
+
+    /// Print the table directory of each font, with the tables' sizes and checksums, and verify
+    /// the checksum adjustment of the 'head' table.
+    #[arg(long)]
+    list_tables: bool,
+
+    /// Print details of the font's OS/2 table, such as the vendor ID, the fsSelection flags, the
+    /// PANOSE classification and the Unicode and code page ranges the font claims to support.
+    #[arg(long)]
+    os2: bool,
+
+    /// Print whether the font has TrueType or CFF outlines, its container format, and its
+    /// hinting: the fpgm, prep and cvt tables, instructed glyphs, gasp ranges, CFF hints, and the
+    /// hdmx, LTSH and VDMX device tables.
+    #[arg(long)]
+    technical: bool,
+
+    /// Print the hhea, OS/2 typo and OS/2 win vertical metrics side by side, with the line height
+    /// each platform computes from them, and warn about glyphs clipping against the win metrics
+    /// and about inconsistent metrics.
+    #[arg(long)]
+    vertical_metrics: bool,
+
+    /// Write the raw bytes of the table with the given tag, e.g., "GSUB", to the file given by
+    /// '--output'. Only a single font may be selected; use '--font-index' to select a font in a
+    /// font collection.
+    #[arg(long, value_parser = settings::parse_tag, requires = "output", conflicts_with = "hexdump_table")]
+    dump_table: Option<swash::Tag>,
+
+    /// Print a hex dump of the raw bytes of the table with the given tag, e.g., "OS/2".
+    #[arg(long, value_parser = settings::parse_tag)]
+    hexdump_table: Option<swash::Tag>,
+
+    /// The file to write the table given by '--dump-table' to.
+    #[arg(short, long, requires = "dump_table")]
+    output: Option<PathBuf>,
+
+    /// Print a listing of variation axes and named instances of variable fonts.
+    #[arg(long)]
+    list_variations: bool,
+
+    /// Inspect variable fonts at the given variation coordinates, e.g., "wght=700,wdth=85". These
+    /// override the coordinates of the named instance given by '--instance'.
+    #[arg(long, global = true, value_delimiter = ',', value_parser = settings::parse_variation)]
+    variation: Vec<Setting<f32>>,
+
+    /// Print a listing of all records in the font's name table.
+    #[arg(long)]
+    list_names: bool,
+
+    /// The preferred language of names, e.g., "en" or "nl-BE". When given, the name table listing
+    /// only includes a single record per name ID, preferring this language.
+    #[arg(long)]
+    name_language: Option<String>,
+
+    /// Inspect variable fonts at the named instance with the given name or PostScript name, e.g.,
+    /// "Bold Condensed".
+    #[arg(long, global = true)]
+    instance: Option<String>,
+}
+
+#[derive(clap::Subcommand, Clone)]
+enum Command {
+    /// Render text with the fonts to a PNG or PGM image.
+    ///
+    /// Each font is rendered on its own line, e.g., 'font-info render --font-file font.ttf --text
+    /// Hello --size 32 -o out.png'.
+    Render(render::RenderArgs),
+
+    /// Shape text with the fonts and print the resulting glyph runs.
+    ///
+    /// For each glyph, this prints the glyph ID and name, the source range of its cluster in
+    /// bytes, and its advance and offset, e.g., 'font-info shape --font-file font.ttf --text
+    /// "office" --features "liga=0"'.
+    Shape(shaping::ShapeArgs),
+
+    /// Measure the width, ink bounds and line height of lines of text shaped with the fonts.
+    ///
+    /// Each line of the text is measured separately, e.g., 'font-info measure --font-file
+    /// font.ttf --text "OK" --size 14' or 'font-info measure --font-file font.ttf --file
+    /// labels.txt --size 14 --format json'.
+    Measure(measure::MeasureArgs),
+
+    /// Audit the embedding permissions and licenses of fonts.
+    ///
+    /// This prints each font's OS/2 fsType embedding permissions, license description and license
+    /// URL, and a heuristic license classification, e.g., 'font-info audit fonts/' to audit all
+    /// font files in a directory tree, or 'font-info audit --all-installed --format json' to audit
+    /// all installed fonts.
+    Audit(audit::AuditArgs),
+
+    /// Check fonts for common problems.
+    ///
+    /// Each finding has a stable rule id and a severity, e.g., 'font-info lint --family-name
+    /// "Noto Sans" --fail-on warning'. The exit status is non-zero if any finding is at least as
+    /// severe as '--fail-on'. Use '--format sarif' to produce a SARIF log for code scanning tools.
+    Lint(lint::LintArgs),
+
+    /// Check the structure of font files for problems of malformed or malicious files.
+    ///
+    /// This reports out-of-bounds offsets, overlapping or misaligned tables, bad table lengths,
+    /// cmap subtables mapping to glyphs that do not exist, truncated glyphs, composite glyphs
+    /// containing themselves and inconsistent metrics counts, e.g., 'font-info validate
+    /// --font-file upload.ttf'. The files are not parsed as fonts first, so this also explains
+    /// why files fail to parse. The exit status is non-zero if any problem is at least as severe
+    /// as '--fail-on'.
+    Validate(validate::ValidateArgs),
+}
+
+/// The fonts to inspect, as given by the command-line arguments.
+enum Input {
+    /// A single font file.
+    File(PathBuf),
+    /// The font files given and found in the directories given.
+    Files(Vec<files::FoundFile>),
+    /// The fonts of a font family found using system font loading utilities.
+    Family(String),
+    /// All fonts installed on the system, found using system font loading utilities.
+    Installed,
+    /// The font data on stdin.
+    Stdin,
+}
+
+impl Command {
+    /// The font files and directories given to the subcommand.
+    fn paths(&self) -> &[PathBuf] {
+        match self {
+            Command::Render(args) => &args.input.paths,
+            Command::Shape(args) => &args.input.paths,
+            Command::Measure(args) => &args.input.paths,
+            Command::Audit(args) => &args.input.paths,
+            Command::Lint(args) => &args.input.paths,
+            Command::Validate(args) => &args.input.paths,
+        }
+    }
+}
+
+impl Cli {
+    /// Get the fonts to inspect. Files and directories given to a subcommand are inspected in
+    /// addition to the ones given by '--font-file' and '--files-from'.
+    fn input(&self) -> anyhow::Result<Input> {
+        let paths = self.command.as_ref().map_or(&[][..], Command::paths);
+
+        // subcommands must come before the files, otherwise they are taken as file names
+        for path in &self.paths {
+            if let Some(name) = path.to_str() {
+                if !path.exists() && Cli::command().find_subcommand(name).is_some() {
+                    anyhow::bail!(
+                        "'{name}' is a subcommand, but it is given after the font files. Give the subcommand first, e.g., 'font-info {name} <PATHS>...'"
+                    );
+                }
+            }
+        }
+
+        if let Some(family_name) = &self.family_name {
+            if !paths.is_empty() {
+                anyhow::bail!("Font files cannot be given together with '--family-name'");
+            }
+            return Ok(Input::Family(family_name.clone()));
+        }
+        if self.all_installed {
+            if !paths.is_empty() {
+                anyhow::bail!("Font files cannot be given together with '--all-installed'");
+            }
+            return Ok(Input::Installed);
+        }
+
+        let mut paths: Vec<PathBuf> = self
+            .paths
+            .iter()
+            .chain(&self.font_file)
+            .chain(paths)
+            .cloned()
+            .collect();
+        match &self.files_from {
+            Some(list) => paths.extend(files::read_file_list(list)?),
+            None => match paths.as_slice() {
+                [] => return Ok(Input::Stdin),
+                [path] if !path.is_dir() => return Ok(Input::File(path.clone())),
+                _ => {}
+            },
+        }
+
+        let filter = files::FileFilter {
+            max_depth: self.max_depth.map(|max_depth| max_depth as usize),
+            include: self.include.clone(),
+            exclude: self.exclude.clone(),
+        };
+        Ok(Input::Files(files::collect_font_files(&paths, &filter)))
+    }
 }
 
 enum PrintFeatures {
@@ -54,9 +389,85 @@ enum PrintWritingSystems {
     No,
 }
 
+enum PrintLayoutTree {
+    Yes,
+    No,
+}
+
+enum PrintVariations {
+    Yes,
+    No,
+}
+
+enum PrintNames {
+    Yes,
+    No,
+}
+
+enum PrintCoverage {
+    Yes,
+    No,
+}
+
+enum PrintColor {
+    Yes,
+    No,
+}
+
+enum PrintTables {
+    Yes,
+    No,
+}
+
+enum PrintOs2 {
+    Yes,
+    No,
+}
+
+enum PrintTechnical {
+    Yes,
+    No,
+}
+
+enum PrintVerticalMetrics {
+    Yes,
+    No,
+}
+
 struct Options {
     print_features: PrintFeatures,
     print_writing_systems: PrintWritingSystems,
+    print_layout_tree: PrintLayoutTree,
+    print_variations: PrintVariations,
+    print_names: PrintNames,
+    print_coverage: PrintCoverage,
+    print_color: PrintColor,
+    print_tables: PrintTables,
+    print_os2: PrintOs2,
+    print_technical: PrintTechnical,
+    print_vertical_metrics: PrintVerticalMetrics,
+    /// the sorted and deduplicated codepoints to check the coverage of
+    covers: Option<Vec<u32>>,
+    /// the glyphs to print metrics of
+    glyphs: Vec<GlyphSelector>,
+    /// the pairs of characters to print the kerning of
+    kern_pairs: Vec<[char; 2]>,
+    /// the GSUB features to print the substitutions of
+    explain_features: Vec<swash::Tag>,
+    /// the font size in pixels per em to scale glyph metrics to
+    size: Option<f32>,
+    /// the preferred language of names
+    name_language: Option<String>,
+    /// the named instance to inspect variable fonts at
+    instance: Option<String>,
+    /// the variation coordinates to inspect variable fonts at
+    variation_settings: Vec<Setting<f32>>,
+}
+
+impl Options {
+    fn location(&self, font: FontRef<'_>) -> anyhow::Result<Location> {
+        Location::from_font(font, self.instance.as_deref(), &self.variation_settings)
+    }
 }
 
 #[derive(Debug, serde::Serialize)]
@@ -77,7 +488,9 @@ struct Metrics {
 }
 
 impl Metrics {
-    fn from_font(font: FontRef<'_>) -> Self {
+    /// Compute the metrics of the font at the given normalized variation coordinates. The
+    /// coordinates are ignored for fonts that are not variable.
+    fn from_font(font: FontRef<'_>, coords: &[NormalizedCoord]) -> Self {
         let swash::Metrics {
             glyph_count,
             units_per_em,
@@ -91,7 +504,7 @@ impl Metrics {
             underline_offset,
             strikeout_offset,
             ..
-        } = font.metrics(&[]);
+        } = font.metrics(coords);
         Metrics {
             glyph_count,
             units_per_em,
@@ -109,13 +522,110 @@ impl Metrics {
     }
 }
 
+/// The outcome of the checks requested on fonts, such as coverage checks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Outcome {
+    Pass,
+    Fail,
+}
+
+impl Outcome {
+    fn and(self, other: Outcome) -> Outcome {
+        if self == Outcome::Pass && other == Outcome::Pass {
+            Outcome::Pass
+        } else {
+            Outcome::Fail
+        }
+    }
+}
+
+/// The stage of inspecting a font file at which an error occurred.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+enum Stage {
+    Read,
+    Parse,
+    Inspect,
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Stage::Read => "read",
+            Stage::Parse => "parse",
+            Stage::Inspect => "inspect",
+        })
+    }
+}
+
+/// An error of a font file, or of one of its fonts, that was skipped to keep going.
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FontError {
+    source: String,
+    /// Not present for errors of the whole font file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    font_index: Option<usize>,
+    stage: Stage,
+    message: String,
+}
+
+impl std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to {} '{}'", self.stage, self.source)?;
+        if let Some(font_index) = self.font_index {
+            write!(f, " (font index {font_index})")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// The names of a font as reported by the system font loading utilities.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SystemNames<'a> {
+    family_name: &'a str,
+    /// Not present if the system did not report a full name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    full_name: Option<&'a str>,
+}
+
+impl<'a> SystemNames<'a> {
+    fn from_font(font: &'a font_enumeration::Font) -> Self {
+        SystemNames {
+            family_name: &font.family_name,
+            full_name: Some(font.font_name.as_str()).filter(|name| !name.is_empty()),
+        }
+    }
+}
+
 trait Out {
+    /// Output the font. Returns whether the font passed the requested checks.
     fn push_font(
         &mut self,
         source: &str,
         font_index: usize,
         font: FontRef<'_>,
-    ) -> anyhow::Result<()>;
+    ) -> anyhow::Result<Outcome>;
+
+    /// Output a font the system matched for a query, such as a font family query, with the names
+    /// the system reported for it. By default, the names are not output.
+    fn push_matched_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+        _system_names: &SystemNames<'_>,
+    ) -> anyhow::Result<Outcome> {
+        self.push_font(source, font_index, font)
+    }
+
+    /// Output an error of a font file or font that was skipped to keep going. By default, the
+    /// error is printed to stderr, for outputs that have no place for it.
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        eprintln!("Error: {error}");
+        Ok(())
+    }
 }
 
 impl<T: Out> Out for &mut T {
@@ -124,9 +634,64 @@ impl<T: Out> Out for &mut T {
         source: &str,
         font_index: usize,
         font: FontRef<'_>,
-    ) -> anyhow::Result<()> {
+    ) -> anyhow::Result<Outcome> {
         (*self).push_font(source, font_index, font)
     }
+
+    fn push_matched_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+        system_names: &SystemNames<'_>,
+    ) -> anyhow::Result<Outcome> {
+        (*self).push_matched_font(source, font_index, font, system_names)
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        (*self).push_error(error)
+    }
+}
+
+/// Write the header that starts the human-readable output of a font, including its names in the
+/// preferred language.
+fn write_font_header(
+    mut write: impl std::io::Write,
+    font_num: usize,
+    source: &str,
+    font_index: usize,
+    font: FontRef<'_>,
+    name_language: Option<&str>,
+) -> std::io::Result<()> {
+    write!(write, "-[ FONT {} ]-", font_num)?;
+
+    {
+        let char_width_idx = font_num.ilog10() + 1;
+        for _ in 0..60 - 11 - char_width_idx {
+            write!(write, "-")?;
+        }
+        writeln!(write)?;
+    }
+
+    writeln!(write, "              Source: {source}")?;
+    writeln!(write, "Font index in source: {font_index}")?;
+
+    writeln!(
+        write,
+        "         Family name: {}",
+        names::family_name(font, name_language)
+            .as_deref()
+            .unwrap_or("<unknown>")
+    )?;
+    writeln!(
+        write,
+        "      Subfamily name: {}",
+        names::subfamily_name(font, name_language)
+            .as_deref()
+            .unwrap_or("<unknown>")
+    )?;
+
+    Ok(())
 }
 
 struct HumanReadable<W> {
@@ -147,27 +712,38 @@ impl<W> HumanReadable<W> {
     }
 }
 
-impl<W: std::io::Write> Out for HumanReadable<W> {
-    fn push_font(
+impl<W: std::io::Write> HumanReadable<W> {
+    fn write_font(
         &mut self,
         source: &str,
         font_index: usize,
         font: FontRef<'_>,
-    ) -> anyhow::Result<()> {
-        let font_num = self.fonts_written + 1;
-        write!(self.write, "-[ FONT {} ]-", font_num)?;
-
-        {
-            let char_width_idx = font_num.ilog10() + 1;
-            for _ in 0..60 - 11 - char_width_idx {
-                write!(self.write, "-")?;
+        system_names: Option<&SystemNames<'_>>,
+    ) -> anyhow::Result<Outcome> {
+        let mut outcome = Outcome::Pass;
+        // resolved first, so nothing is written for a font that has no such location
+        let location = self.options.location(font)?;
+
+        write_font_header(
+            &mut self.write,
+            self.fonts_written + 1,
+            source,
+            font_index,
+            font,
+            self.options.name_language.as_deref(),
+        )?;
+
+        if let Some(system_names) = system_names {
+            writeln!(
+                self.write,
+                "  System family name: {}",
+                system_names.family_name
+            )?;
+            if let Some(full_name) = system_names.full_name {
+                writeln!(self.write, "    System full name: {full_name}")?;
             }
-            writeln!(self.write)?;
         }
 
-        writeln!(self.write, "              Source: {source}")?;
-        writeln!(self.write, "Font index in source: {font_index}")?;
-
         {
             let attributes = font.attributes();
             let weight = attributes.weight().0;
@@ -179,19 +755,32 @@ impl<W: std::io::Write> Out for HumanReadable<W> {
             writeln!(self.write, "             Stretch: {stretch:.2}")?;
         }
 
+        variations::write_coordinates(&mut self.write, &location)?;
+
+        if matches!(self.options.print_variations, PrintVariations::Yes) {
+            variations::write_variations(
+                &mut self.write,
+                &Variations::from_font(font, self.options.name_language.as_deref()),
+            )?;
+        }
+
+        if matches!(self.options.print_tables, PrintTables::Yes) {
+            tables::write_table_directory(
+                &mut self.write,
+                &tables::TableDirectory::from_font(font),
+            )?;
+        }
+
+        if matches!(self.options.print_os2, PrintOs2::Yes) {
+            os2::write_os2(&mut self.write, os2::Os2::from_font(font).as_ref())?;
+        }
+
+        if matches!(self.options.print_technical, PrintTechnical::Yes) {
+            technical::write_technical(&mut self.write, &technical::Technical::from_font(font))?;
+        }
+
         if matches!(self.options.print_features, PrintFeatures::Yes) {
-            write!(self.write, "            Features: ")?;
-            for (idx, feat) in font.features().enumerate() {
-                if idx > 0 {
-                    write!(self.write, "\n                      ")?;
-                }
-                if let Some(name) = feat.name() {
-                    write!(self.write, "{}:{:?}", name, feat.action())?;
-                } else {
-                    write!(self.write, "{:#X}:{:?}", feat.tag(), feat.action())?;
-                }
-            }
-            writeln!(self.write)?;
+            features::write_features(&mut self.write, font, self.options.name_language.as_deref())?;
         }
 
         if matches!(self.options.print_writing_systems, PrintWritingSystems::Yes) {
@@ -229,6 +818,60 @@ impl<W: std::io::Write> Out for HumanReadable<W> {
             writeln!(self.write)?;
         }
 
+        if matches!(self.options.print_layout_tree, PrintLayoutTree::Yes) {
+            layout::write_layout(&mut self.write, &layout::Layout::from_font(font))?;
+        }
+
+        if matches!(self.options.print_names, PrintNames::Yes) {
+            names::write_names(
+                &mut self.write,
+                &names::names(font, self.options.name_language.as_deref()),
+            )?;
+        }
+
+        if matches!(self.options.print_coverage, PrintCoverage::Yes) {
+            coverage::write_coverage(&mut self.write, &coverage::Coverage::from_font(font))?;
+        }
+
+        if matches!(self.options.print_color, PrintColor::Yes) {
+            color::write_color(
+                &mut self.write,
+                &color::Color::from_font(font, self.options.name_language.as_deref()),
+            )?;
+        }
+
+        if let Some(codepoints) = &self.options.covers {
+            let check = CoverageCheck::from_font(font, codepoints);
+            if !check.passed() {
+                outcome = Outcome::Fail;
+            }
+            coverage::write_coverage_check(&mut self.write, &check)?;
+        }
+
+        if !self.options.glyphs.is_empty() {
+            glyphs::write_glyphs(
+                &mut self.write,
+                &GlyphInfo::from_font(
+                    font,
+                    &self.options.glyphs,
+                    &location.normalized,
+                    self.options.size,
+                ),
+            )?;
+        }
+
+        if !self.options.kern_pairs.is_empty() {
+            kerning::write_char_pair_kerning(
+                &mut self.write,
+                &kerning::CharPairKerning::from_font(font, &self.options.kern_pairs),
+            )?;
+        }
+
+        substitutions::write_feature_substitutions(
+            &mut self.write,
+            &substitutions::FeatureSubstitutions::from_font(font, &self.options.explain_features),
+        )?;
+
         let Metrics {
             glyph_count,
             units_per_em,
@@ -242,7 +885,7 @@ impl<W: std::io::Write> Out for HumanReadable<W> {
             stroke_size,
             underline_offset,
             strikeout_offset,
-        } = Metrics::from_font(font);
+        } = Metrics::from_font(font, &location.normalized);
 
         writeln!(self.write, "         Glyph count: {glyph_count}")?;
         writeln!(self.write, "        Units per em: {units_per_em}")?;
@@ -257,8 +900,50 @@ impl<W: std::io::Write> Out for HumanReadable<W> {
         writeln!(self.write, "    Underline offset: {underline_offset}")?;
         writeln!(self.write, "    Strikeout offset: {strikeout_offset}")?;
 
+        if matches!(
+            self.options.print_vertical_metrics,
+            PrintVerticalMetrics::Yes
+        ) {
+            vertical::write_vertical_metrics(
+                &mut self.write,
+                &vertical::VerticalMetrics::from_font(font),
+            )?;
+        }
+
         self.fonts_written += 1;
 
+        Ok(outcome)
+    }
+}
+
+impl<W: std::io::Write> Out for HumanReadable<W> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        self.write_font(source, font_index, font, None)
+    }
+
+    fn push_matched_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+        system_names: &SystemNames<'_>,
+    ) -> anyhow::Result<Outcome> {
+        self.write_font(source, font_index, font, Some(system_names))
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        writeln!(self.write, "-[ ERROR ]{}", "-".repeat(50))?;
+        writeln!(self.write, "              Source: {}", error.source)?;
+        if let Some(font_index) = error.font_index {
+            writeln!(self.write, "Font index in source: {font_index}")?;
+        }
+        writeln!(self.write, "               Stage: {}", error.stage)?;
+        writeln!(self.write, "               Error: {}", error.message)?;
         Ok(())
     }
 }
@@ -268,6 +953,8 @@ impl<W: std::io::Write> Out for HumanReadable<W> {
 struct Feature {
     feature: u32,
     action: &'static str,
+    #[serde(flatten)]
+    description: features::FeatureDescription,
 }
 
 #[derive(serde::Serialize)]
@@ -298,39 +985,93 @@ impl<W: serde::ser::SerializeSeq> Json<W> {
     }
 }
 
-impl<W: serde::ser::SerializeSeq<Error = serde_json::Error>> Out for Json<W> {
-    fn push_font(
+impl<W: serde::ser::SerializeSeq<Error = serde_json::Error>> Json<W> {
+    fn write_font(
         &mut self,
         source: &str,
         font_index: usize,
         font: FontRef<'_>,
-    ) -> anyhow::Result<()> {
+        system_names: Option<&SystemNames<'_>>,
+    ) -> anyhow::Result<Outcome> {
         #[derive(serde::Serialize)]
         #[serde(rename_all = "camelCase")]
         struct Out<'m> {
             source: &'m str,
             font_index: usize,
+            family_name: Option<&'m str>,
+            subfamily_name: Option<&'m str>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            system_names: Option<&'m SystemNames<'m>>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            tables: Option<&'m tables::TableDirectory>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            os2: Option<&'m os2::Os2>,
             #[serde(skip_serializing_if = "Option::is_none")]
             features: Option<&'m [Feature]>,
             #[serde(skip_serializing_if = "Option::is_none")]
             writing_systems: Option<&'m [WritingSystem]>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            layout: Option<&'m layout::Layout>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            technical: Option<&'m technical::Technical>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            variations: Option<&'m Variations>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            names: Option<&'m [names::Name]>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            coverage: Option<&'m coverage::Coverage>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            color: Option<&'m color::Color>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            covers: Option<&'m CoverageCheck>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            glyphs: Option<&'m [GlyphInfo]>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            kerning: Option<&'m [kerning::CharPairKerning]>,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            substitutions: Option<&'m [substitutions::FeatureSubstitutions]>,
+            coordinates: &'m [variations::Coordinate],
             metrics: &'m Metrics,
+            #[serde(skip_serializing_if = "Option::is_none")]
+            vertical_metrics: Option<&'m vertical::VerticalMetrics>,
         }
 
+        let location = self.options.location(font)?;
+        let language = self.options.name_language.as_deref();
+        let coverage_check = self
+            .options
+            .covers
+            .as_ref()
+            .map(|codepoints| CoverageCheck::from_font(font, codepoints));
+
         self.serializer.serialize_element(&Out {
             source,
             font_index,
+            family_name: names::family_name(font, language).as_deref(),
+            subfamily_name: names::subfamily_name(font, language).as_deref(),
+            system_names,
+            tables: matches!(self.options.print_tables, PrintTables::Yes)
+                .then(|| tables::TableDirectory::from_font(font))
+                .as_ref(),
+            os2: matches!(self.options.print_os2, PrintOs2::Yes)
+                .then(|| os2::Os2::from_font(font))
+                .flatten()
+                .as_ref(),
+            technical: matches!(self.options.print_technical, PrintTechnical::Yes)
+                .then(|| technical::Technical::from_font(font))
+                .as_ref(),
             features: matches!(self.options.print_features, PrintFeatures::Yes).then(|| {
                 self.features.clear();
-                for feature in font.features() {
-                    let action = match feature.action() {
+                for (tag, action) in features::font_features(font) {
+                    let action = match action {
                         swash::Action::Attachment => "attachment",
                         swash::Action::Adjustment => "adjustment",
                         swash::Action::Substitution => "substition",
                     };
                     self.features.push(Feature {
-                        feature: feature.tag(),
+                        feature: tag,
                         action,
+                        description: features::FeatureDescription::from_font(font, tag, language),
                     });
                 }
                 &*self.features
@@ -346,102 +1087,570 @@ impl<W: serde::ser::SerializeSeq<Error = serde_json::Error>> Out for Json<W> {
                     }
                     &*self.writing_systems
                 }),
-            metrics: &Metrics::from_font(font),
+            layout: matches!(self.options.print_layout_tree, PrintLayoutTree::Yes)
+                .then(|| layout::Layout::from_font(font))
+                .as_ref(),
+            variations: matches!(self.options.print_variations, PrintVariations::Yes)
+                .then(|| Variations::from_font(font, language))
+                .as_ref(),
+            names: matches!(self.options.print_names, PrintNames::Yes)
+                .then(|| names::names(font, language))
+                .as_deref(),
+            coverage: matches!(self.options.print_coverage, PrintCoverage::Yes)
+                .then(|| coverage::Coverage::from_font(font))
+                .as_ref(),
+            color: matches!(self.options.print_color, PrintColor::Yes)
+                .then(|| color::Color::from_font(font, language))
+                .as_ref(),
+            covers: coverage_check.as_ref(),
+            glyphs: (!self.options.glyphs.is_empty())
+                .then(|| {
+                    GlyphInfo::from_font(
+                        font,
+                        &self.options.glyphs,
+                        &location.normalized,
+                        self.options.size,
+                    )
+                })
+                .as_deref(),
+            kerning: (!self.options.kern_pairs.is_empty())
+                .then(|| kerning::CharPairKerning::from_font(font, &self.options.kern_pairs))
+                .as_deref(),
+            substitutions: (!self.options.explain_features.is_empty())
+                .then(|| {
+                    substitutions::FeatureSubstitutions::from_font(
+                        font,
+                        &self.options.explain_features,
+                    )
+                })
+                .as_deref(),
+            coordinates: &location.coordinates,
+            metrics: &Metrics::from_font(font, &location.normalized),
+            vertical_metrics: matches!(
+                self.options.print_vertical_metrics,
+                PrintVerticalMetrics::Yes
+            )
+            .then(|| vertical::VerticalMetrics::from_font(font))
+            .as_ref(),
         })?;
 
+        Ok(match coverage_check {
+            Some(check) if !check.passed() => Outcome::Fail,
+            _ => Outcome::Pass,
+        })
+    }
+}
+
+impl<W: serde::ser::SerializeSeq<Error = serde_json::Error>> Out for Json<W> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        self.write_font(source, font_index, font, None)
+    }
+
+    fn push_matched_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+        system_names: &SystemNames<'_>,
+    ) -> anyhow::Result<Outcome> {
+        self.write_font(source, font_index, font, Some(system_names))
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        self.serializer.serialize_element(error)?;
         Ok(())
     }
 }
 
-fn main_(cli: Cli, mut out: impl Out) -> anyhow::Result<()> {
-    match (cli.font_file, cli.family_name) {
-        (Some(font_file), None) => {
-            let font_file_name = font_file.to_string_lossy();
+/// Get the error for font data that fails to parse, with the first structural problem of the
+/// data, such as an unsupported web font format or a truncated table directory.
+fn parse_error(data: &[u8]) -> anyhow::Error {
+    match validate::first_error(data) {
+        Some(problem) => anyhow!("{problem}"),
+        None => anyhow!("the data is not a supported font file"),
+    }
+}
 
-            log::info!("Reading font file '{font_file_name}'");
+/// Get the fonts in the font data with their indices, or only the font with the given index.
+fn select_fonts(
+    font_data: FontDataRef<'_>,
+    font_index: Option<usize>,
+) -> impl Iterator<Item = (usize, FontRef<'_>)> {
+    font_data
+        .fonts()
+        .enumerate()
+        .filter(move |&(idx, _)| font_index.is_none_or(|font_index| font_index == idx))
+}
 
-            let data = std::fs::read(&font_file)?;
-            let font_data = FontDataRef::new(&data)
-                .ok_or_else(|| anyhow!("Failed to parse font file: '{font_file_name}'",))?;
+/// Check the font data has a font with the given index, if one is given.
+fn check_font_index(
+    font_data: FontDataRef<'_>,
+    font_index: Option<usize>,
+    source: &str,
+) -> anyhow::Result<()> {
+    match font_index {
+        Some(font_index) if font_index >= font_data.len() => anyhow::bail!(
+            "There is no font with index {font_index} in '{source}', which contains {} fonts",
+            font_data.len()
+        ),
+        _ => Ok(()),
+    }
+}
 
-            for (idx, font) in font_data.fonts().enumerate() {
-                out.push_font(&font_file_name, idx, font)?;
-            }
+/// Group fonts found using system font loading utilities by font file, in the order the font
+/// files are found, and sort the fonts of each file by their index in the file. Fonts with the
+/// same index, such as the named instances of a variable font, are only included once.
+fn group_by_file<'a>(
+    fonts: impl IntoIterator<Item = &'a font_enumeration::Font>,
+) -> Vec<(PathBuf, Vec<font_enumeration::Font>)> {
+    let mut font_files: Vec<(PathBuf, Vec<font_enumeration::Font>)> = Vec::new();
+    let mut indices: HashMap<&Path, usize> = HashMap::new();
+    for font in fonts {
+        let idx = *indices.entry(&font.path).or_insert_with(|| {
+            font_files.push((font.path.clone(), Vec::new()));
+            font_files.len() - 1
+        });
+        let fonts = &mut font_files[idx].1;
+        if !fonts
+            .iter()
+            .any(|other| other.face_index == font.face_index)
+        {
+            fonts.push(font.clone());
+        }
+    }
+    for (_, fonts) in &mut font_files {
+        fonts.sort_by_key(|font| font.face_index);
+    }
+    font_files
+}
+
+/// Get the fonts of a font family using system font loading utilities, grouped by font file. See
+/// [`group_by_file`].
+fn family_fonts(family_name: &str) -> Vec<(PathBuf, Vec<font_enumeration::Font>)> {
+    log::info!("Querying for font family '{family_name}'");
+
+    let font_collection = font_enumeration::Collection::new().unwrap();
+    group_by_file(font_collection.by_family(family_name))
+}
+
+/// Get all fonts installed on the system using system font loading utilities, grouped by font
+/// file and sorted by path. See [`group_by_file`].
+fn installed_fonts() -> anyhow::Result<Vec<(PathBuf, Vec<font_enumeration::Font>)>> {
+    log::info!("Querying for all installed fonts");
+
+    let font_collection = font_enumeration::Collection::new()
+        .context("Failed enumerating the fonts installed on the system")?;
+    let mut font_files = group_by_file(font_collection.all());
+    font_files.sort_unstable_by(|(path, _), (other_path, _)| path.cmp(other_path));
+    Ok(font_files)
+}
+
+/// A progress indicator of inspecting font files on stderr. It is only shown if stderr is an
+/// interactive terminal and stdout is not, so it does not get mixed up with the output, and if
+/// no verbose output is logged.
+struct Progress<W> {
+    total: usize,
+    /// Where the progress is written, or `None` if it is not shown.
+    write: Option<W>,
+}
+
+impl Progress<std::io::Stderr> {
+    fn new(total: usize) -> Self {
+        let enabled = std::io::stderr().is_terminal()
+            && !std::io::stdout().is_terminal()
+            && log::max_level() == log::LevelFilter::Off;
+        Progress {
+            total,
+            write: enabled.then(std::io::stderr),
         }
-        (None, Some(family_name)) => {
-            log::info!("Querying for font family '{family_name}'");
+    }
+}
 
-            let font_collection = font_enumeration::Collection::new().unwrap();
+impl<W: Write> Progress<W> {
+    /// Show the number of font files inspected so far. Errors writing the progress are ignored,
+    /// as they do not affect the output.
+    fn update(&mut self, done: usize) {
+        if let Some(write) = &mut self.write {
+            let _ = write!(write, "\rInspected {done} of {} font files", self.total);
+            let _ = write.flush();
+        }
+    }
 
-            let mut font_files: Vec<&'_ Path> = Vec::new();
-            for font in font_collection.by_family(&family_name) {
-                if !font_files.contains(&font.path.as_ref()) {
-                    font_files.push(font.path.as_ref());
-                }
-            }
+    /// End the progress indicator's line.
+    fn finish(&mut self) {
+        if let Some(write) = &mut self.write {
+            let _ = writeln!(write);
+        }
+    }
+}
+
+/// Read a font file into the buffer, replacing its contents.
+fn read_font_file(font_file: &Path, data: &mut Vec<u8>) -> anyhow::Result<()> {
+    data.clear();
 
+    log::info!("Reading font file '{}'", font_file.to_string_lossy());
+
+    let mut file = std::fs::File::open(font_file).with_context(|| "Failed opening font file")?;
+    if let Ok(metadata) = file.metadata() {
+        data.reserve(metadata.len() as usize);
+    }
+    file.read_to_end(data)
+        .with_context(|| "Failed reading font file")?;
+    Ok(())
+}
+
+/// Read font data from stdin, but only if it is not a tty.
+/// Get the source and data of the font file or the font data on stdin to inspect, for outputs
+/// of a single font.
+fn single_font_data(cli: &Cli) -> anyhow::Result<(String, Vec<u8>)> {
+    match cli.input()? {
+        Input::File(font_file) => {
+            let source = font_file.to_string_lossy().into_owned();
             let mut data = Vec::new();
-            for font_file in font_files {
-                data.clear();
+            read_font_file(&font_file, &mut data)
+                .with_context(|| format!("Failed reading '{source}'"))?;
+            Ok((source, data))
+        }
+        Input::Stdin => Ok(("stdin".to_owned(), read_stdin()?)),
+        Input::Files(_) | Input::Family(_) | Input::Installed => anyhow::bail!(
+            "Only the table of a single font can be written, but multiple fonts were selected. \
+            Use '--font-file' to select a font file and '--font-index' to select a font in a \
+            font collection."
+        ),
+    }
+}
 
-                let font_file_name = font_file.to_string_lossy();
+fn read_stdin() -> anyhow::Result<Vec<u8>> {
+    let mut stdin = std::io::stdin().lock();
 
-                log::info!("Reading font file '{font_file_name}'");
+    if stdin.is_terminal() {
+        anyhow::bail!("Font files, '--files-from' or '--family-name' must be given. If none are given and stdin is not an interactive terminal, this program attempts to parse the data on stdin as a font file.");
+    }
 
-                let mut file =
-                    std::fs::File::open(font_file).with_context(|| "Failed opening font file")?;
-                if let Ok(metadata) = file.metadata() {
-                    data.reserve(metadata.len() as usize);
-                }
-                file.read_to_end(&mut data)
-                    .with_context(|| "Failed reading font file")?;
+    log::info!("Reading font data from stdin.");
 
-                let font_data = FontDataRef::new(&data)
-                    .ok_or_else(|| anyhow!("Failed to parse font file: '{font_file_name}'",))?;
+    let mut data = Vec::new();
+    stdin.read_to_end(&mut data)?;
+    Ok(data)
+}
 
-                for (idx, font) in font_data.fonts().enumerate() {
-                    out.push_font(&font_file_name, idx, font)?;
-                }
+/// Validate the font files given by the command-line arguments, or the font data on stdin. Font
+/// files and directories that cannot be read are reported as errors when keeping going.
+fn validate_(cli: Cli, out: &mut validate::Validator) -> anyhow::Result<()> {
+    // like inspecting, only a single font file stops at the first error by default
+    let mut keep_going = cli.keep_going.unwrap_or(true);
+    let font_files = match cli.input()? {
+        Input::File(font_file) => {
+            keep_going = cli.keep_going.unwrap_or(false);
+            vec![files::FoundFile::Font(font_file)]
+        }
+        Input::Files(font_files) => font_files,
+        Input::Family(family_name) => family_fonts(&family_name)
+            .into_iter()
+            .map(|(font_file, _)| files::FoundFile::Font(font_file))
+            .collect(),
+        Input::Installed => installed_fonts()?
+            .into_iter()
+            .map(|(font_file, _)| files::FoundFile::Font(font_file))
+            .collect(),
+        Input::Stdin => {
+            out.push_file("stdin", &read_stdin()?);
+            return Ok(());
+        }
+    };
+
+    let mut data = Vec::new();
+    for found in font_files {
+        let source = found.path().to_string_lossy().into_owned();
+        let read = found
+            .into_font_file()
+            .and_then(|font_file| read_font_file(&font_file, &mut data));
+        match read {
+            Ok(()) => out.push_file(&source, &data),
+            Err(err) if keep_going => out.push_read_error(&source, &err),
+            Err(err) => return Err(err.context(format!("Failed to read '{source}'"))),
+        }
+    }
+    Ok(())
+}
+
+/// Inspects the fonts of font files, and outputs errors as error records when keeping going.
+struct Inspector<O> {
+    out: O,
+    font_index: Option<usize>,
+    keep_going: bool,
+    /// Whether all fonts passed the requested checks and no errors occurred.
+    outcome: Outcome,
+}
+
+impl<O: Out> Inspector<O> {
+    fn new(out: O, font_index: Option<usize>, keep_going: bool) -> Self {
+        Inspector {
+            out,
+            font_index,
+            keep_going,
+            outcome: Outcome::Pass,
+        }
+    }
+
+    /// Run a stage of inspecting a font file. When keeping going, errors and panics of the stage
+    /// are output as error records and `None` is returned. Errors writing the output are never
+    /// skipped, as the output of the following fonts would fail as well.
+    fn stage<T>(
+        &mut self,
+        source: &str,
+        font_index: Option<usize>,
+        stage: Stage,
+        run: impl FnOnce(&mut O) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Option<T>> {
+        let result = if self.keep_going {
+            std::panic::catch_unwind(AssertUnwindSafe(|| run(&mut self.out)))
+        } else {
+            Ok(run(&mut self.out))
+        };
+
+        let message = match result {
+            Ok(Ok(value)) => return Ok(Some(value)),
+            Ok(Err(err))
+                if stage == Stage::Inspect
+                    && (!self.keep_going || err.downcast_ref::<std::io::Error>().is_some()) =>
+            {
+                return Err(err)
             }
+            Ok(Err(err)) => format!("{err:#}"),
+            Err(payload) => match payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            {
+                Some(message) => format!("panicked: {message}"),
+                None => "panicked".to_owned(),
+            },
+        };
+        let error = FontError {
+            source: source.to_owned(),
+            font_index,
+            stage,
+            message,
+        };
+        if !self.keep_going {
+            anyhow::bail!("{error}");
+        }
+
+        self.outcome = Outcome::Fail;
+        self.out.push_error(&error)?;
+        Ok(None)
+    }
+
+    /// Read a font file into the buffer and inspect its fonts. See [`Inspector::push_data`].
+    fn push_file(
+        &mut self,
+        font_file: &Path,
+        data: &mut Vec<u8>,
+        check_index: bool,
+        matched: Option<&[font_enumeration::Font]>,
+    ) -> anyhow::Result<()> {
+        let font_file_name = font_file.to_string_lossy();
+        let read = self.stage(&font_file_name, None, Stage::Read, |_| {
+            read_font_file(font_file, data)
+        })?;
+        if read.is_some() {
+            self.push_data(&font_file_name, data, check_index, matched)?;
         }
-        (None, None) => {
-            // read from stdin, but only if it is not a tty
-            let mut stdin = std::io::stdin().lock();
+        Ok(())
+    }
 
-            if stdin.is_terminal() {
-                anyhow::bail!("Command-line argument '--font-file' or '--family-name' must be given. If neither argument is given and stdin is not an interactive terminal, this program attempts to parse the data on stdin as a font file.");
+    /// Inspect the fonts of font file data. If `check_index` is set, it is an error if the data
+    /// has no font with the font index to inspect. If the fonts of the data the system matched
+    /// for a query are given, only those fonts are inspected, unless the system did not report
+    /// their indices.
+    fn push_data(
+        &mut self,
+        source: &str,
+        data: &[u8],
+        check_index: bool,
+        matched: Option<&[font_enumeration::Font]>,
+    ) -> anyhow::Result<()> {
+        let font_index = self.font_index;
+        let font_data = self.stage(source, None, Stage::Parse, |_| {
+            FontDataRef::new(data).ok_or_else(|| parse_error(data))
+        })?;
+        let Some(font_data) = font_data else {
+            return Ok(());
+        };
+        if check_index {
+            check_font_index(font_data, font_index, source)?;
+        }
+
+        let Some(matched) =
+            matched.filter(|matched| matched.iter().all(|font| font.face_index.is_some()))
+        else {
+            for (idx, font) in select_fonts(font_data, font_index) {
+                self.push_font(source, idx, font, None)?;
             }
+            return Ok(());
+        };
 
-            log::info!("Reading font data from stdin.");
+        for system_font in matched {
+            let idx = system_font.face_index.expect("checked above") as usize;
+            if font_index.is_some_and(|font_index| font_index != idx) {
+                continue;
+            }
+            let font = self.stage(source, Some(idx), Stage::Parse, |_| {
+                font_data.get(idx).ok_or_else(|| {
+                    anyhow!(
+                        "the system reported a font with index {idx}, but the file contains {} fonts",
+                        font_data.len()
+                    )
+                })
+            })?;
+            if let Some(font) = font {
+                self.push_font(source, idx, font, Some(system_font))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Inspect a font, with the names the system reported for it if it was matched for a query.
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+        system_font: Option<&font_enumeration::Font>,
+    ) -> anyhow::Result<()> {
+        let outcome = self.stage(
+            source,
+            Some(font_index),
+            Stage::Inspect,
+            |out| match system_font {
+                Some(system_font) => {
+                    let system_names = SystemNames::from_font(system_font);
+                    out.push_matched_font(source, font_index, font, &system_names)
+                }
+                None => out.push_font(source, font_index, font),
+            },
+        )?;
+        if let Some(outcome) = outcome {
+            self.outcome = self.outcome.and(outcome);
+        }
+        Ok(())
+    }
+}
+
+fn main_(cli: Cli, out: impl Out) -> anyhow::Result<Outcome> {
+    let input = cli.input()?;
+    inspect(&cli, input, out)
+}
+
+/// Inspect the fonts to inspect. A single font file or the font data on stdin stops at the first
+/// error by default, while multiple font files keep going.
+fn inspect(cli: &Cli, input: Input, out: impl Out) -> anyhow::Result<Outcome> {
+    let mut inspector = Inspector::new(out, cli.font_index, cli.keep_going.unwrap_or(false));
+
+    match input {
+        Input::File(font_file) => {
+            inspector.push_file(&font_file, &mut Vec::new(), true, None)?;
+        }
+        Input::Files(font_files) => {
+            inspector.keep_going = cli.keep_going.unwrap_or(true);
 
             let mut data = Vec::new();
-            stdin.read_to_end(&mut data)?;
+            for found in font_files {
+                let source = found.path().to_string_lossy().into_owned();
+                match found.into_font_file() {
+                    Ok(font_file) => inspector.push_file(&font_file, &mut data, false, None)?,
+                    Err(err) => {
+                        inspector.stage(&source, None, Stage::Read, |_| Err::<(), _>(err))?;
+                    }
+                }
+            }
+        }
+        Input::Family(family_name) => {
+            // a family may have many font files, so keep going by default
+            inspector.keep_going = cli.keep_going.unwrap_or(true);
 
-            let font_data = swash::FontDataRef::new(&data)
-                .ok_or_else(|| anyhow!("Failed to parse font file",))?;
+            let mut data = Vec::new();
+            for (font_file, fonts) in family_fonts(&family_name) {
+                inspector.push_file(&font_file, &mut data, false, Some(&fonts))?;
+            }
+        }
+        Input::Installed => {
+            inspector.keep_going = cli.keep_going.unwrap_or(true);
+
+            let font_files = installed_fonts()?;
+            let mut progress = Progress::new(font_files.len());
+            progress.update(0);
 
-            for (font_idx, font) in font_data.fonts().enumerate() {
-                out.push_font("stdin", font_idx, font)?;
+            let mut data = Vec::new();
+            for (idx, (font_file, fonts)) in font_files.iter().enumerate() {
+                inspector.push_file(font_file, &mut data, false, Some(fonts))?;
+                progress.update(idx + 1);
             }
+            progress.finish();
         }
-        (Some(_), Some(_)) => {
-            // cli arguments --font-file and --family-name are mutually exclusive
-            unreachable!()
+        Input::Stdin => {
+            let data = read_stdin()?;
+            inspector.push_data("stdin", &data, true, None)?;
         }
     };
 
-    Ok(())
+    Ok(inspector.outcome)
 }
 
-fn main() -> anyhow::Result<()> {
+/// Get the glyphs selected with '--glyph' and '--glyph-id', in the order they are given.
+fn glyph_selectors(matches: &clap::ArgMatches) -> Vec<GlyphSelector> {
+    let mut selectors: Vec<(usize, GlyphSelector)> = ["glyph", "glyph_id"]
+        .into_iter()
+        .flat_map(|id| {
+            let indices = matches.indices_of(id).into_iter().flatten();
+            let values = matches.get_many::<GlyphSelector>(id).into_iter().flatten();
+            indices.zip(values.copied())
+        })
+        .collect();
+    selectors.sort_unstable_by_key(|&(index, _)| index);
+    selectors
+        .into_iter()
+        .map(|(_, selector)| selector)
+        .collect()
+}
+
+fn main() -> anyhow::Result<ExitCode> {
     logger::StderrLogger::init().expect("Failed to initialize logger");
 
-    let cli = Cli::parse();
+    let matches = Cli::command().get_matches();
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|err| err.exit());
 
     if cli.verbose {
         log::set_max_level(log::LevelFilter::Trace);
     }
 
+    let covers = if cli.covers.is_empty() && cli.covers_file.is_empty() {
+        None
+    } else {
+        let mut codepoints: Vec<u32> = Vec::new();
+        for Codepoints(c) in &cli.covers {
+            codepoints.extend(c);
+        }
+        for path in &cli.covers_file {
+            let text = std::fs::read_to_string(path).with_context(|| {
+                format!(
+                    "Failed reading characters file '{}'",
+                    path.to_string_lossy()
+                )
+            })?;
+            codepoints.extend(Codepoints::from_text(&text).0);
+        }
+        codepoints.sort_unstable();
+        codepoints.dedup();
+        Some(codepoints)
+    };
+
     let options = Options {
         print_features: if cli.list_features {
             PrintFeatures::Yes
@@ -453,21 +1662,193 @@ fn main() -> anyhow::Result<()> {
         } else {
             PrintWritingSystems::No
         },
+        print_layout_tree: if cli.layout_tree {
+            PrintLayoutTree::Yes
+        } else {
+            PrintLayoutTree::No
+        },
+        print_variations: if cli.list_variations {
+            PrintVariations::Yes
+        } else {
+            PrintVariations::No
+        },
+        print_names: if cli.list_names {
+            PrintNames::Yes
+        } else {
+            PrintNames::No
+        },
+        print_coverage: if cli.coverage {
+            PrintCoverage::Yes
+        } else {
+            PrintCoverage::No
+        },
+        print_color: if cli.color {
+            PrintColor::Yes
+        } else {
+            PrintColor::No
+        },
+        print_tables: if cli.list_tables {
+            PrintTables::Yes
+        } else {
+            PrintTables::No
+        },
+        print_os2: if cli.os2 { PrintOs2::Yes } else { PrintOs2::No },
+        print_technical: if cli.technical {
+            PrintTechnical::Yes
+        } else {
+            PrintTechnical::No
+        },
+        print_vertical_metrics: if cli.vertical_metrics {
+            PrintVerticalMetrics::Yes
+        } else {
+            PrintVerticalMetrics::No
+        },
+        covers,
+        glyphs: glyph_selectors(&matches),
+        kern_pairs: cli.kern.iter().flat_map(|pairs| pairs.0.clone()).collect(),
+        explain_features: cli.explain_feature.clone(),
+        size: cli.size,
+        name_language: cli.name_language.clone(),
+        instance: cli.instance.clone(),
+        variation_settings: cli.variation.clone(),
     };
 
     let mut stdout = std::io::stdout().lock();
 
-    match cli.format {
-        OutputFormat::HumanReadable => {
+    let outcome = match (cli.command.clone(), cli.format) {
+        (Some(Command::Render(args)), _) => {
+            let mut out = render::Renderer::new(args, options)?;
+            let outcome = main_(cli, &mut out)?;
+            out.finish()?;
+            outcome
+        }
+        (Some(Command::Shape(args)), OutputFormat::HumanReadable) => {
+            let mut out = shaping::HumanReadableRun::new(&mut stdout, args, options);
+            main_(cli, &mut out)?
+        }
+        (Some(Command::Shape(args)), OutputFormat::Json) => {
+            let mut serializer = serde_json::Serializer::new(&mut stdout);
+            let serialize_seq = serializer.serialize_seq(None).expect("infallible");
+            let mut out = shaping::JsonRun::new(serialize_seq, args, options);
+
+            // end the array even if inspecting the fonts failed, so the output is well-formed
+            let outcome = main_(cli, &mut out);
+
+            out.serializer.end()?;
+            writeln!(stdout)?;
+
+            outcome?
+        }
+        (Some(Command::Measure(args)), OutputFormat::HumanReadable) => {
+            let lines = args.lines()?;
+            let mut out =
+                measure::HumanReadableMeasurements::new(&mut stdout, &args, lines, options)?;
+            main_(cli, &mut out)?
+        }
+        (Some(Command::Measure(args)), OutputFormat::Json) => {
+            let lines = args.lines()?;
+            let mut serializer = serde_json::Serializer::new(&mut stdout);
+            let serialize_seq = serializer.serialize_seq(None).expect("infallible");
+            let mut out = measure::JsonMeasurements::new(serialize_seq, &args, lines, options)?;
+
+            // end the array even if inspecting the fonts failed
+            let outcome = main_(cli, &mut out);
+
+            out.serializer.end()?;
+            writeln!(stdout)?;
+
+            outcome?
+        }
+        (Some(Command::Audit(_)), OutputFormat::HumanReadable) => {
+            let mut out = audit::AuditTable::new(&mut stdout, options.name_language);
+            let outcome = main_(cli, &mut out)?;
+            out.finish()?;
+            outcome
+        }
+        (Some(Command::Audit(_)), OutputFormat::Json) => {
+            let mut serializer = serde_json::Serializer::new(&mut stdout);
+            let serialize_seq = serializer.serialize_seq(None).expect("infallible");
+            let mut out = audit::JsonAudit::new(serialize_seq, options.name_language);
+
+            // end the array even if inspecting the fonts failed
+            let outcome = main_(cli, &mut out);
+
+            out.serializer.end()?;
+            writeln!(stdout)?;
+
+            outcome?
+        }
+        (Some(Command::Lint(args)), format) => {
+            let mut out = lint::Linter::new(&args, options.name_language);
+            let outcome = main_(cli, &mut out)?;
+            let findings_outcome = match format {
+                OutputFormat::HumanReadable => out.write_human_readable(&mut stdout)?,
+                OutputFormat::Json => out.write_json(&mut stdout)?,
+                OutputFormat::Sarif => out.write_sarif(&mut stdout)?,
+            };
+            outcome.and(findings_outcome)
+        }
+        (_, OutputFormat::Sarif) => {
+            anyhow::bail!("SARIF output is only supported by the lint subcommand")
+        }
+        (Some(Command::Validate(args)), OutputFormat::HumanReadable) => {
+            let mut out = validate::Validator::new(&args, cli.font_index);
+            validate_(cli, &mut out)?;
+            out.write_human_readable(&mut stdout)?
+        }
+        (Some(Command::Validate(args)), OutputFormat::Json) => {
+            let mut out = validate::Validator::new(&args, cli.font_index);
+            validate_(cli, &mut out)?;
+            out.write_json(&mut stdout)?
+        }
+        (None, _) if cli.dump_table.is_some() => {
+            let tag = cli.dump_table.expect("checked above");
+            let path = cli.output.as_ref().expect("required by clap");
+            let (source, data) = single_font_data(&cli)?;
+            let font_data = FontDataRef::new(&data).ok_or_else(|| parse_error(&data))?;
+            check_font_index(font_data, cli.font_index, &source)?;
+            let font_index = match cli.font_index {
+                Some(font_index) => font_index,
+                None if font_data.len() == 1 => 0,
+                None => anyhow::bail!(
+                    "'{source}' is a font collection of {} fonts, but only the table of a single \
+                    font can be written. Use '--font-index' to select a font.",
+                    font_data.len()
+                ),
+            };
+            let font = font_data.get(font_index).expect("checked above");
+            dump::write_table(&source, font_index, font, tag, path)?;
+            Outcome::Pass
+        }
+        (None, _) if cli.kern_export => {
+            let mut serializer = serde_json::Serializer::new(&mut stdout);
+            let serialize_seq = serializer.serialize_seq(None).expect("infallible");
+            let mut out = kerning::JsonKerningExport::new(serialize_seq, options.name_language);
+
+            // end the array even if inspecting the fonts failed
+            let outcome = main_(cli, &mut out);
+
+            out.serializer.end()?;
+            writeln!(stdout)?;
+
+            outcome?
+        }
+        (None, _) if cli.hexdump_table.is_some() => {
+            let tag = cli.hexdump_table.expect("checked above");
+            let mut out = dump::HexDump::new(&mut stdout, tag, options);
+            main_(cli, &mut out)?
+        }
+        (None, OutputFormat::HumanReadable) => {
             let mut out = HumanReadable::new(&mut stdout, options);
-            main_(cli, &mut out)?;
+            main_(cli, &mut out)?
         }
-        OutputFormat::Json => {
+        (None, OutputFormat::Json) => {
             let mut serializer = serde_json::Serializer::new(&mut stdout);
             let serialize_seq = serializer.serialize_seq(None).expect("infallible");
             let mut out = Json::new(serialize_seq, options);
 
-            main_(cli, &mut out)?;
+            // end the array even if inspecting the fonts failed
+            let outcome = main_(cli, &mut out);
 
             let Json {
                 serializer: ser_seq,
@@ -475,8 +1856,312 @@ fn main() -> anyhow::Result<()> {
             } = out;
             ser_seq.end()?;
             writeln!(stdout)?;
+
+            outcome?
+        }
+    };
+
+    Ok(match outcome {
+        Outcome::Pass => ExitCode::SUCCESS,
+        Outcome::Fail => ExitCode::FAILURE,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Records the indices of the fonts and the errors pushed to it.
+    #[derive(Default)]
+    struct Recorder {
+        fonts: Vec<usize>,
+        errors: Vec<FontError>,
+    }
+
+    impl Out for Recorder {
+        fn push_font(
+            &mut self,
+            _source: &str,
+            font_index: usize,
+            _font: FontRef<'_>,
+        ) -> anyhow::Result<Outcome> {
+            self.fonts.push(font_index);
+            Ok(Outcome::Pass)
+        }
+
+        fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+            self.errors.push(error.clone());
+            Ok(())
         }
     }
 
-    Ok(())
+    fn errors(recorder: &Recorder) -> Vec<(&str, Option<usize>, Stage, &str)> {
+        recorder
+            .errors
+            .iter()
+            .map(|error| {
+                (
+                    error.source.as_str(),
+                    error.font_index,
+                    error.stage,
+                    error.message.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stage_keep_going() {
+        let mut inspector = Inspector::new(Recorder::default(), None, true);
+        assert_eq!(
+            inspector
+                .stage("a.ttf", None, Stage::Read, |_| Ok(1))
+                .unwrap(),
+            Some(1)
+        );
+        assert_eq!(inspector.outcome, Outcome::Pass);
+
+        let failed = [
+            inspector.stage("a.ttf", None, Stage::Read, |_| {
+                Err::<(), _>(anyhow!("not found"))
+            }),
+            inspector.stage("b.ttf", None, Stage::Parse, |_| {
+                Err::<(), _>(anyhow!("unknown format"))
+            }),
+            inspector.stage("c.ttf", Some(1), Stage::Inspect, |_| {
+                Err::<(), _>(anyhow!("bad table"))
+            }),
+            inspector.stage(
+                "d.ttf",
+                Some(0),
+                Stage::Inspect,
+                |_| -> anyhow::Result<()> { panic!("index out of bounds") },
+            ),
+        ];
+        for result in failed {
+            assert_eq!(result.unwrap(), None);
+        }
+        assert_eq!(inspector.outcome, Outcome::Fail);
+        assert_eq!(
+            errors(&inspector.out),
+            [
+                ("a.ttf", None, Stage::Read, "not found"),
+                ("b.ttf", None, Stage::Parse, "unknown format"),
+                ("c.ttf", Some(1), Stage::Inspect, "bad table"),
+                (
+                    "d.ttf",
+                    Some(0),
+                    Stage::Inspect,
+                    "panicked: index out of bounds"
+                ),
+            ]
+        );
+
+        // errors writing the output are never skipped
+        let result = inspector.stage("e.ttf", Some(0), Stage::Inspect, |_| {
+            Err::<(), _>(std::io::Error::from(std::io::ErrorKind::BrokenPipe).into())
+        });
+        assert!(result.is_err());
+        assert_eq!(inspector.out.errors.len(), 4);
+    }
+
+    #[test]
+    fn test_stage_stop() {
+        let mut inspector = Inspector::new(Recorder::default(), None, false);
+        let err = inspector
+            .stage("a.ttf", None, Stage::Read, |_| {
+                Err::<(), _>(anyhow!("not found"))
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "Failed to read 'a.ttf': not found");
+        let err = inspector
+            .stage("a.ttf", Some(0), Stage::Inspect, |_| {
+                Err::<(), _>(anyhow!("bad table"))
+            })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "bad table");
+        assert!(inspector.out.errors.is_empty());
+        assert_eq!(inspector.outcome, Outcome::Pass);
+    }
+
+    #[test]
+    fn test_push_file_errors() {
+        let mut inspector = Inspector::new(Recorder::default(), None, true);
+        let missing = std::env::temp_dir().join("font-info-test-missing.ttf");
+        inspector
+            .push_file(&missing, &mut Vec::new(), false, None)
+            .unwrap();
+        inspector
+            .push_data("garbage.ttf", b"not a font", false, None)
+            .unwrap();
+
+        assert_eq!(inspector.outcome, Outcome::Fail);
+        assert!(inspector.out.fonts.is_empty());
+        let errors = errors(&inspector.out);
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            (errors[0].0, errors[0].1, errors[0].2),
+            (missing.to_str().unwrap(), None, Stage::Read)
+        );
+        assert!(errors[0].3.starts_with("Failed opening font file"));
+        assert_eq!(
+            (errors[1].0, errors[1].1, errors[1].2),
+            ("garbage.ttf", None, Stage::Parse)
+        );
+    }
+
+    /// Build a font collection of fonts without tables.
+    fn font_collection(font_count: u32) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        data.extend(b"ttcf");
+        data.extend(0x0001_0000u32.to_be_bytes());
+        data.extend(font_count.to_be_bytes());
+        let fonts_offset = 12 + 4 * font_count;
+        for idx in 0..font_count {
+            data.extend((fonts_offset + 12 * idx).to_be_bytes());
+        }
+        for _ in 0..font_count {
+            data.extend(0x0001_0000u32.to_be_bytes());
+            data.extend([0; 8]);
+        }
+        data
+    }
+
+    fn system_font(path: &str, face_index: Option<u32>) -> font_enumeration::Font {
+        font_enumeration::Font {
+            family_name: "Sans".to_owned(),
+            font_name: format!("Sans {face_index:?}"),
+            path: PathBuf::from(path),
+            face_index,
+            style: font_enumeration::Style::Normal,
+            weight: font_enumeration::Weight::NORMAL,
+            stretch: font_enumeration::Stretch::NORMAL,
+        }
+    }
+
+    #[test]
+    fn test_group_by_file() {
+        let fonts = [
+            system_font("b.ttc", Some(2)),
+            system_font("a.ttf", Some(0)),
+            system_font("b.ttc", Some(0)),
+            // a named instance of the same face
+            system_font("b.ttc", Some(2)),
+        ];
+        let grouped: Vec<(PathBuf, Vec<Option<u32>>)> = group_by_file(&fonts)
+            .into_iter()
+            .map(|(path, fonts)| (path, fonts.iter().map(|font| font.face_index).collect()))
+            .collect();
+        assert_eq!(
+            grouped,
+            [
+                (PathBuf::from("b.ttc"), vec![Some(0), Some(2)]),
+                (PathBuf::from("a.ttf"), vec![Some(0)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matched_faces() {
+        let data = font_collection(3);
+        let matched = [system_font("a.ttc", Some(0)), system_font("a.ttc", Some(2))];
+
+        // only the matched faces are inspected
+        let mut inspector = Inspector::new(Recorder::default(), None, true);
+        inspector
+            .push_data("a.ttc", &data, false, Some(&matched))
+            .unwrap();
+        assert_eq!(inspector.out.fonts, [0, 2]);
+
+        // of which only the requested font index
+        let mut inspector = Inspector::new(Recorder::default(), Some(2), true);
+        inspector
+            .push_data("a.ttc", &data, false, Some(&matched))
+            .unwrap();
+        assert_eq!(inspector.out.fonts, [2]);
+
+        // all faces are inspected if the system does not report the face indices
+        let unknown = [system_font("a.ttc", Some(0)), system_font("a.ttc", None)];
+        let mut inspector = Inspector::new(Recorder::default(), None, true);
+        inspector
+            .push_data("a.ttc", &data, false, Some(&unknown))
+            .unwrap();
+        assert_eq!(inspector.out.fonts, [0, 1, 2]);
+
+        // a face the file does not contain is an error of that face
+        let missing = [system_font("a.ttc", Some(1)), system_font("a.ttc", Some(5))];
+        let mut inspector = Inspector::new(Recorder::default(), None, true);
+        inspector
+            .push_data("a.ttc", &data, false, Some(&missing))
+            .unwrap();
+        assert_eq!(inspector.out.fonts, [1]);
+        assert_eq!(
+            errors(&inspector.out),
+            [(
+                "a.ttc",
+                Some(5),
+                Stage::Parse,
+                "the system reported a font with index 5, but the file contains 3 fonts"
+            )]
+        );
+        assert_eq!(inspector.outcome, Outcome::Fail);
+    }
+
+    #[test]
+    fn test_progress() {
+        let mut progress = Progress {
+            total: 2,
+            write: Some(Vec::new()),
+        };
+        progress.update(0);
+        progress.update(1);
+        progress.update(2);
+        progress.finish();
+        assert_eq!(
+            String::from_utf8(progress.write.unwrap()).unwrap(),
+            "\rInspected 0 of 2 font files\rInspected 1 of 2 font files\rInspected 2 of 2 font files\n"
+        );
+
+        let mut progress: Progress<Vec<u8>> = Progress {
+            total: 2,
+            write: None,
+        };
+        progress.update(1);
+        progress.finish();
+        assert!(progress.write.is_none());
+    }
+
+    #[test]
+    fn test_all_installed_input() {
+        let cli = Cli::parse_from(["font-info", "--all-installed"]);
+        assert!(matches!(cli.input().unwrap(), Input::Installed));
+
+        assert!(Cli::try_parse_from(["font-info", "--all-installed", "fonts/"]).is_err());
+        let cli = Cli::parse_from(["font-info", "--all-installed", "lint", "fonts/"]);
+        assert_eq!(
+            cli.input().err().unwrap().to_string(),
+            "Font files cannot be given together with '--all-installed'"
+        );
+    }
+
+    #[test]
+    fn test_glyph_selectors() {
+        let matches = Cli::command().get_matches_from([
+            "font-info",
+            "--glyph",
+            "A",
+            "--glyph-id",
+            "5",
+            "--glyph",
+            "U+00E9",
+        ]);
+        assert_eq!(
+            glyph_selectors(&matches),
+            [
+                GlyphSelector::Char('A'),
+                GlyphSelector::Id(5),
+                GlyphSelector::Char('\u{e9}')
+            ]
+        );
+    }
 }
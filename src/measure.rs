@@ -0,0 +1,444 @@
+use std::{io::Write, path::PathBuf};
+
+use anyhow::Context;
+use swash::{scale::ScaleContext, shape::ShapeContext, FontRef, NormalizedCoord};
+
+use crate::{
+    glyphs::BoundingBox,
+    shaping::{self, ShapeOptions, ShapingArgs},
+    variations, FontError, Metrics, Options, Out, Outcome,
+};
+
+/// Arguments of the measure subcommand.
+#[derive(clap::Args, Clone, Debug)]
+#[group(id = "lines", required = true, multiple = true, args = ["text", "file"])]
+pub(crate) struct MeasureArgs {
+    #[command(flatten)]
+    pub input: crate::files::PathArgs,
+
+    /// The text to measure. Each line of the text is measured separately. Can be given multiple
+    /// times.
+    #[arg(long)]
+    text: Vec<String>,
+
+    /// Measure every line of the given file.
+    #[arg(long)]
+    file: Option<PathBuf>,
+
+    /// Measure at the given font size in pixels per em, rather than in font units.
+    #[arg(long)]
+    size: Option<f32>,
+
+    #[command(flatten)]
+    shaping: ShapingArgs,
+}
+
+impl MeasureArgs {
+    /// Get the lines to measure, reading the file if one is given.
+    pub fn lines(&self) -> anyhow::Result<Vec<String>> {
+        let mut lines: Vec<String> = self
+            .text
+            .iter()
+            .flat_map(|text| text.lines())
+            .map(ToOwned::to_owned)
+            .collect();
+
+        if let Some(path) = &self.file {
+            let text = std::fs::read_to_string(path).with_context(|| {
+                format!("Failed reading text file '{}'", path.to_string_lossy())
+            })?;
+            lines.extend(text.lines().map(ToOwned::to_owned));
+        }
+
+        Ok(lines)
+    }
+}
+
+/// The measurements of a single line of text.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LineMeasurement {
+    /// The number of the line in the input, starting at 1.
+    pub line: usize,
+    pub text: String,
+    /// The total advance of the shaped line.
+    pub width: f32,
+    /// The bounding box of the outlines of the line's glyphs, relative to the start of the line
+    /// on the baseline, with y pointing upward. Not present if no glyph has an outline.
+    pub ink_bounds: Option<BoundingBox>,
+    /// The height of the line box, which is the sum of the ascent, descent and leading. This
+    /// differs from the line height of the font's metrics, which does not include the leading.
+    pub line_box_height: f32,
+    pub ascent: f32,
+    pub descent: f32,
+    pub leading: f32,
+}
+
+impl LineMeasurement {
+    /// Create the measurement of a line from its width and ink bounds, which are already
+    /// scaled, and the font's vertical metrics in font units, which are multiplied by the scale.
+    fn new(
+        line: usize,
+        text: String,
+        width: f32,
+        ink_bounds: Option<BoundingBox>,
+        metrics: &Metrics,
+        scale: f32,
+    ) -> Self {
+        LineMeasurement {
+            line,
+            text,
+            width,
+            ink_bounds,
+            line_box_height: (metrics.ascent + metrics.descent + metrics.leading) * scale,
+            ascent: metrics.ascent * scale,
+            descent: metrics.descent * scale,
+            leading: metrics.leading * scale,
+        }
+    }
+}
+
+/// The factor to scale font units by to get pixels at the given size in pixels per em. 1 if no
+/// size is given, or if the font's units per em are invalid.
+fn scale_factor(size: Option<f32>, units_per_em: u16) -> f32 {
+    match size {
+        Some(size) if units_per_em != 0 => size / f32::from(units_per_em),
+        _ => 1.,
+    }
+}
+
+/// Extend the ink bounds of a line with the bounds of a glyph's outline drawn at the given
+/// position.
+fn add_ink(ink: Option<BoundingBox>, bounds: BoundingBox, x: f32, y: f32) -> BoundingBox {
+    let glyph_ink = BoundingBox {
+        x_min: bounds.x_min + x,
+        y_min: bounds.y_min + y,
+        x_max: bounds.x_max + x,
+        y_max: bounds.y_max + y,
+    };
+    match ink {
+        Some(ink) => BoundingBox {
+            x_min: ink.x_min.min(glyph_ink.x_min),
+            y_min: ink.y_min.min(glyph_ink.y_min),
+            x_max: ink.x_max.max(glyph_ink.x_max),
+            y_max: ink.y_max.max(glyph_ink.y_max),
+        },
+        None => glyph_ink,
+    }
+}
+
+/// Measures lines of text shaped with fonts.
+struct Measurer {
+    lines: Vec<String>,
+    size: Option<f32>,
+    shape_options: ShapeOptions,
+    shape_context: ShapeContext,
+    scale_context: ScaleContext,
+}
+
+impl Measurer {
+    fn new(args: &MeasureArgs, lines: Vec<String>) -> anyhow::Result<Self> {
+        if args.size.is_some_and(|size| size.is_nan() || size <= 0.) {
+            anyhow::bail!("The font size must be positive");
+        }
+
+        Ok(Measurer {
+            lines,
+            size: args.size,
+            shape_options: args.shaping.options(),
+            shape_context: ShapeContext::new(),
+            scale_context: ScaleContext::new(),
+        })
+    }
+
+    fn measure(&mut self, font: FontRef<'_>, coords: &[NormalizedCoord]) -> Vec<LineMeasurement> {
+        let metrics = Metrics::from_font(font, coords);
+        let scale = scale_factor(self.size, metrics.units_per_em);
+
+        // outlines are scaled to the requested size, or kept in font units
+        let mut scaler = self
+            .scale_context
+            .builder(font)
+            .size(self.size.unwrap_or(0.))
+            .normalized_coords(coords)
+            .hint(false)
+            .build();
+
+        self.lines
+            .iter()
+            .enumerate()
+            .map(|(idx, text)| {
+                let shaped = shaping::shape(
+                    &mut self.shape_context,
+                    font,
+                    text,
+                    self.size.unwrap_or(0.),
+                    coords,
+                    &self.shape_options,
+                );
+
+                let mut ink: Option<BoundingBox> = None;
+                let mut pen = 0.;
+                for glyph in &shaped.glyphs {
+                    if let Some(bounds) = scaler
+                        .scale_outline(glyph.glyph_id)
+                        .map(|outline| outline.bounds())
+                        .filter(|bounds| !bounds.is_empty())
+                    {
+                        let bounds = BoundingBox {
+                            x_min: bounds.min.x,
+                            y_min: bounds.min.y,
+                            x_max: bounds.max.x,
+                            y_max: bounds.max.y,
+                        };
+                        ink = Some(add_ink(ink, bounds, pen + glyph.x_offset, glyph.y_offset));
+                    }
+                    pen += glyph.x_advance;
+                }
+
+                LineMeasurement::new(idx + 1, text.clone(), shaped.advance, ink, &metrics, scale)
+            })
+            .collect()
+    }
+}
+
+/// Prints the measurements of lines of text shaped with each font in human-readable form.
+pub(crate) struct HumanReadableMeasurements<W> {
+    write: W,
+    options: Options,
+    measurer: Measurer,
+    /// the number of fonts already written
+    fonts_written: usize,
+}
+
+impl<W> HumanReadableMeasurements<W> {
+    pub fn new(
+        write: W,
+        args: &MeasureArgs,
+        lines: Vec<String>,
+        options: Options,
+    ) -> anyhow::Result<Self> {
+        Ok(HumanReadableMeasurements {
+            write,
+            options,
+            measurer: Measurer::new(args, lines)?,
+            fonts_written: 0,
+        })
+    }
+}
+
+impl<W: Write> Out for HumanReadableMeasurements<W> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        let location = self.options.location(font)?;
+        self.fonts_written += 1;
+        crate::write_font_header(
+            &mut self.write,
+            self.fonts_written,
+            source,
+            font_index,
+            font,
+            self.options.name_language.as_deref(),
+        )?;
+
+        crate::variations::write_coordinates(&mut self.write, &location)?;
+
+        write!(self.write, "               Lines: ")?;
+        for (idx, measurement) in self
+            .measurer
+            .measure(font, &location.normalized)
+            .iter()
+            .enumerate()
+        {
+            if idx > 0 {
+                write!(self.write, "\n                      ")?;
+            }
+            write!(
+                self.write,
+                "{}: {:?}: width {}",
+                measurement.line, measurement.text, measurement.width
+            )?;
+            if let Some(ink) = &measurement.ink_bounds {
+                write!(
+                    self.write,
+                    ", ink ({}, {}) to ({}, {})",
+                    ink.x_min, ink.y_min, ink.x_max, ink.y_max
+                )?;
+            }
+            write!(
+                self.write,
+                ", line box height {} (ascent {}, descent {}, leading {})",
+                measurement.line_box_height,
+                measurement.ascent,
+                measurement.descent,
+                measurement.leading
+            )?;
+        }
+        writeln!(self.write)?;
+
+        Ok(Outcome::Pass)
+    }
+}
+
+/// Outputs the measurements of lines of text shaped with each font as JSON.
+pub(crate) struct JsonMeasurements<S> {
+    pub serializer: S,
+    options: Options,
+    measurer: Measurer,
+}
+
+impl<S> JsonMeasurements<S> {
+    pub fn new(
+        serializer: S,
+        args: &MeasureArgs,
+        lines: Vec<String>,
+        options: Options,
+    ) -> anyhow::Result<Self> {
+        Ok(JsonMeasurements {
+            serializer,
+            options,
+            measurer: Measurer::new(args, lines)?,
+        })
+    }
+}
+
+impl<S: serde::ser::SerializeSeq<Error = serde_json::Error>> Out for JsonMeasurements<S> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Out<'m> {
+            source: &'m str,
+            font_index: usize,
+            family_name: Option<&'m str>,
+            subfamily_name: Option<&'m str>,
+            coordinates: &'m [variations::Coordinate],
+            lines: &'m [LineMeasurement],
+        }
+
+        let location = self.options.location(font)?;
+        let language = self.options.name_language.as_deref();
+
+        serde::ser::SerializeSeq::serialize_element(
+            &mut self.serializer,
+            &Out {
+                source,
+                font_index,
+                family_name: crate::names::family_name(font, language).as_deref(),
+                subfamily_name: crate::names::subfamily_name(font, language).as_deref(),
+                coordinates: &location.coordinates,
+                lines: &self.measurer.measure(font, &location.normalized),
+            },
+        )?;
+
+        Ok(Outcome::Pass)
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        serde::ser::SerializeSeq::serialize_element(&mut self.serializer, error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        measure: MeasureArgs,
+    }
+
+    fn bounds(x_min: f32, y_min: f32, x_max: f32, y_max: f32) -> BoundingBox {
+        BoundingBox {
+            x_min,
+            y_min,
+            x_max,
+            y_max,
+        }
+    }
+
+    fn assert_bounds_eq(actual: BoundingBox, expected: BoundingBox) {
+        assert_eq!(
+            (actual.x_min, actual.y_min, actual.x_max, actual.y_max),
+            (
+                expected.x_min,
+                expected.y_min,
+                expected.x_max,
+                expected.y_max
+            )
+        );
+    }
+
+    #[test]
+    fn test_add_ink() {
+        let ink = add_ink(None, bounds(10., -20., 500., 700.), 0., 0.);
+        assert_bounds_eq(ink, bounds(10., -20., 500., 700.));
+
+        // the next glyph is drawn at its pen position and offset
+        let ink = add_ink(Some(ink), bounds(20., 0., 480., 520.), 600., -50.);
+        assert_bounds_eq(ink, bounds(10., -50., 1080., 700.));
+
+        // a mark drawn above and left of the previous glyphs extends the bounds
+        let ink = add_ink(Some(ink), bounds(0., 0., 100., 100.), -30., 800.);
+        assert_bounds_eq(ink, bounds(-30., -50., 1080., 900.));
+    }
+
+    #[test]
+    fn test_scaling() {
+        assert_eq!(scale_factor(None, 2048), 1.);
+        assert_eq!(scale_factor(Some(16.), 2048), 0.0078125);
+        assert_eq!(scale_factor(Some(16.), 0), 1.);
+
+        let metrics = Metrics {
+            glyph_count: 10,
+            units_per_em: 1000,
+            average_advance: 500.,
+            ascent: 800.,
+            descent: 200.,
+            leading: 100.,
+            line_height: 1000.,
+            cap_height: 700.,
+            x_height: 500.,
+            stroke_size: 50.,
+            underline_offset: -100.,
+            strikeout_offset: 300.,
+        };
+        let measurement = LineMeasurement::new(
+            1,
+            "text".to_owned(),
+            20.,
+            None,
+            &metrics,
+            scale_factor(Some(20.), metrics.units_per_em),
+        );
+        assert_eq!(measurement.ascent, 16.);
+        assert_eq!(measurement.descent, 4.);
+        assert_eq!(measurement.leading, 2.);
+        assert_eq!(measurement.line_box_height, 22.);
+    }
+
+    #[test]
+    fn test_size() {
+        let measurer = |size: &str| {
+            let size = format!("--size={size}");
+            let cli = TestCli::parse_from(["test", "--text", "text", &size]);
+            Measurer::new(&cli.measure, cli.measure.lines().unwrap())
+        };
+        assert_eq!(measurer("16").unwrap().size, Some(16.));
+        assert!(measurer("0").is_err());
+        assert!(measurer("-12").is_err());
+        assert!(measurer("NaN").is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use std::collections::HashMap;
+
+use swash::{FontRef, StringId};
+
+const NAME: swash::Tag = swash::tag_from_bytes(b"name");
+
+/// A single record of the `name` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Name {
+    pub name_id: u16,
+    pub platform_id: u16,
+    pub encoding_id: u16,
+    pub language_id: u16,
+    /// The language as a BCP 47-like language tag. Empty for language-independent strings, "zz"
+    /// if the language is unknown.
+    pub language: String,
+    /// The decoded string, or `None` if its encoding is not supported.
+    pub value: Option<String>,
+}
+
+impl Name {
+    /// A human-readable description of the name ID, as registered in the OpenType specification.
+    pub fn description(&self) -> &'static str {
+        name_id_description(self.name_id)
+    }
+
+    /// A human-readable description of the platform ID.
+    pub fn platform(&self) -> &'static str {
+        match self.platform_id {
+            0 => "Unicode",
+            1 => "Macintosh",
+            2 => "ISO",
+            3 => "Windows",
+            4 => "Custom",
+            _ => "Unknown",
+        }
+    }
+}
+
+/// Get the human-readable description of a name ID.
+fn name_id_description(name_id: u16) -> &'static str {
+    match name_id {
+        0 => "Copyright notice",
+        1 => "Family name",
+        2 => "Subfamily name",
+        3 => "Unique identifier",
+        4 => "Full name",
+        5 => "Version",
+        6 => "PostScript name",
+        7 => "Trademark",
+        8 => "Manufacturer",
+        9 => "Designer",
+        10 => "Description",
+        11 => "Vendor URL",
+        12 => "Designer URL",
+        13 => "License description",
+        14 => "License info URL",
+        16 => "Typographic family name",
+        17 => "Typographic subfamily name",
+        18 => "Compatible full name",
+        19 => "Sample text",
+        20 => "PostScript CID findfont name",
+        21 => "WWS family name",
+        22 => "WWS subfamily name",
+        23 => "Light background palette",
+        24 => "Dark background palette",
+        25 => "Variations PostScript name prefix",
+        256.. => "Font-specific name",
+        _ => "Reserved",
+    }
+}
+
+/// Read all records in the `name` table. If a language is given, only a single record is
+/// returned for each name ID, preferring records in that language.
+pub(crate) fn names(font: FontRef<'_>, language: Option<&str>) -> Vec<Name> {
+    let table = font.table(NAME).unwrap_or(&[]);
+
+    // swash does not expose the platform, encoding and language IDs of name records, so they are
+    // read from the raw records. swash's localized strings iterate over the records in order.
+    let read_u16 = |offset: usize| -> u16 {
+        table
+            .get(offset..offset + 2)
+            .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+            .unwrap_or(0)
+    };
+
+    let mut names: Vec<Name> = font
+        .localized_strings()
+        .enumerate()
+        .map(|(idx, string)| {
+            let record = 6 + idx * 12;
+            Name {
+                name_id: string.id().to_raw(),
+                platform_id: read_u16(record),
+                encoding_id: read_u16(record + 2),
+                language_id: read_u16(record + 4),
+                language: string.language().to_owned(),
+                value: string.is_decodable().then(|| string.to_string()),
+            }
+        })
+        .collect();
+
+    if let Some(language) = language {
+        let language = language.to_ascii_lowercase();
+        let preference = |name: &Name| {
+            if name.value.is_none() {
+                4
+            } else if name.language.starts_with(&language) {
+                0
+            } else if name.language.starts_with(primary_subtag(&language)) {
+                1
+            } else if name.language.is_empty() || name.language.starts_with("en") {
+                2
+            } else {
+                3
+            }
+        };
+
+        // the index of the first of the most preferred records of each name ID
+        let mut preferred: HashMap<u16, usize> = HashMap::new();
+        for (idx, name) in names.iter().enumerate() {
+            preferred
+                .entry(name.name_id)
+                .and_modify(|best| {
+                    if preference(name) < preference(&names[*best]) {
+                        *best = idx;
+                    }
+                })
+                .or_insert(idx);
+        }
+
+        let mut keep = vec![false; names.len()];
+        for idx in preferred.into_values() {
+            keep[idx] = true;
+        }
+        let mut keep = keep.into_iter();
+        names.retain(|_| keep.next().expect("one flag per record"));
+    }
+
+    names
+}
+
+/// Write the name records in the human-readable format. Multi-line strings continue on indented
+/// lines.
+pub(crate) fn write_names(mut write: impl std::io::Write, names: &[Name]) -> std::io::Result<()> {
+    write!(write, "               Names: ")?;
+    for (idx, name) in names.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        write!(
+            write,
+            "{} {} ({}, encoding {}, language {}): ",
+            name.name_id,
+            name.description(),
+            name.platform(),
+            name.encoding_id,
+            if name.language.is_empty() {
+                "none"
+            } else {
+                &name.language
+            },
+        )?;
+        match &name.value {
+            Some(value) => {
+                for (idx, line) in value.lines().enumerate() {
+                    if idx > 0 {
+                        write!(write, "\n                        ")?;
+                    }
+                    write!(write, "{line}")?;
+                }
+            }
+            None => write!(write, "<unsupported encoding>")?,
+        }
+    }
+    writeln!(write)?;
+
+    Ok(())
+}
+
+/// Find the localized string with the given ID, preferring the given language. If no string in
+/// that language exists, this falls back to a string in the same primary language (e.g. "nl" for
+/// "nl-BE"), and then to a language-independent or English string.
+pub(crate) fn localized_string<'a>(
+    font: FontRef<'a>,
+    id: StringId,
+    language: Option<&str>,
+) -> Option<swash::LocalizedString<'a>> {
+    let strings = font.localized_strings();
+    let language = language.map(str::to_ascii_lowercase);
+    language
+        .as_deref()
+        .and_then(|language| {
+            strings
+                .find_by_id(id, Some(language))
+                .or_else(|| strings.find_by_id(id, Some(primary_subtag(language))))
+        })
+        .or_else(|| strings.find_by_id(id, Some("en")))
+        .or_else(|| strings.find_by_id(id, None))
+}
+
+/// Get the primary language subtag of a language tag, e.g. "nl" for "nl-be".
+fn primary_subtag(language: &str) -> &str {
+    language
+        .split_once('-')
+        .map_or(language, |(primary, _)| primary)
+}
+
+/// Get the family name of the font, preferring the typographic family name.
+pub(crate) fn family_name(font: FontRef<'_>, language: Option<&str>) -> Option<String> {
+    localized_string(font, StringId::TypographicFamily, language)
+        .or_else(|| localized_string(font, StringId::Family, language))
+        .map(|name| name.to_string())
+}
+
+/// Get the subfamily name of the font, preferring the typographic subfamily name.
+pub(crate) fn subfamily_name(font: FontRef<'_>, language: Option<&str>) -> Option<String> {
+    localized_string(font, StringId::TypographicSubFamily, language)
+        .or_else(|| localized_string(font, StringId::SubFamily, language))
+        .map(|name| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a font that only has a `name` table with the given Windows Unicode records of
+    /// language ID, name ID and string, which must be sorted.
+    fn font_with_names(records: &[(u16, u16, &str)]) -> Vec<u8> {
+        let mut strings: Vec<u8> = Vec::new();
+        let mut table: Vec<u8> = Vec::new();
+        table.extend(0u16.to_be_bytes());
+        table.extend((records.len() as u16).to_be_bytes());
+        table.extend((6 + 12 * records.len() as u16).to_be_bytes());
+        for &(language_id, name_id, string) in records {
+            let encoded: Vec<u8> = string.encode_utf16().flat_map(u16::to_be_bytes).collect();
+            for value in [
+                3,
+                1,
+                language_id,
+                name_id,
+                encoded.len() as u16,
+                strings.len() as u16,
+            ] {
+                table.extend(value.to_be_bytes());
+            }
+            strings.extend(encoded);
+        }
+        table.extend(strings);
+
+        let mut font: Vec<u8> = Vec::new();
+        font.extend(0x0001_0000u32.to_be_bytes());
+        for value in [1u16, 16, 0, 0] {
+            font.extend(value.to_be_bytes());
+        }
+        font.extend(b"name");
+        font.extend(0u32.to_be_bytes());
+        font.extend(28u32.to_be_bytes());
+        font.extend((table.len() as u32).to_be_bytes());
+        font.extend(table);
+        font
+    }
+
+    #[test]
+    fn test_names() {
+        let data = font_with_names(&[
+            (0x0407, 1, "Schrift"),
+            (0x0409, 1, "Font"),
+            (0x0409, 2, "Regular"),
+            (0x040C, 2, "Normal"),
+        ]);
+        let font = FontRef::from_index(&data, 0).unwrap();
+        let values = |names: Vec<Name>| -> Vec<(u16, String)> {
+            names
+                .into_iter()
+                .map(|name| (name.name_id, name.value.unwrap()))
+                .collect()
+        };
+
+        let all = names(font, None);
+        assert_eq!(all.len(), 4);
+        assert_eq!(
+            (all[0].platform_id, all[0].encoding_id, all[0].language_id),
+            (3, 1, 0x0407)
+        );
+        assert_eq!(all[0].language, "de");
+
+        // a single record per name ID, in the requested language or falling back to English
+        assert_eq!(
+            values(names(font, Some("de"))),
+            [(1, "Schrift".to_owned()), (2, "Regular".to_owned())]
+        );
+        assert_eq!(
+            values(names(font, Some("fr"))),
+            [(1, "Font".to_owned()), (2, "Normal".to_owned())]
+        );
+        assert_eq!(
+            values(names(font, Some("nl"))),
+            [(1, "Font".to_owned()), (2, "Regular".to_owned())]
+        );
+
+        // a region falls back to the primary language, and tags are compared case-insensitively
+        assert_eq!(
+            values(names(font, Some("de-CH"))),
+            [(1, "Schrift".to_owned()), (2, "Regular".to_owned())]
+        );
+        assert_eq!(
+            values(names(font, Some("FR"))),
+            [(1, "Font".to_owned()), (2, "Normal".to_owned())]
+        );
+
+        assert_eq!(family_name(font, Some("de")).as_deref(), Some("Schrift"));
+        assert_eq!(family_name(font, Some("de-AT")).as_deref(), Some("Schrift"));
+        assert_eq!(family_name(font, Some("nl")).as_deref(), Some("Font"));
+        assert_eq!(subfamily_name(font, Some("fr")).as_deref(), Some("Normal"));
+        assert_eq!(
+            subfamily_name(font, Some("fr-CA")).as_deref(),
+            Some("Normal")
+        );
+        assert_eq!(subfamily_name(font, None).as_deref(), Some("Regular"));
+
+        // records with a region match other regions of the same language
+        let data = font_with_names(&[(0x0404, 1, "Traditional"), (0x0409, 1, "Font")]);
+        let font = FontRef::from_index(&data, 0).unwrap();
+        assert_eq!(
+            values(names(font, Some("zh-HK"))),
+            [(1, "Traditional".to_owned())]
+        );
+        assert_eq!(
+            family_name(font, Some("zh-TW")).as_deref(),
+            Some("Traditional")
+        );
+        assert_eq!(
+            family_name(font, Some("zh-HK")).as_deref(),
+            Some("Traditional")
+        );
+    }
+}
@@ -0,0 +1,585 @@
+use read_fonts::tables::os2::Os2 as Os2Table;
+use swash::FontRef;
+
+use crate::{settings::tag_to_string, tables::read_table};
+
+/// The names of the `fsSelection` bits.
+const FS_SELECTION: [&str; 10] = [
+    "ITALIC",
+    "UNDERSCORE",
+    "NEGATIVE",
+    "OUTLINED",
+    "STRIKEOUT",
+    "BOLD",
+    "REGULAR",
+    "USE_TYPO_METRICS",
+    "WWS",
+    "OBLIQUE",
+];
+
+/// The names of the `ulUnicodeRange` bits. Bits 123 to 127 are reserved.
+const UNICODE_RANGES: [&str; 123] = [
+    "Basic Latin",
+    "Latin-1 Supplement",
+    "Latin Extended-A",
+    "Latin Extended-B",
+    "IPA Extensions",
+    "Spacing Modifier Letters",
+    "Combining Diacritical Marks",
+    "Greek and Coptic",
+    "Coptic",
+    "Cyrillic",
+    "Armenian",
+    "Hebrew",
+    "Vai",
+    "Arabic",
+    "NKo",
+    "Devanagari",
+    "Bengali",
+    "Gurmukhi",
+    "Gujarati",
+    "Oriya",
+    "Tamil",
+    "Telugu",
+    "Kannada",
+    "Malayalam",
+    "Thai",
+    "Lao",
+    "Georgian",
+    "Balinese",
+    "Hangul Jamo",
+    "Latin Extended Additional",
+    "Greek Extended",
+    "General Punctuation",
+    "Superscripts And Subscripts",
+    "Currency Symbols",
+    "Combining Diacritical Marks For Symbols",
+    "Letterlike Symbols",
+    "Number Forms",
+    "Arrows",
+    "Mathematical Operators",
+    "Miscellaneous Technical",
+    "Control Pictures",
+    "Optical Character Recognition",
+    "Enclosed Alphanumerics",
+    "Box Drawing",
+    "Block Elements",
+    "Geometric Shapes",
+    "Miscellaneous Symbols",
+    "Dingbats",
+    "CJK Symbols And Punctuation",
+    "Hiragana",
+    "Katakana",
+    "Bopomofo",
+    "Hangul Compatibility Jamo",
+    "Phags-pa",
+    "Enclosed CJK Letters And Months",
+    "CJK Compatibility",
+    "Hangul Syllables",
+    "Non-Plane 0",
+    "Phoenician",
+    "CJK Unified Ideographs",
+    "Private Use Area (plane 0)",
+    "CJK Strokes",
+    "Alphabetic Presentation Forms",
+    "Arabic Presentation Forms-A",
+    "Combining Half Marks",
+    "Vertical Forms",
+    "Small Form Variants",
+    "Arabic Presentation Forms-B",
+    "Halfwidth And Fullwidth Forms",
+    "Specials",
+    "Tibetan",
+    "Syriac",
+    "Thaana",
+    "Sinhala",
+    "Myanmar",
+    "Ethiopic",
+    "Cherokee",
+    "Unified Canadian Aboriginal Syllabics",
+    "Ogham",
+    "Runic",
+    "Khmer",
+    "Mongolian",
+    "Braille Patterns",
+    "Yi Syllables",
+    "Tagalog",
+    "Old Italic",
+    "Gothic",
+    "Deseret",
+    "Byzantine Musical Symbols",
+    "Mathematical Alphanumeric Symbols",
+    "Private Use (plane 15 and 16)",
+    "Variation Selectors",
+    "Tags",
+    "Limbu",
+    "Tai Le",
+    "New Tai Lue",
+    "Buginese",
+    "Glagolitic",
+    "Tifinagh",
+    "Yijing Hexagram Symbols",
+    "Syloti Nagri",
+    "Linear B Syllabary",
+    "Ancient Greek Numbers",
+    "Ugaritic",
+    "Old Persian",
+    "Shavian",
+    "Osmanya",
+    "Cypriot Syllabary",
+    "Kharoshthi",
+    "Tai Xuan Jing Symbols",
+    "Cuneiform",
+    "Counting Rod Numerals",
+    "Sundanese",
+    "Lepcha",
+    "Ol Chiki",
+    "Saurashtra",
+    "Kayah Li",
+    "Rejang",
+    "Cham",
+    "Ancient Symbols",
+    "Phaistos Disc",
+    "Carian",
+    "Domino Tiles",
+];
+
+/// The names of the `ulCodePageRange` bits that are not reserved.
+const CODE_PAGES: [(u32, &str); 34] = [
+    (0, "1252 Latin 1"),
+    (1, "1250 Latin 2: Eastern Europe"),
+    (2, "1251 Cyrillic"),
+    (3, "1253 Greek"),
+    (4, "1254 Turkish"),
+    (5, "1255 Hebrew"),
+    (6, "1256 Arabic"),
+    (7, "1257 Windows Baltic"),
+    (8, "1258 Vietnamese"),
+    (16, "874 Thai"),
+    (17, "932 JIS/Japan"),
+    (18, "936 Chinese: Simplified"),
+    (19, "949 Korean Wansung"),
+    (20, "950 Chinese: Traditional"),
+    (21, "1361 Korean Johab"),
+    (29, "Macintosh Character Set (US Roman)"),
+    (30, "OEM Character Set"),
+    (31, "Symbol Character Set"),
+    (48, "869 IBM Greek"),
+    (49, "866 MS-DOS Russian"),
+    (50, "865 MS-DOS Nordic"),
+    (51, "864 Arabic"),
+    (52, "863 MS-DOS Canadian French"),
+    (53, "862 Hebrew"),
+    (54, "861 MS-DOS Icelandic"),
+    (55, "860 MS-DOS Portuguese"),
+    (56, "857 IBM Turkish"),
+    (57, "855 IBM Cyrillic"),
+    (58, "852 Latin 2"),
+    (59, "775 MS-DOS Baltic"),
+    (60, "737 Greek"),
+    (61, "708 Arabic; ASMO 708"),
+    (62, "850 WE/Latin 1"),
+    (63, "437 US"),
+];
+
+/// The names of the PANOSE family kinds.
+const PANOSE_FAMILY_KINDS: [&str; 6] = [
+    "Any",
+    "No Fit",
+    "Latin Text",
+    "Latin Hand Written",
+    "Latin Decorative",
+    "Latin Symbol",
+];
+
+/// The names of the ten PANOSE classifications, which depend on the family kind. Family kinds
+/// without classifications of their own use those of Latin Text.
+fn panose_classifications(family_kind: u8) -> [&'static str; 10] {
+    match family_kind {
+        3 => [
+            "Family Kind",
+            "Tool Kind",
+            "Weight",
+            "Spacing",
+            "Aspect Ratio",
+            "Contrast",
+            "Topology",
+            "Form",
+            "Finials",
+            "X-Ascent",
+        ],
+        4 => [
+            "Family Kind",
+            "Class",
+            "Weight",
+            "Aspect",
+            "Contrast",
+            "Serif Variant",
+            "Treatment",
+            "Lining",
+            "Topology",
+            "Range of Characters",
+        ],
+        5 => [
+            "Family Kind",
+            "Kind",
+            "Weight",
+            "Spacing",
+            "Aspect Ratio and Contrast",
+            "Aspect Ratio of Character 94",
+            "Aspect Ratio of Character 119",
+            "Aspect Ratio of Character 157",
+            "Aspect Ratio of Character 163",
+            "Aspect Ratio of Character 211",
+        ],
+        _ => [
+            "Family Kind",
+            "Serif Style",
+            "Weight",
+            "Proportion",
+            "Contrast",
+            "Stroke Variation",
+            "Arm Style",
+            "Letterform",
+            "Midline",
+            "X-height",
+        ],
+    }
+}
+
+/// The names of the values of the Latin Text PANOSE classifications, following the family kind.
+const PANOSE_LATIN_TEXT: [&[&str]; 9] = [
+    &[
+        "Any",
+        "No Fit",
+        "Cove",
+        "Obtuse Cove",
+        "Square Cove",
+        "Obtuse Square Cove",
+        "Square",
+        "Thin",
+        "Oval",
+        "Exaggerated",
+        "Triangle",
+        "Normal Sans",
+        "Obtuse Sans",
+        "Perpendicular Sans",
+        "Flared",
+        "Rounded",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "Very Light",
+        "Light",
+        "Thin",
+        "Book",
+        "Medium",
+        "Demi",
+        "Bold",
+        "Heavy",
+        "Black",
+        "Extra Black",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "Old Style",
+        "Modern",
+        "Even Width",
+        "Extended",
+        "Condensed",
+        "Very Extended",
+        "Very Condensed",
+        "Monospaced",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "None",
+        "Very Low",
+        "Low",
+        "Medium Low",
+        "Medium",
+        "Medium High",
+        "High",
+        "Very High",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "No Variation",
+        "Gradual/Diagonal",
+        "Gradual/Transitional",
+        "Gradual/Vertical",
+        "Gradual/Horizontal",
+        "Rapid/Vertical",
+        "Rapid/Horizontal",
+        "Instant/Vertical",
+        "Instant/Horizontal",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "Straight Arms/Horizontal",
+        "Straight Arms/Wedge",
+        "Straight Arms/Vertical",
+        "Straight Arms/Single Serif",
+        "Straight Arms/Double Serif",
+        "Non-Straight/Horizontal",
+        "Non-Straight/Wedge",
+        "Non-Straight/Vertical",
+        "Non-Straight/Single Serif",
+        "Non-Straight/Double Serif",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "Normal/Contact",
+        "Normal/Weighted",
+        "Normal/Boxed",
+        "Normal/Flattened",
+        "Normal/Rounded",
+        "Normal/Off Center",
+        "Normal/Square",
+        "Oblique/Contact",
+        "Oblique/Weighted",
+        "Oblique/Boxed",
+        "Oblique/Flattened",
+        "Oblique/Rounded",
+        "Oblique/Off Center",
+        "Oblique/Square",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "Standard/Trimmed",
+        "Standard/Pointed",
+        "Standard/Serifed",
+        "High/Trimmed",
+        "High/Pointed",
+        "High/Serifed",
+        "Constant/Trimmed",
+        "Constant/Pointed",
+        "Constant/Serifed",
+        "Low/Trimmed",
+        "Low/Pointed",
+        "Low/Serifed",
+    ],
+    &[
+        "Any",
+        "No Fit",
+        "Constant/Small",
+        "Constant/Standard",
+        "Constant/Large",
+        "Ducking/Small",
+        "Ducking/Standard",
+        "Ducking/Large",
+    ],
+];
+
+/// A single PANOSE classification.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PanoseDigit {
+    pub classification: &'static str,
+    pub value: u8,
+    /// The name of the value. Only present for the family kind and the classifications of Latin
+    /// Text fonts, and only for known values.
+    pub name: Option<&'static str>,
+}
+
+/// Decode the ten PANOSE classifications.
+fn panose(digits: &[u8]) -> Vec<PanoseDigit> {
+    let family_kind = digits.first().copied().unwrap_or(0);
+    panose_classifications(family_kind)
+        .into_iter()
+        .zip(digits)
+        .enumerate()
+        .map(|(idx, (classification, &value))| {
+            let names: &[&str] = match idx {
+                0 => &PANOSE_FAMILY_KINDS,
+                _ if family_kind == 2 => PANOSE_LATIN_TEXT[idx - 1],
+                // "Any" and "No Fit" mean the same for all family kinds
+                _ => &PANOSE_FAMILY_KINDS[..2],
+            };
+            PanoseDigit {
+                classification,
+                value,
+                name: names.get(usize::from(value)).copied(),
+            }
+        })
+        .collect()
+}
+
+/// Get the names of the bits set in a bit field of 32-bit words, in increasing bit order.
+/// Reserved bits are named by their number.
+fn bit_names(words: &[u32], name: impl Fn(u32) -> Option<&'static str>) -> Vec<String> {
+    (0..words.len() as u32 * 32)
+        .filter(|&bit| words[bit as usize / 32] & (1 << (bit % 32)) != 0)
+        .map(|bit| match name(bit) {
+            Some(name) => name.to_owned(),
+            None => format!("Reserved (bit {bit})"),
+        })
+        .collect()
+}
+
+/// Details of the font's `OS/2` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Os2 {
+    pub version: u16,
+    pub vendor_id: String,
+    pub weight_class: u16,
+    pub width_class: u16,
+    /// The names of the `fsSelection` bits that are set.
+    pub fs_selection: Vec<String>,
+    pub panose: Vec<PanoseDigit>,
+    /// The names of the Unicode ranges the font claims to support.
+    pub unicode_ranges: Vec<String>,
+    /// The names of the code pages the font claims to support. Not present before version 1.
+    pub code_page_ranges: Option<Vec<String>>,
+    /// Not present before version 2.
+    pub default_char: Option<u16>,
+    /// Not present before version 2.
+    pub break_char: Option<u16>,
+}
+
+impl Os2 {
+    /// Returns `None` if the font has no `OS/2` table.
+    pub fn from_font(font: FontRef<'_>) -> Option<Self> {
+        let os2 = read_table::<Os2Table>(font)?;
+
+        let code_page_ranges = os2
+            .ul_code_page_range_1()
+            .zip(os2.ul_code_page_range_2())
+            .map(|(range_1, range_2)| {
+                bit_names(&[range_1, range_2], |bit| {
+                    CODE_PAGES
+                        .iter()
+                        .find(|&&(code_page_bit, _)| code_page_bit == bit)
+                        .map(|&(_, name)| name)
+                })
+            });
+
+        Some(Os2 {
+            version: os2.version(),
+            vendor_id: tag_to_string(u32::from_be_bytes(os2.ach_vend_id().to_be_bytes())),
+            weight_class: os2.us_weight_class(),
+            width_class: os2.us_width_class(),
+            fs_selection: bit_names(&[u32::from(os2.fs_selection().bits())], |bit| {
+                FS_SELECTION.get(bit as usize).copied()
+            }),
+            panose: panose(os2.panose_10()),
+            unicode_ranges: bit_names(
+                &[
+                    os2.ul_unicode_range_1(),
+                    os2.ul_unicode_range_2(),
+                    os2.ul_unicode_range_3(),
+                    os2.ul_unicode_range_4(),
+                ],
+                |bit| UNICODE_RANGES.get(bit as usize).copied(),
+            ),
+            code_page_ranges,
+            default_char: os2.us_default_char(),
+            break_char: os2.us_break_char(),
+        })
+    }
+}
+
+/// Write the OS/2 table details of a font in the human-readable format, or that the font has no
+/// OS/2 table.
+pub(crate) fn write_os2(mut write: impl std::io::Write, os2: Option<&Os2>) -> std::io::Result<()> {
+    let Some(os2) = os2 else {
+        return writeln!(write, "                OS/2: no OS/2 table");
+    };
+
+    writeln!(
+        write,
+        "                OS/2: version {}, vendor ID \"{}\"",
+        os2.version, os2.vendor_id
+    )?;
+    writeln!(write, "        Weight class: {}", os2.weight_class)?;
+    writeln!(write, "         Width class: {}", os2.width_class)?;
+    writeln!(
+        write,
+        "         fsSelection: {}",
+        os2.fs_selection.join(", ")
+    )?;
+
+    write!(write, "              PANOSE: ")?;
+    for (idx, digit) in os2.panose.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        write!(write, "{}: {}", digit.classification, digit.value)?;
+        if let Some(name) = digit.name {
+            write!(write, " ({name})")?;
+        }
+    }
+    writeln!(write)?;
+
+    let ranges = [
+        ("      Unicode ranges: ", Some(&os2.unicode_ranges)),
+        ("    Code page ranges: ", os2.code_page_ranges.as_ref()),
+    ];
+    for (label, names) in ranges {
+        let Some(names) = names else { continue };
+        write!(write, "{label}")?;
+        for (idx, name) in names.iter().enumerate() {
+            if idx > 0 {
+                write!(write, "\n                      ")?;
+            }
+            write!(write, "{name}")?;
+        }
+        writeln!(write)?;
+    }
+
+    for (label, char) in [
+        ("        Default char: ", os2.default_char),
+        ("          Break char: ", os2.break_char),
+    ] {
+        if let Some(char) = char {
+            write!(write, "{label}U+{char:04X}")?;
+            if let Some(c) =
+                char::from_u32(u32::from(char)).filter(|c| !c.is_control() && !c.is_whitespace())
+            {
+                write!(write, " {c}")?;
+            }
+            writeln!(write)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bit_names() {
+        let names = bit_names(&[0b1000_0001, 1 << 31], |bit| {
+            FS_SELECTION.get(bit as usize).copied()
+        });
+        assert_eq!(names, ["ITALIC", "USE_TYPO_METRICS", "Reserved (bit 63)"]);
+    }
+
+    #[test]
+    fn test_panose() {
+        let digits = panose(&[2, 11, 6, 3, 3, 8, 4, 2, 2, 4]);
+        assert_eq!(digits.len(), 10);
+        assert_eq!(digits[0].name, Some("Latin Text"));
+        assert_eq!(digits[1].classification, "Serif Style");
+        assert_eq!(digits[1].name, Some("Normal Sans"));
+        assert_eq!(digits[2].name, Some("Medium"));
+        assert_eq!(digits[9].classification, "X-height");
+        assert_eq!(digits[9].name, Some("Constant/Large"));
+
+        let digits = panose(&[3, 1, 0, 5, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(digits[0].name, Some("Latin Hand Written"));
+        assert_eq!(digits[1].classification, "Tool Kind");
+        assert_eq!(digits[1].name, Some("No Fit"));
+        assert_eq!(digits[3].name, None);
+    }
+}
@@ -0,0 +1,339 @@
+use std::{
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use swash::{
+    scale::{image::Content, Render, ScaleContext, Source, StrikeWith},
+    shape::ShapeContext,
+    zeno::{Format, Vector},
+    FontRef,
+};
+
+use crate::{
+    shaping::{self, ShapeOptions},
+    Options, Out, Outcome,
+};
+
+/// Arguments of the render subcommand.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct RenderArgs {
+    #[command(flatten)]
+    pub input: crate::files::PathArgs,
+
+    /// The text to render.
+    #[arg(long)]
+    text: String,
+
+    /// The font size in pixels per em.
+    #[arg(long, default_value_t = 32.)]
+    size: f32,
+
+    /// The image file to write. The image format is determined by the file extension: PNG
+    /// ('.png') or PGM ('.pgm').
+    #[arg(short, long)]
+    output: PathBuf,
+
+    /// Write a grayscale image rather than an RGBA image. PGM images are always grayscale.
+    #[arg(long)]
+    grayscale: bool,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ImageFormat {
+    Png,
+    Pgm,
+}
+
+impl ImageFormat {
+    fn from_path(path: &Path) -> anyhow::Result<Self> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("png") => Ok(ImageFormat::Png),
+            Some(ext) if ext.eq_ignore_ascii_case("pgm") => Ok(ImageFormat::Pgm),
+            _ => anyhow::bail!(
+                "Cannot determine the image format of '{}': expected a '.png' or '.pgm' extension",
+                path.to_string_lossy()
+            ),
+        }
+    }
+}
+
+/// The glyph sources to render from, in order of preference. Color glyphs are preferred.
+const SOURCES: [Source; 4] = [
+    Source::ColorOutline(0),
+    Source::ColorBitmap(StrikeWith::BestFit),
+    Source::Outline,
+    Source::Bitmap(StrikeWith::BestFit),
+];
+
+const BACKGROUND: [u8; 4] = [255, 255, 255, 255];
+const FOREGROUND: [u8; 4] = [0, 0, 0, 255];
+
+/// An RGBA image with an opaque background.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: vec![BACKGROUND; width as usize * height as usize],
+        }
+    }
+
+    /// Blend a color with the given alpha over the pixel at (x, y). Pixels outside the canvas are
+    /// ignored.
+    fn blend(&mut self, x: i32, y: i32, color: [u8; 3], alpha: u8) {
+        if x < 0 || y < 0 || x as u32 >= self.width || y as u32 >= self.height || alpha == 0 {
+            return;
+        }
+
+        let pixel = &mut self.pixels[y as usize * self.width as usize + x as usize];
+        let alpha = u32::from(alpha);
+        for channel in 0..3 {
+            pixel[channel] = ((u32::from(color[channel]) * alpha
+                + u32::from(pixel[channel]) * (255 - alpha)
+                + 127)
+                / 255) as u8;
+        }
+    }
+
+    /// Draw another canvas onto this one, with its top-left corner at (x, y).
+    fn draw(&mut self, other: &Canvas, x: u32, y: u32) {
+        for row in 0..other.height.min(self.height.saturating_sub(y)) {
+            let src = row as usize * other.width as usize;
+            let dst = (y + row) as usize * self.width as usize + x as usize;
+            let len = other.width.min(self.width.saturating_sub(x)) as usize;
+            self.pixels[dst..dst + len].copy_from_slice(&other.pixels[src..src + len]);
+        }
+    }
+
+    /// Stack canvases vertically, aligned to the left, into a canvas as wide as the widest one.
+    fn stack(canvases: &[Canvas]) -> Canvas {
+        let width = canvases
+            .iter()
+            .map(|canvas| canvas.width)
+            .max()
+            .unwrap_or(1);
+        let height = canvases.iter().map(|canvas| canvas.height).sum();
+        let mut stacked = Canvas::new(width, height);
+        let mut y = 0;
+        for canvas in canvases {
+            stacked.draw(canvas, 0, y);
+            y += canvas.height;
+        }
+        stacked
+    }
+
+    fn to_gray(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .map(|&[r, g, b, _]| {
+                (0.2126 * f32::from(r) + 0.7152 * f32::from(g) + 0.0722 * f32::from(b)).round()
+                    as u8
+            })
+            .collect()
+    }
+}
+
+/// Renders the text with each font on its own line, and writes the lines stacked vertically to an
+/// image file when finished.
+pub(crate) struct Renderer {
+    args: RenderArgs,
+    format: ImageFormat,
+    options: Options,
+    shape_context: ShapeContext,
+    scale_context: ScaleContext,
+    lines: Vec<Canvas>,
+}
+
+impl Renderer {
+    pub fn new(args: RenderArgs, options: Options) -> anyhow::Result<Self> {
+        if args.size.is_nan() || args.size <= 0. {
+            anyhow::bail!("The font size must be positive");
+        }
+
+        Ok(Renderer {
+            format: ImageFormat::from_path(&args.output)?,
+            args,
+            options,
+            shape_context: ShapeContext::new(),
+            scale_context: ScaleContext::new(),
+            lines: Vec::new(),
+        })
+    }
+
+    /// Write the rendered lines to the output image file.
+    pub fn finish(self) -> anyhow::Result<()> {
+        if self.lines.is_empty() {
+            anyhow::bail!("No fonts to render");
+        }
+
+        let image = Canvas::stack(&self.lines);
+        let (width, height) = (image.width, image.height);
+
+        let output = &self.args.output;
+        log::info!(
+            "Writing {width}x{height} image to '{}'",
+            output.to_string_lossy()
+        );
+
+        let file = std::fs::File::create(output).with_context(|| {
+            format!("Failed creating image file '{}'", output.to_string_lossy())
+        })?;
+        let mut write = BufWriter::new(file);
+
+        match self.format {
+            ImageFormat::Pgm => {
+                write!(write, "P5\n{width} {height}\n255\n")?;
+                write.write_all(&image.to_gray())?;
+            }
+            ImageFormat::Png => {
+                let mut encoder = png::Encoder::new(&mut write, width, height);
+                encoder.set_depth(png::BitDepth::Eight);
+                let data = if self.args.grayscale {
+                    encoder.set_color(png::ColorType::Grayscale);
+                    image.to_gray()
+                } else {
+                    encoder.set_color(png::ColorType::Rgba);
+                    image.pixels.concat()
+                };
+                encoder.write_header()?.write_image_data(&data)?;
+            }
+        }
+        write.flush()?;
+
+        Ok(())
+    }
+}
+
+impl Out for Renderer {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        log::info!("Rendering font {font_index} of '{source}'");
+
+        let size = self.args.size;
+        let location = self.options.location(font)?;
+        let metrics = font.metrics(&location.normalized).scale(size);
+        let shaped = shaping::shape(
+            &mut self.shape_context,
+            font,
+            &self.args.text,
+            size,
+            &location.normalized,
+            &ShapeOptions::default(),
+        );
+
+        let padding = (size / 4.).ceil();
+        let baseline = padding + metrics.ascent.ceil();
+        let width = (shaped.advance.ceil() + 2. * padding).max(1.) as u32;
+        let height = (baseline + metrics.descent.ceil() + padding).max(1.) as u32;
+        let mut canvas = Canvas::new(width, height);
+
+        let mut scaler = self
+            .scale_context
+            .builder(font)
+            .size(size)
+            .normalized_coords(&location.normalized)
+            .hint(false)
+            .build();
+
+        let mut pen = padding;
+        for glyph in &shaped.glyphs {
+            let x = pen + glyph.x_offset;
+            let y = baseline - glyph.y_offset;
+            pen += glyph.x_advance;
+            let Some(image) = Render::new(&SOURCES)
+                .format(Format::Alpha)
+                .default_color(FOREGROUND)
+                .offset(Vector::new(x.fract(), -y.fract()))
+                .render(&mut scaler, glyph.glyph_id)
+            else {
+                continue;
+            };
+
+            let left = x.floor() as i32 + image.placement.left;
+            let top = y.floor() as i32 - image.placement.top;
+            let glyph_width = image.placement.width as usize;
+            for row in 0..image.placement.height as usize {
+                for col in 0..glyph_width {
+                    let (px, py) = (left + col as i32, top + row as i32);
+                    match image.content {
+                        Content::Mask => {
+                            let alpha = image.data[row * glyph_width + col];
+                            let [r, g, b, _] = FOREGROUND;
+                            canvas.blend(px, py, [r, g, b], alpha);
+                        }
+                        Content::Color | Content::SubpixelMask => {
+                            let offset = (row * glyph_width + col) * 4;
+                            let [r, g, b, a]: [u8; 4] = image.data[offset..offset + 4]
+                                .try_into()
+                                .expect("slice has four bytes");
+                            canvas.blend(px, py, [r, g, b], a);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.lines.push(canvas);
+
+        Ok(Outcome::Pass)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_format() {
+        assert!(ImageFormat::from_path(Path::new("text.png")).unwrap() == ImageFormat::Png);
+        assert!(ImageFormat::from_path(Path::new("out/text.PGM")).unwrap() == ImageFormat::Pgm);
+        assert!(ImageFormat::from_path(Path::new("text.jpg")).is_err());
+        assert!(ImageFormat::from_path(Path::new("text")).is_err());
+    }
+
+    #[test]
+    fn test_blend() {
+        let mut canvas = Canvas::new(2, 2);
+        canvas.blend(0, 0, [0, 0, 0], 255);
+        canvas.blend(1, 0, [0, 0, 0], 128);
+        canvas.blend(0, 1, [255, 0, 0], 255);
+        // outside the canvas
+        canvas.blend(-1, 0, [0, 0, 0], 255);
+        canvas.blend(2, 1, [0, 0, 0], 255);
+
+        assert_eq!(
+            canvas.pixels,
+            [
+                [0, 0, 0, 255],
+                [127, 127, 127, 255],
+                [255, 0, 0, 255],
+                BACKGROUND
+            ]
+        );
+        assert_eq!(canvas.to_gray(), [0, 127, 54, 255]);
+    }
+
+    #[test]
+    fn test_stack() {
+        let mut first = Canvas::new(3, 1);
+        first.blend(2, 0, [0, 0, 0], 255);
+        let mut second = Canvas::new(1, 2);
+        second.blend(0, 1, [0, 0, 0], 255);
+
+        let stacked = Canvas::stack(&[first, second]);
+        assert_eq!((stacked.width, stacked.height), (3, 3));
+        assert_eq!(stacked.to_gray(), [255, 255, 0, 255, 255, 255, 0, 255, 255]);
+    }
+}
@@ -0,0 +1,107 @@
+use swash::{Setting, Tag};
+
+/// Parse a four-character OpenType tag, such as "wght". Tags shorter than four characters are
+/// padded with spaces.
+pub(crate) fn parse_tag(tag: &str) -> Result<Tag, String> {
+    if tag.is_empty() || tag.len() > 4 || !tag.is_ascii() {
+        return Err(format!(
+            "'{tag}' is not a valid tag: expected one to four ASCII characters"
+        ));
+    }
+
+    let mut bytes = [b' '; 4];
+    bytes[..tag.len()].copy_from_slice(tag.as_bytes());
+    Ok(Tag::from_be_bytes(bytes))
+}
+
+/// Format an OpenType tag as a string. Trailing spaces are kept.
+pub(crate) fn tag_to_string(tag: Tag) -> String {
+    tag.to_be_bytes()
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '?'
+            }
+        })
+        .collect()
+}
+
+/// Parse a variation setting of the form "wght=700".
+pub(crate) fn parse_variation(setting: &str) -> Result<Setting<f32>, String> {
+    let (tag, value) = setting
+        .split_once('=')
+        .ok_or_else(|| format!("'{setting}' is not of the form 'tag=value'"))?;
+
+    let tag = parse_tag(tag.trim())?;
+    let value = value
+        .trim()
+        .parse::<f32>()
+        .map_err(|_| format!("'{value}' is not a valid variation value"))?;
+
+    Ok(Setting { tag, value })
+}
+
+/// Parse a feature setting of the form "liga=0". A feature given without a value, such as "ss01",
+/// is enabled.
+pub(crate) fn parse_feature(setting: &str) -> Result<Setting<u16>, String> {
+    let (tag, value) = match setting.split_once('=') {
+        Some((tag, value)) => {
+            let value = value
+                .trim()
+                .parse::<u16>()
+                .map_err(|_| format!("'{value}' is not a valid feature value"))?;
+            (tag, value)
+        }
+        None => (setting, 1),
+    };
+
+    Ok(Setting {
+        tag: parse_tag(tag.trim())?,
+        value,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tag() {
+        assert_eq!(parse_tag("wght"), Ok(u32::from_be_bytes(*b"wght")));
+        assert_eq!(parse_tag("cv"), Ok(u32::from_be_bytes(*b"cv  ")));
+        assert!(parse_tag("").is_err());
+        assert!(parse_tag("toolong").is_err());
+        assert_eq!(tag_to_string(u32::from_be_bytes(*b"OS/2")), "OS/2");
+    }
+
+    #[test]
+    fn test_parse_variation() {
+        let setting = parse_variation("wght=700").unwrap();
+        assert_eq!(setting.tag, u32::from_be_bytes(*b"wght"));
+        assert_eq!(setting.value, 700.);
+
+        let setting = parse_variation(" wdth = 87.5 ").unwrap();
+        assert_eq!(setting.tag, u32::from_be_bytes(*b"wdth"));
+        assert_eq!(setting.value, 87.5);
+
+        assert!(parse_variation("wght").is_err());
+        assert!(parse_variation("wght=bold").is_err());
+    }
+
+    #[test]
+    fn test_parse_feature() {
+        let setting = parse_feature("liga=0").unwrap();
+        assert_eq!(setting.tag, u32::from_be_bytes(*b"liga"));
+        assert_eq!(setting.value, 0);
+
+        let setting = parse_feature(" ss01 ").unwrap();
+        assert_eq!(setting.tag, u32::from_be_bytes(*b"ss01"));
+        assert_eq!(setting.value, 1);
+
+        assert_eq!(parse_feature("aalt=3").unwrap().value, 3);
+        assert!(parse_feature("liga=-1").is_err());
+        assert!(parse_feature("=1").is_err());
+    }
+}
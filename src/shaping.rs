@@ -0,0 +1,499 @@
+use std::io::Write;
+
+use swash::{
+    shape::{Direction, ShapeContext},
+    text::{BidiClass, Codepoint, Language, Script},
+    FontRef, GlyphId, NormalizedCoord, Setting,
+};
+
+use crate::{glyphs::GlyphNames, names, settings, FontError, Options, Out, Outcome};
+
+/// Arguments of the shape subcommand.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ShapeArgs {
+    #[command(flatten)]
+    pub input: crate::files::PathArgs,
+
+    /// The text to shape.
+    #[arg(long)]
+    text: String,
+
+    /// Scale advances and offsets to the given font size in pixels per em, rather than printing
+    /// them in font units.
+    #[arg(long)]
+    size: Option<f32>,
+
+    #[command(flatten)]
+    shaping: ShapingArgs,
+}
+
+/// Arguments controlling how text is shaped.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ShapingArgs {
+    /// The script of the text as an OpenType or ISO 15924 script tag, e.g., "arab" or "Latn".
+    /// Detected from the text by default.
+    #[arg(long, value_parser = parse_script)]
+    script: Option<Script>,
+
+    /// The language of the text as a BCP 47 language tag, e.g., "tr" or "nl-BE".
+    #[arg(long, value_parser = parse_language)]
+    language: Option<String>,
+
+    /// The direction of the text. Detected from the text by default.
+    #[arg(long)]
+    direction: Option<DirectionArg>,
+
+    /// Feature settings to apply, e.g., "liga=0,ss01". Features given without a value are
+    /// enabled.
+    #[arg(long, value_delimiter = ',', value_parser = settings::parse_feature)]
+    features: Vec<Setting<u16>>,
+}
+
+#[derive(Copy, Clone, clap::ValueEnum, Debug)]
+enum DirectionArg {
+    Ltr,
+    Rtl,
+}
+
+fn parse_script(arg: &str) -> Result<Script, String> {
+    settings::parse_tag(&arg.to_ascii_lowercase())
+        .ok()
+        .and_then(Script::from_opentype)
+        .ok_or_else(|| format!("'{arg}' is not a known script tag"))
+}
+
+fn parse_language(arg: &str) -> Result<String, String> {
+    match Language::parse(arg) {
+        Some(_) => Ok(arg.to_owned()),
+        None => Err(format!("'{arg}' is not a valid language tag")),
+    }
+}
+
+/// Options for shaping text. Options that are not given are detected from the text.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ShapeOptions {
+    pub script: Option<Script>,
+    pub language: Option<Language>,
+    pub direction: Option<Direction>,
+    pub features: Vec<Setting<u16>>,
+}
+
+/// The range of the text, in bytes, that a glyph was shaped from.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SourceRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// A glyph in a run of shaped text.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ShapedGlyph {
+    pub glyph_id: GlyphId,
+    /// The source range of the cluster the glyph belongs to.
+    pub cluster: SourceRange,
+    pub x_advance: f32,
+    /// Always zero, as only horizontal text is supported.
+    pub y_advance: f32,
+    /// The horizontal offset of the glyph from its pen position.
+    pub x_offset: f32,
+    /// The vertical offset of the glyph from the baseline, positive upward.
+    pub y_offset: f32,
+}
+
+/// A single line of shaped text, with glyphs in visual order.
+#[derive(Debug)]
+pub(crate) struct ShapedText {
+    pub script: Script,
+    pub direction: Direction,
+    pub glyphs: Vec<ShapedGlyph>,
+    /// The total advance of the line.
+    pub advance: f32,
+}
+
+/// Detect the script of the text, which is the script of its first character that is not
+/// common to multiple scripts. Falls back to Latin.
+pub(crate) fn detect_script(text: &str) -> Script {
+    text.chars()
+        .map(|c| c.script())
+        .find(|script| !matches!(script, Script::Common | Script::Inherited | Script::Unknown))
+        .unwrap_or(Script::Latin)
+}
+
+/// Detect the direction of the text from its first strongly directional character.
+pub(crate) fn detect_direction(text: &str) -> Direction {
+    text.chars()
+        .find_map(|c| match c.bidi_class() {
+            BidiClass::L => Some(Direction::LeftToRight),
+            BidiClass::R | BidiClass::AL => Some(Direction::RightToLeft),
+            _ => None,
+        })
+        .unwrap_or(Direction::LeftToRight)
+}
+
+/// Shape a single line of text at the given normalized variation coordinates. Advances and
+/// offsets are in pixels at the given size in pixels per em, or in font units if the size is 0.
+pub(crate) fn shape(
+    context: &mut ShapeContext,
+    font: FontRef<'_>,
+    text: &str,
+    size: f32,
+    coords: &[NormalizedCoord],
+    options: &ShapeOptions,
+) -> ShapedText {
+    let script = options.script.unwrap_or_else(|| detect_script(text));
+    let direction = options.direction.unwrap_or_else(|| detect_direction(text));
+    let mut shaper = context
+        .builder(font)
+        .script(script)
+        .language(options.language)
+        .direction(direction)
+        .size(size)
+        .normalized_coords(coords)
+        .features(options.features.iter().copied())
+        .build();
+    shaper.add_str(text);
+
+    let mut glyphs = Vec::new();
+    let mut clusters = Vec::new();
+    shaper.shape_with(|cluster| {
+        let start = glyphs.len();
+        glyphs.extend(cluster.glyphs.iter().map(|glyph| ShapedGlyph {
+            glyph_id: glyph.id,
+            cluster: SourceRange {
+                start: cluster.source.start,
+                end: cluster.source.end,
+            },
+            x_advance: glyph.advance,
+            y_advance: 0.,
+            x_offset: glyph.x,
+            y_offset: glyph.y,
+        }));
+        clusters.push(start..glyphs.len());
+    });
+
+    // swash does not reverse right-to-left runs, so the clusters are in logical order
+    if direction == Direction::RightToLeft {
+        clusters.reverse();
+    }
+
+    let glyphs: Vec<ShapedGlyph> = clusters
+        .into_iter()
+        .flat_map(|range| glyphs[range].iter().copied())
+        .collect();
+    ShapedText {
+        script,
+        direction,
+        advance: glyphs
+            .iter()
+            .fold(0., |advance, glyph| advance + glyph.x_advance),
+        glyphs,
+    }
+}
+
+fn direction_name(direction: Direction) -> &'static str {
+    match direction {
+        Direction::LeftToRight => "left-to-right",
+        Direction::RightToLeft => "right-to-left",
+    }
+}
+
+impl ShapingArgs {
+    pub fn options(&self) -> ShapeOptions {
+        ShapeOptions {
+            script: self.script,
+            language: self.language.as_deref().and_then(Language::parse),
+            direction: self.direction.map(|direction| match direction {
+                DirectionArg::Ltr => Direction::LeftToRight,
+                DirectionArg::Rtl => Direction::RightToLeft,
+            }),
+            features: self.features.clone(),
+        }
+    }
+}
+
+impl ShapeArgs {
+    fn shape(
+        &self,
+        context: &mut ShapeContext,
+        font: FontRef<'_>,
+        coords: &[NormalizedCoord],
+    ) -> ShapedText {
+        shape(
+            context,
+            font,
+            &self.text,
+            self.size.unwrap_or(0.),
+            coords,
+            &self.shaping.options(),
+        )
+    }
+}
+
+/// Prints the glyph runs of text shaped with each font in human-readable form.
+pub(crate) struct HumanReadableRun<W> {
+    write: W,
+    args: ShapeArgs,
+    options: Options,
+    context: ShapeContext,
+    /// the number of fonts already written
+    fonts_written: usize,
+}
+
+impl<W> HumanReadableRun<W> {
+    pub fn new(write: W, args: ShapeArgs, options: Options) -> Self {
+        HumanReadableRun {
+            write,
+            args,
+            options,
+            context: ShapeContext::new(),
+            fonts_written: 0,
+        }
+    }
+}
+
+impl<W: Write> Out for HumanReadableRun<W> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        let location = self.options.location(font)?;
+        self.fonts_written += 1;
+        crate::write_font_header(
+            &mut self.write,
+            self.fonts_written,
+            source,
+            font_index,
+            font,
+            self.options.name_language.as_deref(),
+        )?;
+
+        crate::variations::write_coordinates(&mut self.write, &location)?;
+
+        let shaped = self
+            .args
+            .shape(&mut self.context, font, &location.normalized);
+
+        writeln!(self.write, "              Script: {}", shaped.script.name())?;
+        if let Some(language) = &self.args.shaping.language {
+            writeln!(self.write, "            Language: {language}")?;
+        }
+        writeln!(
+            self.write,
+            "           Direction: {}",
+            direction_name(shaped.direction)
+        )?;
+
+        let glyph_names = GlyphNames::new(font);
+        write!(self.write, "           Glyph run: ")?;
+        for (idx, glyph) in shaped.glyphs.iter().enumerate() {
+            if idx > 0 {
+                write!(self.write, "\n                      ")?;
+            }
+            write!(self.write, "glyph {}", glyph.glyph_id)?;
+            if let Some(name) = glyph_names.get(glyph.glyph_id) {
+                write!(self.write, " \"{name}\"")?;
+            }
+            write!(
+                self.write,
+                ": cluster {}..{}, advance ({}, {}), offset ({}, {})",
+                glyph.cluster.start,
+                glyph.cluster.end,
+                glyph.x_advance,
+                glyph.y_advance,
+                glyph.x_offset,
+                glyph.y_offset
+            )?;
+        }
+        writeln!(self.write)?;
+        writeln!(self.write, "       Total advance: {}", shaped.advance)?;
+
+        Ok(Outcome::Pass)
+    }
+}
+
+/// Outputs the glyph runs of text shaped with each font as JSON.
+pub(crate) struct JsonRun<S> {
+    pub serializer: S,
+    args: ShapeArgs,
+    options: Options,
+    context: ShapeContext,
+}
+
+impl<S> JsonRun<S> {
+    pub fn new(serializer: S, args: ShapeArgs, options: Options) -> Self {
+        JsonRun {
+            serializer,
+            args,
+            options,
+            context: ShapeContext::new(),
+        }
+    }
+}
+
+impl<S: serde::ser::SerializeSeq<Error = serde_json::Error>> Out for JsonRun<S> {
+    fn push_font(
+        &mut self,
+        source: &str,
+        font_index: usize,
+        font: FontRef<'_>,
+    ) -> anyhow::Result<Outcome> {
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Glyph<'m> {
+            #[serde(flatten)]
+            glyph: &'m ShapedGlyph,
+            name: Option<String>,
+        }
+
+        #[derive(serde::Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Out<'m> {
+            source: &'m str,
+            font_index: usize,
+            family_name: Option<&'m str>,
+            subfamily_name: Option<&'m str>,
+            coordinates: &'m [crate::variations::Coordinate],
+            script: &'m str,
+            language: Option<&'m str>,
+            direction: &'m str,
+            glyphs: &'m [Glyph<'m>],
+            advance: f32,
+        }
+
+        let location = self.options.location(font)?;
+        let language = self.options.name_language.as_deref();
+        let shaped = self
+            .args
+            .shape(&mut self.context, font, &location.normalized);
+        let glyph_names = GlyphNames::new(font);
+        let glyphs: Vec<Glyph> = shaped
+            .glyphs
+            .iter()
+            .map(|glyph| Glyph {
+                glyph,
+                name: glyph_names.get(glyph.glyph_id),
+            })
+            .collect();
+
+        serde::ser::SerializeSeq::serialize_element(
+            &mut self.serializer,
+            &Out {
+                source,
+                font_index,
+                family_name: names::family_name(font, language).as_deref(),
+                subfamily_name: names::subfamily_name(font, language).as_deref(),
+                coordinates: &location.coordinates,
+                script: shaped.script.name(),
+                language: self.args.shaping.language.as_deref(),
+                direction: direction_name(shaped.direction),
+                glyphs: &glyphs,
+                advance: shaped.advance,
+            },
+        )?;
+
+        Ok(Outcome::Pass)
+    }
+
+    fn push_error(&mut self, error: &FontError) -> anyhow::Result<()> {
+        serde::ser::SerializeSeq::serialize_element(&mut self.serializer, error)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::test_fonts::TestFont;
+
+    #[derive(Parser)]
+    struct TestCli {
+        #[command(flatten)]
+        shaping: ShapingArgs,
+    }
+
+    #[test]
+    fn test_options() {
+        let options = TestCli::parse_from([
+            "test",
+            "--script",
+            "Arab",
+            "--language",
+            "tr",
+            "--direction",
+            "rtl",
+            "--features",
+            "liga=0,ss01",
+        ])
+        .shaping
+        .options();
+        assert_eq!(options.script, Some(Script::Arabic));
+        assert_eq!(
+            options
+                .language
+                .map(|language| language.language().to_owned()),
+            Some("tr".to_owned())
+        );
+        assert_eq!(options.direction, Some(Direction::RightToLeft));
+        let features: Vec<(swash::Tag, u16)> = options
+            .features
+            .iter()
+            .map(|setting| (setting.tag, setting.value))
+            .collect();
+        assert_eq!(
+            features,
+            [
+                (swash::tag_from_bytes(b"liga"), 0),
+                (swash::tag_from_bytes(b"ss01"), 1)
+            ]
+        );
+
+        // options that are not given are detected from the text
+        let options = TestCli::parse_from(["test"]).shaping.options();
+        assert!(options.script.is_none() && options.language.is_none());
+        assert!(options.direction.is_none() && options.features.is_empty());
+
+        assert!(TestCli::try_parse_from(["test", "--script", "xyz"]).is_err());
+        assert!(TestCli::try_parse_from(["test", "--language", "not a tag"]).is_err());
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(detect_script("123 שלום"), Script::Hebrew);
+        assert_eq!(detect_direction("123 שלום"), Direction::RightToLeft);
+        assert_eq!(detect_script("123"), Script::Latin);
+        assert_eq!(detect_direction("(abc)"), Direction::LeftToRight);
+    }
+
+    #[test]
+    fn test_shape() {
+        let data = TestFont::new(&[('a', 400), ('b', 600)]).build();
+        let font = FontRef::from_index(&data, 0).unwrap();
+        let mut context = ShapeContext::new();
+        let glyphs = |shaped: &ShapedText| -> Vec<(GlyphId, u32, f32)> {
+            shaped
+                .glyphs
+                .iter()
+                .map(|glyph| (glyph.glyph_id, glyph.cluster.start, glyph.x_advance))
+                .collect()
+        };
+
+        let shaped = shape(&mut context, font, "ab", 0., &[], &ShapeOptions::default());
+        assert_eq!(shaped.direction, Direction::LeftToRight);
+        assert_eq!(glyphs(&shaped), [(1, 0, 400.), (2, 1, 600.)]);
+        assert_eq!(shaped.advance, 1000.);
+
+        // right-to-left clusters are reversed into visual order
+        let options = ShapeOptions {
+            direction: Some(Direction::RightToLeft),
+            ..ShapeOptions::default()
+        };
+        let shaped = shape(&mut context, font, "ab", 20., &[], &options);
+        assert_eq!(glyphs(&shaped), [(2, 1, 12.), (1, 0, 8.)]);
+    }
+}
@@ -0,0 +1,489 @@
+use std::collections::HashMap;
+
+use read_fonts::{
+    tables::{
+        gsub::{ChainedSequenceContext, Gsub, SequenceContext, SingleSubst, SubstitutionSubtables},
+        layout::SequenceLookupRecord,
+    },
+    types::GlyphId as ReadGlyphId,
+};
+use swash::{FontRef, GlyphId, Tag};
+
+use crate::{coverage, glyphs::GlyphNames, settings::tag_to_string, tables::read_table};
+
+/// A glyph in a substitution.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SubstitutionGlyph {
+    pub id: GlyphId,
+    pub name: Option<String>,
+    /// The character the font's character map maps to the glyph, if any.
+    pub character: Option<char>,
+}
+
+impl std::fmt::Display for SubstitutionGlyph {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}")?,
+            None => write!(f, "gid{}", self.id)?,
+        }
+        if let Some(c) = self
+            .character
+            .filter(|c| !c.is_control() && !c.is_whitespace())
+        {
+            if self.name.as_deref() != Some(c.encode_utf8(&mut [0; 4])) {
+                write!(f, " ({c})")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A substitution of a glyph sequence.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Substitution {
+    pub input: Vec<SubstitutionGlyph>,
+    /// The glyphs the input is replaced by. For alternate substitutions, these are the
+    /// alternatives of which one is chosen.
+    pub output: Vec<SubstitutionGlyph>,
+}
+
+/// A GSUB lookup and the substitutions it makes.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct LookupSubstitutions {
+    pub index: u16,
+    /// E.g., "single", "ligature" or "chained contextual".
+    pub kind: &'static str,
+    /// Whether the feature refers to the lookup directly, rather than only through the rules of
+    /// contextual lookups.
+    pub in_feature: bool,
+    /// The substitutions of single, multiple, alternate, ligature and reverse chaining lookups.
+    /// Reverse chaining substitutions only apply in the context given by their subtable.
+    pub substitutions: Vec<Substitution>,
+    /// The number of rules of contextual lookups.
+    pub rules: usize,
+    /// The lookups the rules of contextual lookups apply.
+    pub applies_lookups: Vec<u16>,
+}
+
+/// The substitutions a GSUB feature makes.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct FeatureSubstitutions {
+    pub feature: String,
+    /// Whether the font's GSUB table has the feature.
+    pub present: bool,
+    /// The lookups of the feature in any script and language system, followed by the lookups
+    /// only applied through contextual lookups, each in lookup order.
+    pub lookups: Vec<LookupSubstitutions>,
+}
+
+/// Describes glyphs, using the character map to find the characters glyphs represent.
+struct GlyphDescriber<'a> {
+    names: GlyphNames<'a>,
+    characters: HashMap<GlyphId, char>,
+}
+
+impl<'a> GlyphDescriber<'a> {
+    fn new(font: FontRef<'a>) -> Self {
+        let charmap = font.charmap();
+        let mut characters = HashMap::new();
+        // codepoints are sorted, so the lowest codepoint of a glyph is kept
+        for codepoint in coverage::mapped_codepoints(font) {
+            if let Some(c) = char::from_u32(codepoint) {
+                characters.entry(charmap.map(c)).or_insert(c);
+            }
+        }
+
+        GlyphDescriber {
+            names: GlyphNames::new(font),
+            characters,
+        }
+    }
+
+    fn glyph(&self, glyph_id: ReadGlyphId) -> SubstitutionGlyph {
+        let id = glyph_id.to_u16();
+        SubstitutionGlyph {
+            id,
+            name: self.names.get(id),
+            character: self.characters.get(&id).copied(),
+        }
+    }
+}
+
+fn lookup_indices(records: &[SequenceLookupRecord]) -> impl Iterator<Item = u16> + '_ {
+    records.iter().map(|record| record.lookup_list_index())
+}
+
+/// Get the number of rules of a contextual lookup subtable and the lookups they apply.
+fn sequence_context_rules(context: &SequenceContext<'_>) -> (usize, Vec<u16>) {
+    let mut rules = 0;
+    let mut lookups = Vec::new();
+    match context {
+        SequenceContext::Format1(context) => {
+            for rule in context
+                .seq_rule_sets()
+                .iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .flat_map(|set| set.seq_rules().iter().filter_map(Result::ok))
+            {
+                rules += 1;
+                lookups.extend(lookup_indices(rule.seq_lookup_records()));
+            }
+        }
+        SequenceContext::Format2(context) => {
+            for rule in context
+                .class_seq_rule_sets()
+                .iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .flat_map(|set| set.class_seq_rules().iter().filter_map(Result::ok))
+            {
+                rules += 1;
+                lookups.extend(lookup_indices(rule.seq_lookup_records()));
+            }
+        }
+        SequenceContext::Format3(context) => {
+            rules += 1;
+            lookups.extend(lookup_indices(context.seq_lookup_records()));
+        }
+    }
+    (rules, lookups)
+}
+
+/// Get the number of rules of a chained contextual lookup subtable and the lookups they apply.
+fn chained_context_rules(context: &ChainedSequenceContext<'_>) -> (usize, Vec<u16>) {
+    let mut rules = 0;
+    let mut lookups = Vec::new();
+    match context {
+        ChainedSequenceContext::Format1(context) => {
+            for rule in context
+                .chained_seq_rule_sets()
+                .iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .flat_map(|set| set.chained_seq_rules().iter().filter_map(Result::ok))
+            {
+                rules += 1;
+                lookups.extend(lookup_indices(rule.seq_lookup_records()));
+            }
+        }
+        ChainedSequenceContext::Format2(context) => {
+            for rule in context
+                .chained_class_seq_rule_sets()
+                .iter()
+                .flatten()
+                .filter_map(Result::ok)
+                .flat_map(|set| set.chained_class_seq_rules().iter().filter_map(Result::ok))
+            {
+                rules += 1;
+                lookups.extend(lookup_indices(rule.seq_lookup_records()));
+            }
+        }
+        ChainedSequenceContext::Format3(context) => {
+            rules += 1;
+            lookups.extend(lookup_indices(context.seq_lookup_records()));
+        }
+    }
+    (rules, lookups)
+}
+
+/// Explain the lookup with the given index. Returns `None` if the lookup fails to parse.
+fn explain_lookup(
+    gsub: &Gsub<'_>,
+    describer: &GlyphDescriber<'_>,
+    index: u16,
+    in_feature: bool,
+) -> Option<LookupSubstitutions> {
+    let lookup = gsub
+        .lookup_list()
+        .ok()?
+        .lookups()
+        .get(usize::from(index))
+        .ok()?;
+
+    let mut substitutions = Vec::new();
+    let mut rules = 0;
+    let mut applies_lookups = Vec::new();
+    let mut substitute = |input: Vec<ReadGlyphId>, output: Vec<ReadGlyphId>| {
+        substitutions.push(Substitution {
+            input: input
+                .into_iter()
+                .map(|glyph| describer.glyph(glyph))
+                .collect(),
+            output: output
+                .into_iter()
+                .map(|glyph| describer.glyph(glyph))
+                .collect(),
+        });
+    };
+
+    let kind = match lookup.subtables().ok()? {
+        SubstitutionSubtables::Single(subtables) => {
+            for subtable in subtables.iter().filter_map(Result::ok) {
+                match subtable {
+                    SingleSubst::Format1(subtable) => {
+                        let Ok(coverage) = subtable.coverage() else {
+                            continue;
+                        };
+                        for glyph in coverage.iter() {
+                            // the delta is added modulo 65536
+                            let output = glyph
+                                .to_u16()
+                                .wrapping_add_signed(subtable.delta_glyph_id());
+                            substitute(vec![glyph], vec![ReadGlyphId::new(output)]);
+                        }
+                    }
+                    SingleSubst::Format2(subtable) => {
+                        let Ok(coverage) = subtable.coverage() else {
+                            continue;
+                        };
+                        for (glyph, output) in coverage.iter().zip(subtable.substitute_glyph_ids())
+                        {
+                            substitute(vec![glyph], vec![output.get()]);
+                        }
+                    }
+                }
+            }
+            "single"
+        }
+        SubstitutionSubtables::Multiple(subtables) => {
+            for subtable in subtables.iter().filter_map(Result::ok) {
+                let Ok(coverage) = subtable.coverage() else {
+                    continue;
+                };
+                for (glyph, sequence) in coverage.iter().zip(subtable.sequences().iter()) {
+                    let Ok(sequence) = sequence else { continue };
+                    let output = sequence
+                        .substitute_glyph_ids()
+                        .iter()
+                        .map(|glyph| glyph.get());
+                    substitute(vec![glyph], output.collect());
+                }
+            }
+            "multiple"
+        }
+        SubstitutionSubtables::Alternate(subtables) => {
+            for subtable in subtables.iter().filter_map(Result::ok) {
+                let Ok(coverage) = subtable.coverage() else {
+                    continue;
+                };
+                for (glyph, set) in coverage.iter().zip(subtable.alternate_sets().iter()) {
+                    let Ok(set) = set else { continue };
+                    let output = set.alternate_glyph_ids().iter().map(|glyph| glyph.get());
+                    substitute(vec![glyph], output.collect());
+                }
+            }
+            "alternate"
+        }
+        SubstitutionSubtables::Ligature(subtables) => {
+            for subtable in subtables.iter().filter_map(Result::ok) {
+                let Ok(coverage) = subtable.coverage() else {
+                    continue;
+                };
+                for (glyph, set) in coverage.iter().zip(subtable.ligature_sets().iter()) {
+                    let Ok(set) = set else { continue };
+                    for ligature in set.ligatures().iter().filter_map(Result::ok) {
+                        let input = std::iter::once(glyph).chain(
+                            ligature
+                                .component_glyph_ids()
+                                .iter()
+                                .map(|glyph| glyph.get()),
+                        );
+                        substitute(input.collect(), vec![ligature.ligature_glyph()]);
+                    }
+                }
+            }
+            "ligature"
+        }
+        SubstitutionSubtables::Contextual(subtables) => {
+            for subtable in subtables.iter().filter_map(Result::ok) {
+                let (subtable_rules, lookups) = sequence_context_rules(&subtable);
+                rules += subtable_rules;
+                applies_lookups.extend(lookups);
+            }
+            "contextual"
+        }
+        SubstitutionSubtables::ChainContextual(subtables) => {
+            for subtable in subtables.iter().filter_map(Result::ok) {
+                let (subtable_rules, lookups) = chained_context_rules(&subtable);
+                rules += subtable_rules;
+                applies_lookups.extend(lookups);
+            }
+            "chained contextual"
+        }
+        SubstitutionSubtables::Reverse(subtables) => {
+            for subtable in subtables.iter().filter_map(Result::ok) {
+                let Ok(coverage) = subtable.coverage() else {
+                    continue;
+                };
+                for (glyph, output) in coverage.iter().zip(subtable.substitute_glyph_ids()) {
+                    substitute(vec![glyph], vec![output.get()]);
+                }
+            }
+            "reverse chaining single"
+        }
+    };
+
+    applies_lookups.sort_unstable();
+    applies_lookups.dedup();
+
+    Some(LookupSubstitutions {
+        index,
+        kind,
+        in_feature,
+        substitutions,
+        rules,
+        applies_lookups,
+    })
+}
+
+impl FeatureSubstitutions {
+    /// Explain the substitutions of each of the GSUB features with the given tags.
+    pub fn from_font(font: FontRef<'_>, features: &[Tag]) -> Vec<Self> {
+        let gsub = read_table::<Gsub>(font);
+        let feature_list = gsub.as_ref().and_then(|gsub| gsub.feature_list().ok());
+        let describer = GlyphDescriber::new(font);
+
+        features
+            .iter()
+            .map(|&tag| {
+                let mut lookups = Vec::new();
+                let mut present = false;
+
+                if let (Some(gsub), Some(feature_list)) = (&gsub, &feature_list) {
+                    let mut feature_lookups: Vec<u16> = Vec::new();
+                    for record in feature_list.feature_records() {
+                        if u32::from_be_bytes(record.feature_tag().to_be_bytes()) != tag {
+                            continue;
+                        }
+                        present = true;
+                        if let Ok(feature) = record.feature(feature_list.offset_data()) {
+                            feature_lookups
+                                .extend(feature.lookup_list_indices().iter().map(|idx| idx.get()));
+                        }
+                    }
+                    feature_lookups.sort_unstable();
+                    feature_lookups.dedup();
+
+                    // follow the lookups applied by contextual lookups
+                    let mut explained: Vec<u16> = Vec::new();
+                    let mut pending: Vec<(u16, bool)> = feature_lookups
+                        .iter()
+                        .rev()
+                        .map(|&idx| (idx, true))
+                        .collect();
+                    while let Some((index, in_feature)) = pending.pop() {
+                        if explained.contains(&index) {
+                            continue;
+                        }
+                        explained.push(index);
+                        if let Some(lookup) = explain_lookup(gsub, &describer, index, in_feature) {
+                            pending.extend(
+                                lookup
+                                    .applies_lookups
+                                    .iter()
+                                    .rev()
+                                    .filter(|idx| !feature_lookups.contains(idx))
+                                    .map(|&idx| (idx, false)),
+                            );
+                            lookups.push(lookup);
+                        }
+                    }
+                    lookups.sort_by_key(|lookup| (!lookup.in_feature, lookup.index));
+                }
+
+                FeatureSubstitutions {
+                    feature: tag_to_string(tag),
+                    present,
+                    lookups,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Write the lookups and substitutions of the explained GSUB features in the human-readable
+/// format. Contextual lookups are summarized by their rules and the lookups they apply.
+pub(crate) fn write_feature_substitutions(
+    mut write: impl std::io::Write,
+    features: &[FeatureSubstitutions],
+) -> std::io::Result<()> {
+    for feature in features {
+        write!(write, "{:>20}: ", format!("Feature {}", feature.feature))?;
+        if !feature.present {
+            writeln!(write, "not present")?;
+            continue;
+        }
+        if feature.lookups.is_empty() {
+            writeln!(write, "no lookups")?;
+            continue;
+        }
+        for (idx, lookup) in feature.lookups.iter().enumerate() {
+            if idx > 0 {
+                write!(write, "                      ")?;
+            }
+            write!(write, "lookup {}: {}", lookup.index, lookup.kind)?;
+            if !lookup.in_feature {
+                write!(write, ", applied by contextual lookups")?;
+            }
+            if lookup.kind.ends_with("contextual") {
+                write!(write, ", {} rules", lookup.rules)?;
+                if !lookup.applies_lookups.is_empty() {
+                    let lookups: Vec<String> = lookup
+                        .applies_lookups
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect();
+                    write!(write, " applying lookups {}", lookups.join(", "))?;
+                }
+                writeln!(write)?;
+                continue;
+            }
+            writeln!(write, ", {} substitutions", lookup.substitutions.len())?;
+            let separator = if lookup.kind == "alternate" {
+                " | "
+            } else {
+                " "
+            };
+            for substitution in &lookup.substitutions {
+                let glyphs = |glyphs: &[SubstitutionGlyph], separator| {
+                    glyphs
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join(separator)
+                };
+                writeln!(
+                    write,
+                    "                        {} -> {}",
+                    glyphs(&substitution.input, " "),
+                    glyphs(&substitution.output, separator)
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_substitution_glyph_display() {
+        let glyph = |name: Option<&str>, character| SubstitutionGlyph {
+            id: 42,
+            name: name.map(ToOwned::to_owned),
+            character,
+        };
+        assert_eq!(glyph(Some("a.alt"), None).to_string(), "a.alt");
+        assert_eq!(glyph(Some("a"), Some('a')).to_string(), "a");
+        assert_eq!(glyph(Some("uniFB01"), Some('ﬁ')).to_string(), "uniFB01 (ﬁ)");
+        assert_eq!(glyph(None, Some(' ')).to_string(), "gid42");
+    }
+}
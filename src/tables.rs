@@ -0,0 +1,335 @@
+use read_fonts::{FontData, FontRead, TopLevelTable};
+use swash::FontRef;
+
+use crate::settings::tag_to_string;
+
+const HEAD: swash::Tag = swash::tag_from_bytes(b"head");
+const TTCF: u32 = u32::from_be_bytes(*b"ttcf");
+
+/// The value the checksum of a font with a correct `head.checkSumAdjustment` sums to.
+const CHECKSUM_MAGIC: u32 = 0xB1B0AFBA;
+
+/// Parse a table of the font using read-fonts, for information swash does not expose. Returns
+/// `None` if the font does not have the table or if it fails to parse.
+pub(crate) fn read_table<'a, T: FontRead<'a> + TopLevelTable>(font: FontRef<'a>) -> Option<T> {
+    let data = font.table(u32::from_be_bytes(T::TAG.to_be_bytes()))?;
+    T::read(FontData::new(data)).ok()
+}
+
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset.checked_add(2)?)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+}
+
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset.checked_add(4)?)
+        .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// Compute the OpenType checksum of the data: the wrapping sum of its big-endian 32-bit words,
+/// with the data padded with zeros to a multiple of four bytes.
+pub(crate) fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+/// A raw record of a table directory.
+#[derive(Debug, Clone, Copy)]
+struct RawRecord {
+    tag: u32,
+    checksum: u32,
+    offset: u32,
+    length: u32,
+}
+
+/// Read the table records of the table directory at the given offset.
+fn raw_records(data: &[u8], directory: usize) -> Vec<RawRecord> {
+    let num_tables = read_u16(data, directory + 4).unwrap_or(0);
+    (0..usize::from(num_tables))
+        .map_while(|idx| {
+            let record = directory + 12 + idx * 16;
+            Some(RawRecord {
+                tag: read_u32(data, record)?,
+                checksum: read_u32(data, record + 4)?,
+                offset: read_u32(data, record + 8)?,
+                length: read_u32(data, record + 12)?,
+            })
+        })
+        .collect()
+}
+
+/// Get the offsets of the table directories of the fonts in a file. A file that is not a font
+/// collection has a single table directory at the start of the file.
+fn directory_offsets(data: &[u8]) -> Vec<usize> {
+    if read_u32(data, 0) != Some(TTCF) {
+        return vec![0];
+    }
+
+    let num_fonts = read_u32(data, 8).unwrap_or(0);
+    (0..num_fonts as usize)
+        .map_while(|idx| read_u32(data, 12 + idx * 4).map(|offset| offset as usize))
+        .collect()
+}
+
+/// A record of a font's table directory.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TableRecord {
+    pub tag: String,
+    pub offset: u32,
+    pub length: u32,
+    pub stored_checksum: u32,
+    /// Not present if the table extends beyond the end of the file.
+    pub computed_checksum: Option<u32>,
+    pub checksum_mismatch: bool,
+    /// The size of the table as a percentage of the size of the file.
+    pub file_share: f32,
+    /// The indices of the other fonts in a font collection that share this table.
+    pub shared_with: Vec<usize>,
+}
+
+/// The `head.checkSumAdjustment` value of a font.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ChecksumAdjustment {
+    pub stored: u32,
+    /// Not present for fonts in a collection, as the adjustment depends on the layout of the
+    /// file the font was originally built as.
+    pub computed: Option<u32>,
+    pub mismatch: bool,
+}
+
+/// The table directory of a font.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct TableDirectory {
+    pub file_size: usize,
+    pub tables: Vec<TableRecord>,
+    /// Not present if the font has no `head` table.
+    pub checksum_adjustment: Option<ChecksumAdjustment>,
+}
+
+impl TableDirectory {
+    pub fn from_font(font: FontRef<'_>) -> Self {
+        let data = font.data;
+        let directory = font.offset as usize;
+        let records = raw_records(data, directory);
+
+        // the tables of the other fonts in the collection, if any
+        let is_collection = read_u32(data, 0) == Some(TTCF);
+        let others: Vec<(usize, Vec<RawRecord>)> = directory_offsets(data)
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, offset)| offset != directory)
+            .map(|(idx, offset)| (idx, raw_records(data, offset)))
+            .collect();
+
+        let mut face_checksum = checksum(
+            data.get(directory..directory + 12 + records.len() * 16)
+                .unwrap_or(&[]),
+        );
+        let mut stored_adjustment = None;
+
+        let tables = records
+            .iter()
+            .map(|record| {
+                let start = record.offset as usize;
+                let table = data.get(start..start.saturating_add(record.length as usize));
+                let computed_checksum = table.map(|table| {
+                    if record.tag == HEAD {
+                        // the checksum of `head` is computed with checkSumAdjustment set to 0
+                        stored_adjustment = read_u32(table, 8);
+                        let adjustment = stored_adjustment.unwrap_or(0);
+                        checksum(table).wrapping_sub(adjustment)
+                    } else {
+                        checksum(table)
+                    }
+                });
+                face_checksum = face_checksum.wrapping_add(computed_checksum.unwrap_or(0));
+
+                TableRecord {
+                    tag: tag_to_string(record.tag),
+                    offset: record.offset,
+                    length: record.length,
+                    stored_checksum: record.checksum,
+                    computed_checksum,
+                    checksum_mismatch: computed_checksum != Some(record.checksum),
+                    file_share: if data.is_empty() {
+                        0.
+                    } else {
+                        record.length as f32 / data.len() as f32 * 100.
+                    },
+                    shared_with: others
+                        .iter()
+                        .filter(|(_, other)| {
+                            other.iter().any(|other| {
+                                other.offset == record.offset && other.length == record.length
+                            })
+                        })
+                        .map(|&(idx, _)| idx)
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let checksum_adjustment = stored_adjustment.map(|stored| {
+            let computed = (!is_collection).then(|| CHECKSUM_MAGIC.wrapping_sub(face_checksum));
+            ChecksumAdjustment {
+                stored,
+                computed,
+                mismatch: computed.is_some_and(|computed| computed != stored),
+            }
+        });
+
+        TableDirectory {
+            file_size: data.len(),
+            tables,
+            checksum_adjustment,
+        }
+    }
+}
+
+/// Write the table directory of a font in the human-readable format, marking checksum mismatches.
+pub(crate) fn write_table_directory(
+    mut write: impl std::io::Write,
+    directory: &TableDirectory,
+) -> std::io::Result<()> {
+    write!(write, "              Tables: ")?;
+    for (idx, table) in directory.tables.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        write!(
+            write,
+            "{}: offset {}, length {} ({:.1}% of file), checksum {:#010X}",
+            table.tag, table.offset, table.length, table.file_share, table.stored_checksum
+        )?;
+        match table.computed_checksum {
+            Some(computed) if table.checksum_mismatch => {
+                write!(write, " (MISMATCH, computed {computed:#010X})")?
+            }
+            Some(_) => {}
+            None => write!(write, " (table extends beyond end of file)")?,
+        }
+        if !table.shared_with.is_empty() {
+            write!(write, ", shared with font index")?;
+            for (idx, font_index) in table.shared_with.iter().enumerate() {
+                write!(write, "{} {font_index}", if idx > 0 { "," } else { "" })?;
+            }
+        }
+    }
+    writeln!(write)?;
+
+    if let Some(adjustment) = &directory.checksum_adjustment {
+        write!(write, " Checksum adjustment: {:#010X}", adjustment.stored)?;
+        match adjustment.computed {
+            Some(computed) if adjustment.mismatch => {
+                write!(write, " (MISMATCH, computed {computed:#010X})")?
+            }
+            Some(_) => {}
+            None => write!(write, " (not verified for fonts in a collection)")?,
+        }
+        writeln!(write)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fonts::{font_collection, TestFont};
+
+    /// Build a font with glyphs for 'a' and 'b' with the table checksums and the `head`
+    /// checksum adjustment set.
+    fn font_with_checksums() -> Vec<u8> {
+        let mut data = TestFont::new(&[('a', 500), ('b', 500)]).build();
+        let records = raw_records(&data, 0);
+        for (idx, record) in records.iter().enumerate() {
+            let table = &data[record.offset as usize..][..record.length as usize];
+            let table_checksum = checksum(table);
+            data[12 + idx * 16 + 4..][..4].copy_from_slice(&table_checksum.to_be_bytes());
+        }
+        let head = records.iter().find(|record| record.tag == HEAD).unwrap();
+        let adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&data));
+        data[head.offset as usize + 8..][..4].copy_from_slice(&adjustment.to_be_bytes());
+        data
+    }
+
+    fn table_directory(data: &[u8], index: usize) -> TableDirectory {
+        TableDirectory::from_font(FontRef::from_index(data, index).unwrap())
+    }
+
+    #[test]
+    fn test_checksum() {
+        assert_eq!(checksum(&[]), 0);
+        assert_eq!(checksum(&[0, 0, 1, 0, 0, 0, 0, 2]), 0x102);
+        // data is padded with zeros to a multiple of four bytes
+        assert_eq!(checksum(&[0, 0, 0, 1, 0xFF]), 0xFF000001);
+        assert_eq!(checksum(&[0xFF, 0xFF, 0xFF, 0xFF, 0, 0, 0, 2]), 1);
+    }
+
+    #[test]
+    fn test_table_directory() {
+        let data = font_with_checksums();
+        let directory = table_directory(&data, 0);
+        assert_eq!(directory.file_size, data.len());
+        let tags: Vec<&str> = directory
+            .tables
+            .iter()
+            .map(|table| table.tag.as_str())
+            .collect();
+        assert_eq!(tags, ["cmap", "head", "hhea", "hmtx", "maxp"]);
+        let head = &directory.tables[1];
+        assert_eq!((head.offset, head.length), (144, 54));
+        assert_eq!(head.file_share, 54. / data.len() as f32 * 100.);
+        assert!(directory
+            .tables
+            .iter()
+            .all(|table| !table.checksum_mismatch && table.shared_with.is_empty()));
+        let adjustment = directory.checksum_adjustment.unwrap();
+        assert_eq!(adjustment.computed, Some(adjustment.stored));
+        assert!(!adjustment.mismatch);
+
+        // changing the glyph advances changes the checksums of `hmtx` and the whole font
+        let mut data = data;
+        let hmtx = directory.tables[3].offset as usize;
+        data[hmtx + 4] = 0xFF;
+        let directory = table_directory(&data, 0);
+        let mismatches: Vec<&str> = directory
+            .tables
+            .iter()
+            .filter(|table| table.checksum_mismatch)
+            .map(|table| table.tag.as_str())
+            .collect();
+        assert_eq!(mismatches, ["hmtx"]);
+        assert!(directory.checksum_adjustment.unwrap().mismatch);
+    }
+
+    #[test]
+    fn test_shared_tables() {
+        // the second font uses the tables of the first font, except for `hmtx`
+        let font = font_with_checksums();
+        let mut data = font_collection(&[font.clone(), font]);
+        let [first, second] = directory_offsets(&data)[..] else {
+            panic!("expected two fonts");
+        };
+        data.copy_within(first + 12..first + 12 + 5 * 16, second + 12);
+        data[second + 12 + 3 * 16 + 8..][..4].copy_from_slice(&0u32.to_be_bytes());
+
+        let shared: Vec<Vec<usize>> = table_directory(&data, 0)
+            .tables
+            .into_iter()
+            .map(|table| table.shared_with)
+            .collect();
+        assert_eq!(shared, [vec![1], vec![1], vec![1], vec![], vec![1]]);
+
+        // the adjustment cannot be verified for fonts in a collection
+        let adjustment = table_directory(&data, 1).checksum_adjustment.unwrap();
+        assert_eq!(adjustment.computed, None);
+        assert!(!adjustment.mismatch);
+    }
+}
@@ -0,0 +1,505 @@
+use read_fonts::{
+    tables::{
+        cff::Cff,
+        cff2::Cff2,
+        gasp::{Gasp, GaspRangeBehavior},
+        glyf::{Glyf, Glyph},
+        head::Head,
+        loca::Loca,
+        postscript::{
+            dict::{self, Entry},
+            Index,
+        },
+    },
+    types::GlyphId as ReadGlyphId,
+    FontData, FontRead,
+};
+use swash::{tag_from_bytes, FontRef};
+
+use crate::{
+    report::count,
+    tables::{read_table, read_u16, read_u32},
+};
+
+/// The names of the `gasp` range behavior flags.
+const GASP_FLAGS: [(GaspRangeBehavior, &str); 4] = [
+    (GaspRangeBehavior::GASP_GRIDFIT, "gridfit"),
+    (GaspRangeBehavior::GASP_DOGRAY, "grayscale"),
+    (
+        GaspRangeBehavior::GASP_SYMMETRIC_GRIDFIT,
+        "symmetric gridfit",
+    ),
+    (
+        GaspRangeBehavior::GASP_SYMMETRIC_SMOOTHING,
+        "symmetric smoothing",
+    ),
+];
+
+/// Get the web font format of font file data, as these are not supported.
+pub(crate) fn web_font_format(data: &[u8]) -> Option<&'static str> {
+    match data.get(..4)? {
+        b"wOFF" => Some("WOFF"),
+        b"wOF2" => Some("WOFF2"),
+        _ => None,
+    }
+}
+
+/// A range of sizes of the `gasp` table and how glyphs are rendered at those sizes.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct GaspRange {
+    /// The range includes the sizes up to and including this size in pixels per em, starting
+    /// after the previous range.
+    pub max_ppem: u16,
+    /// E.g., "gridfit" or "grayscale".
+    pub flags: Vec<&'static str>,
+}
+
+/// The hints of a CFF or CFF2 private DICT.
+#[derive(Debug, Default, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CffPrivateHints {
+    pub blue_zones: usize,
+    pub other_blue_zones: usize,
+    pub std_hw: Option<f64>,
+    pub std_vw: Option<f64>,
+    pub stem_snap_h: usize,
+    pub stem_snap_v: usize,
+}
+
+impl CffPrivateHints {
+    fn from_dict(dict_data: &[u8]) -> Self {
+        let mut hints = CffPrivateHints::default();
+        for entry in dict::entries(dict_data, None).filter_map(Result::ok) {
+            match entry {
+                Entry::BlueValues(blues) => hints.blue_zones = blues.values().len(),
+                Entry::OtherBlues(blues) => hints.other_blue_zones = blues.values().len(),
+                Entry::StdHw(value) => hints.std_hw = Some(value.to_f64()),
+                Entry::StdVw(value) => hints.std_vw = Some(value.to_f64()),
+                Entry::StemSnapH(snaps) => hints.stem_snap_h = snaps.values().len(),
+                Entry::StemSnapV(snaps) => hints.stem_snap_v = snaps.values().len(),
+                _ => {}
+            }
+        }
+        hints
+    }
+
+    /// Whether the private DICT has any alignment zones or stem widths.
+    pub fn is_hinted(&self) -> bool {
+        self.blue_zones + self.other_blue_zones + self.stem_snap_h + self.stem_snap_v > 0
+            || self.std_hw.is_some()
+            || self.std_vw.is_some()
+    }
+}
+
+/// Get the hints of the private DICTs of a CFF or CFF2 table, given the top DICT and the table
+/// data the DICT offsets are relative to.
+fn cff_private_hints(top_dict: &[u8], table_data: &[u8], is_cff2: bool) -> Vec<CffPrivateHints> {
+    let private_hints = |dict_data: &[u8]| {
+        dict::entries(dict_data, None)
+            .filter_map(Result::ok)
+            .find_map(|entry| match entry {
+                Entry::PrivateDictRange(range) => table_data.get(range),
+                _ => None,
+            })
+            .map(CffPrivateHints::from_dict)
+    };
+
+    let mut hints: Vec<CffPrivateHints> = private_hints(top_dict).into_iter().collect();
+
+    // CID-keyed CFF and CFF2 fonts have a private DICT for each font DICT
+    let fd_array = dict::entries(top_dict, None)
+        .filter_map(Result::ok)
+        .find_map(|entry| match entry {
+            Entry::FdArrayOffset(offset) => table_data.get(offset..),
+            _ => None,
+        });
+    if let Some(fd_array) = fd_array.and_then(|data| Index::new(data, is_cff2).ok()) {
+        for idx in 0..fd_array.count() as usize {
+            if let Some(font_dict) = fd_array.get(idx).ok().and_then(private_hints) {
+                hints.push(font_dict);
+            }
+        }
+    }
+
+    hints
+}
+
+/// The TrueType and CFF hinting data and the device-specific tables of a font.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Hinting {
+    /// The size in bytes of the `fpgm` table, if present.
+    pub fpgm_size: Option<usize>,
+    /// The size in bytes of the `prep` table, if present.
+    pub prep_size: Option<usize>,
+    /// The number of control values of the `cvt ` table, if present.
+    pub cvt_values: Option<usize>,
+    /// The number of glyphs with TrueType instructions. Not present for fonts without a `glyf`
+    /// table.
+    pub instructed_glyphs: Option<usize>,
+    /// Not present if the font has no `gasp` table.
+    pub gasp: Option<Vec<GaspRange>>,
+    /// The hints of each CFF or CFF2 private DICT.
+    pub cff_private_dicts: Vec<CffPrivateHints>,
+    /// The sizes in pixels per em of the `hdmx` device records, if present.
+    pub hdmx_sizes: Option<Vec<u8>>,
+    /// The number of glyphs of the `LTSH` table, if present.
+    pub ltsh_glyphs: Option<u16>,
+    /// The number of ratio groups of the `VDMX` table, if present.
+    pub vdmx_groups: Option<u16>,
+}
+
+impl Hinting {
+    fn from_font(font: FontRef<'_>) -> Self {
+        let table = |tag: &[u8; 4]| font.table(tag_from_bytes(tag));
+
+        // read-fonts does not register `gasp` as a top-level table
+        let gasp = table(b"gasp")
+            .and_then(|data| Gasp::read(FontData::new(data)).ok())
+            .map(|gasp| {
+                gasp.gasp_ranges()
+                    .iter()
+                    .map(|range| GaspRange {
+                        max_ppem: range.range_max_ppem(),
+                        flags: GASP_FLAGS
+                            .iter()
+                            .filter(|(flag, _)| range.range_gasp_behavior().contains(*flag))
+                            .map(|&(_, name)| name)
+                            .collect(),
+                    })
+                    .collect()
+            });
+
+        let mut cff_private_dicts = Vec::new();
+        if let Some(cff) = read_table::<Cff>(font) {
+            if let Ok(top_dict) = cff.top_dicts().get(0) {
+                cff_private_dicts =
+                    cff_private_hints(top_dict, cff.offset_data().as_bytes(), false);
+            }
+        }
+        if let Some(cff2) = read_table::<Cff2>(font) {
+            cff_private_dicts.extend(cff_private_hints(
+                cff2.top_dict_data(),
+                cff2.offset_data().as_bytes(),
+                true,
+            ));
+        }
+
+        let hdmx_sizes = table(b"hdmx").map(|hdmx| {
+            let record_count = read_u16(hdmx, 2).unwrap_or(0);
+            let record_size = read_u32(hdmx, 4).unwrap_or(0) as usize;
+            (0..usize::from(record_count))
+                .map_while(|idx| hdmx.get(8 + idx * record_size).copied())
+                .collect()
+        });
+
+        Hinting {
+            fpgm_size: table(b"fpgm").map(<[u8]>::len),
+            prep_size: table(b"prep").map(<[u8]>::len),
+            cvt_values: table(b"cvt ").map(|cvt| cvt.len() / 2),
+            instructed_glyphs: instructed_glyphs(font),
+            gasp,
+            cff_private_dicts,
+            hdmx_sizes,
+            ltsh_glyphs: table(b"LTSH").and_then(|ltsh| read_u16(ltsh, 2)),
+            vdmx_groups: table(b"VDMX").and_then(|vdmx| read_u16(vdmx, 2)),
+        }
+    }
+
+    /// Whether the font has TrueType instructions or CFF hints.
+    pub fn is_hinted(&self) -> bool {
+        self.fpgm_size.is_some_and(|size| size > 0)
+            || self.prep_size.is_some_and(|size| size > 0)
+            || self.instructed_glyphs.is_some_and(|count| count > 0)
+            || self
+                .cff_private_dicts
+                .iter()
+                .any(CffPrivateHints::is_hinted)
+    }
+}
+
+/// Count the glyphs of the `glyf` table that have TrueType instructions.
+fn instructed_glyphs(font: FontRef<'_>) -> Option<usize> {
+    let glyf = read_table::<Glyf>(font)?;
+    let is_long = read_table::<Head>(font)?.index_to_loc_format() == 1;
+    let loca = Loca::read(FontData::new(font.table(tag_from_bytes(b"loca"))?), is_long).ok()?;
+
+    let count = (0..loca.len())
+        .filter_map(|glyph_id| {
+            let glyph_id = ReadGlyphId::new(u16::try_from(glyph_id).ok()?);
+            loca.get_glyf(glyph_id, &glyf).ok().flatten()
+        })
+        .filter(|glyph| match glyph {
+            Glyph::Simple(glyph) => !glyph.instructions().is_empty(),
+            Glyph::Composite(glyph) => glyph
+                .instructions()
+                .is_some_and(|instructions| !instructions.is_empty()),
+        })
+        .count();
+    Some(count)
+}
+
+/// The outline format, container format and hinting of a font.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Technical {
+    /// "TrueType (glyf)", "CFF" or "CFF2". Not present for fonts without outlines, such as
+    /// bitmap-only fonts.
+    pub outline_format: Option<&'static str>,
+    /// "TTF", "OTF", "TTC" or "OTC".
+    pub container: &'static str,
+    pub hinted: bool,
+    pub hinting: Hinting,
+}
+
+impl Technical {
+    pub fn from_font(font: FontRef<'_>) -> Self {
+        let has_table = |tag: &[u8; 4]| font.table(tag_from_bytes(tag)).is_some();
+        let outline_format = if has_table(b"glyf") {
+            Some("TrueType (glyf)")
+        } else if has_table(b"CFF2") {
+            Some("CFF2")
+        } else if has_table(b"CFF ") {
+            Some("CFF")
+        } else {
+            None
+        };
+
+        let is_cff = matches!(outline_format, Some("CFF" | "CFF2"));
+        let container = match (font.data.get(..4), is_cff) {
+            (Some(b"ttcf"), false) => "TTC",
+            (Some(b"ttcf"), true) => "OTC",
+            (_, false) => "TTF",
+            (_, true) => "OTF",
+        };
+
+        let hinting = Hinting::from_font(font);
+        Technical {
+            outline_format,
+            container,
+            hinted: hinting.is_hinted(),
+            hinting,
+        }
+    }
+}
+
+/// Write the outline format, container format and hinting details of a font in the
+/// human-readable format.
+pub(crate) fn write_technical(
+    mut write: impl std::io::Write,
+    technical: &Technical,
+) -> std::io::Result<()> {
+    let Technical {
+        outline_format,
+        container,
+        hinted,
+        hinting,
+    } = technical;
+    writeln!(
+        write,
+        "           Technical: {} outlines, {container} container, {}",
+        outline_format.unwrap_or("no"),
+        if *hinted { "hinted" } else { "not hinted" }
+    )?;
+
+    let mut truetype = Vec::new();
+    if let Some(size) = hinting.fpgm_size {
+        truetype.push(format!("fpgm {}", count(size, "byte")));
+    }
+    if let Some(size) = hinting.prep_size {
+        truetype.push(format!("prep {}", count(size, "byte")));
+    }
+    if let Some(values) = hinting.cvt_values {
+        truetype.push(format!("cvt {}", count(values, "value")));
+    }
+    if let Some(glyphs) = hinting.instructed_glyphs {
+        truetype.push(count(glyphs, "instructed glyph"));
+    }
+    if !truetype.is_empty() {
+        writeln!(write, "    TrueType hinting: {}", truetype.join(", "))?;
+    }
+
+    for (idx, hints) in hinting.cff_private_dicts.iter().enumerate() {
+        if idx == 0 {
+            write!(write, "         CFF hinting: ")?;
+        } else {
+            write!(write, "                      ")?;
+        }
+        write!(
+            write,
+            "private DICT {idx}: {} blue zones, {} other blue zones",
+            hints.blue_zones, hints.other_blue_zones
+        )?;
+        if let Some(std_hw) = hints.std_hw {
+            write!(write, ", StdHW {std_hw}")?;
+        }
+        if let Some(std_vw) = hints.std_vw {
+            write!(write, ", StdVW {std_vw}")?;
+        }
+        writeln!(
+            write,
+            ", {} StemSnapH, {} StemSnapV",
+            hints.stem_snap_h, hints.stem_snap_v
+        )?;
+    }
+
+    write!(write, "                gasp: ")?;
+    match &hinting.gasp {
+        Some(ranges) => {
+            for (idx, range) in ranges.iter().enumerate() {
+                if idx > 0 {
+                    write!(write, "\n                      ")?;
+                }
+                let flags = if range.flags.is_empty() {
+                    "no flags".to_owned()
+                } else {
+                    range.flags.join(", ")
+                };
+                write!(write, "up to {} ppem: {flags}", range.max_ppem)?;
+            }
+            writeln!(write)?;
+        }
+        None => writeln!(write, "no gasp table")?,
+    }
+
+    let mut device = Vec::new();
+    if let Some(sizes) = &hinting.hdmx_sizes {
+        let sizes: Vec<String> = sizes.iter().map(ToString::to_string).collect();
+        device.push(format!("hdmx sizes {}", sizes.join(" ")));
+    }
+    if let Some(glyphs) = hinting.ltsh_glyphs {
+        device.push(format!("LTSH {}", count(usize::from(glyphs), "glyph")));
+    }
+    if let Some(groups) = hinting.vdmx_groups {
+        device.push(format!("VDMX {}", count(usize::from(groups), "group")));
+    }
+    if !device.is_empty() {
+        writeln!(write, "      Device metrics: {}", device.join(", "))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fonts::{font_collection, Data, TestFont};
+
+    fn technical(data: &[u8]) -> Technical {
+        Technical::from_font(FontRef::from_index(data, 0).unwrap())
+    }
+
+    #[test]
+    fn test_web_font_format() {
+        assert_eq!(web_font_format(b"wOFF\0\x01\0\0"), Some("WOFF"));
+        assert_eq!(web_font_format(b"wOF2OTTO"), Some("WOFF2"));
+        assert_eq!(web_font_format(b"OTTO"), None);
+        assert_eq!(web_font_format(b"wO"), None);
+    }
+
+    #[test]
+    fn test_container() {
+        let font = TestFont::new(&[(' ', 250)]);
+        let truetype = font.table(b"glyf", vec![]).table(b"loca", vec![0; 4]);
+        let cff = TestFont::new(&[(' ', 250)]).table(b"CFF ", vec![1, 0, 4, 1]);
+
+        let technical_ttf = technical(&truetype.build());
+        assert_eq!(technical_ttf.outline_format, Some("TrueType (glyf)"));
+        assert_eq!(technical_ttf.container, "TTF");
+        let technical_otf = technical(&cff.build());
+        assert_eq!(technical_otf.outline_format, Some("CFF"));
+        assert_eq!(technical_otf.container, "OTF");
+
+        assert_eq!(
+            technical(&font_collection(&[truetype.build()])).container,
+            "TTC"
+        );
+        assert_eq!(technical(&font_collection(&[cff.build()])).container, "OTC");
+    }
+
+    #[test]
+    fn test_gasp() {
+        let gasp = Data::default()
+            .u16(1)
+            .u16(3)
+            .u16(8)
+            .u16(0x0002)
+            .u16(16)
+            .u16(0x0005)
+            .u16(0xFFFF)
+            .u16(0x000F);
+        let font = TestFont::new(&[(' ', 250)]).table(b"gasp", gasp.0);
+
+        let ranges: Vec<(u16, Vec<&str>)> = technical(&font.build())
+            .hinting
+            .gasp
+            .unwrap()
+            .into_iter()
+            .map(|range| (range.max_ppem, range.flags))
+            .collect();
+        assert_eq!(
+            ranges,
+            [
+                (8, vec!["grayscale"]),
+                (16, vec!["gridfit", "symmetric gridfit"]),
+                (
+                    0xFFFF,
+                    vec![
+                        "gridfit",
+                        "grayscale",
+                        "symmetric gridfit",
+                        "symmetric smoothing"
+                    ]
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cff_private_hints() {
+        // integers from -107 to 107 are encoded as a single byte of the integer plus 139
+        let private_dict = [
+            // BlueValues -15 0 100 15, delta encoded
+            &[124, 139, 239, 154, 6][..],
+            // OtherBlues -20 10
+            &[119, 149, 7],
+            // StdHW 50 and StdVW 60
+            &[189, 10, 199, 11],
+            // StemSnapH 50 10
+            &[189, 149, 12, 12],
+        ]
+        .concat();
+        let hints = CffPrivateHints::from_dict(&private_dict);
+        assert_eq!((hints.blue_zones, hints.other_blue_zones), (2, 1));
+        assert_eq!((hints.std_hw, hints.std_vw), (Some(50.), Some(60.)));
+        assert_eq!((hints.stem_snap_h, hints.stem_snap_v), (2, 0));
+        assert!(hints.is_hinted());
+
+        // the top DICT gives the size and offset of the private DICT
+        let mut table_data = vec![0; 10];
+        table_data.extend(&private_dict);
+        let top_dict = [private_dict.len() as u8 + 139, 10 + 139, 18];
+        let hints = cff_private_hints(&top_dict, &table_data, false);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].std_vw, Some(60.));
+
+        assert!(!CffPrivateHints::from_dict(&[]).is_hinted());
+    }
+
+    #[test]
+    fn test_hdmx_sizes() {
+        // device records of 2 glyphs, padded to 4 bytes, of which the third is missing
+        let hdmx = Data::default()
+            .u16(0)
+            .u16(3)
+            .u32(4)
+            .bytes(&[12, 10, 6, 6])
+            .bytes(&[16, 14, 8, 8]);
+        let font = TestFont::new(&[(' ', 250)]).table(b"hdmx", hdmx.0);
+        assert_eq!(
+            technical(&font.build()).hinting.hdmx_sizes,
+            Some(vec![12, 16])
+        );
+    }
+}
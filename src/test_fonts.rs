@@ -0,0 +1,200 @@
+//! Builders of font data for tests.
+
+/// Big-endian binary data, built by chaining values.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Data(pub Vec<u8>);
+
+impl Data {
+    pub fn u16(mut self, value: u16) -> Self {
+        self.0.extend(value.to_be_bytes());
+        self
+    }
+
+    pub fn i16(self, value: i16) -> Self {
+        self.u16(value as u16)
+    }
+
+    pub fn u32(mut self, value: u32) -> Self {
+        self.0.extend(value.to_be_bytes());
+        self
+    }
+
+    /// A 16.16 fixed-point number.
+    pub fn fixed(self, value: f32) -> Self {
+        self.u32((value * 65536.).round() as i32 as u32)
+    }
+
+    pub fn tag(self, tag: &[u8; 4]) -> Self {
+        self.bytes(tag)
+    }
+
+    pub fn bytes(mut self, bytes: &[u8]) -> Self {
+        self.0.extend(bytes);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Build a font file with a table directory of the given tables, which are placed after the
+/// directory in order.
+pub(crate) fn font_file(tables: &[(&[u8; 4], &[u8])]) -> Vec<u8> {
+    let num_tables = tables.len() as u16;
+    let entry_selector = num_tables.ilog2() as u16;
+    let search_range = 16 << entry_selector;
+    let mut data = Vec::new();
+    data.extend(0x0001_0000u32.to_be_bytes());
+    for value in [
+        num_tables,
+        search_range,
+        entry_selector,
+        num_tables * 16 - search_range,
+    ] {
+        data.extend(value.to_be_bytes());
+    }
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, table) in tables {
+        data.extend(*tag);
+        data.extend(0u32.to_be_bytes());
+        data.extend((offset as u32).to_be_bytes());
+        data.extend((table.len() as u32).to_be_bytes());
+        offset += table.len().next_multiple_of(4);
+    }
+    for (_, table) in tables {
+        data.extend(*table);
+        data.resize(data.len().next_multiple_of(4), 0);
+    }
+    data
+}
+
+/// Build a font collection of font files built by `font_file`, moving the table offsets of
+/// each font to its position in the collection.
+pub(crate) fn font_collection(fonts: &[Vec<u8>]) -> Vec<u8> {
+    let mut data = Data::default()
+        .tag(b"ttcf")
+        .u32(0x0001_0000)
+        .u32(fonts.len() as u32)
+        .0;
+    let mut offset = 12 + 4 * fonts.len();
+    for font in fonts {
+        data.extend((offset as u32).to_be_bytes());
+        offset += font.len();
+    }
+    for font in fonts {
+        let base = data.len() as u32;
+        let mut font = font.clone();
+        let num_tables = usize::from(u16::from_be_bytes([font[4], font[5]]));
+        for idx in 0..num_tables {
+            let position = 12 + 16 * idx + 8;
+            let table_offset = u32::from_be_bytes(font[position..position + 4].try_into().unwrap());
+            font[position..position + 4].copy_from_slice(&(base + table_offset).to_be_bytes());
+        }
+        data.extend(font);
+    }
+    data
+}
+
+/// Build a `name` table with Windows Unicode records of language ID, name ID and string, which
+/// must be sorted.
+pub(crate) fn name_table(records: &[(u16, u16, &str)]) -> Vec<u8> {
+    let mut strings = Data::default();
+    let mut table = Data::default()
+        .u16(0)
+        .u16(records.len() as u16)
+        .u16(6 + 12 * records.len() as u16);
+    for &(language_id, name_id, string) in records {
+        let encoded: Vec<u8> = string.encode_utf16().flat_map(u16::to_be_bytes).collect();
+        table = table
+            .u16(3)
+            .u16(1)
+            .u16(language_id)
+            .u16(name_id)
+            .u16(encoded.len() as u16)
+            .u16(strings.len() as u16);
+        strings = strings.bytes(&encoded);
+    }
+    table.bytes(&strings.0).0
+}
+
+/// Build a `head` table with the given units per em.
+pub(crate) fn head_table(units_per_em: u16) -> Vec<u8> {
+    Data::default()
+        .u32(0x0001_0000)
+        .bytes(&[0; 8])
+        .u32(0x5F0F_3CF5)
+        .u16(0)
+        .u16(units_per_em)
+        .bytes(&[0; 34])
+        .0
+}
+
+/// Build a version 0.5 `maxp` table with the given number of glyphs.
+pub(crate) fn maxp_table(num_glyphs: u16) -> Vec<u8> {
+    Data::default().u32(0x0000_5000).u16(num_glyphs).0
+}
+
+/// A font built from tables, which are written in tag order.
+pub(crate) struct TestFont {
+    tables: Vec<([u8; 4], Vec<u8>)>,
+}
+
+impl TestFont {
+    /// A font with a `.notdef` glyph with an advance of 500, followed by glyphs of the given
+    /// characters and advances, which must be sorted by character. The glyphs have no outlines.
+    /// The ascender is 800 and the descender -200, at 1000 units per em.
+    pub fn new(glyphs: &[(char, u16)]) -> Self {
+        let num_glyphs = glyphs.len() as u16 + 1;
+        let hhea = Data::default()
+            .u32(0x0001_0000)
+            .i16(800)
+            .i16(-200)
+            .u16(0)
+            .bytes(&[0; 24])
+            .u16(num_glyphs);
+        let mut cmap = Data::default()
+            .u16(0)
+            .u16(1)
+            .u16(3)
+            .u16(10)
+            .u32(12)
+            .u16(12)
+            .u16(0)
+            .u32(16 + 12 * glyphs.len() as u32)
+            .u32(0)
+            .u32(glyphs.len() as u32);
+        let mut hmtx = Data::default().u16(500).u16(0);
+        for (glyph_id, &(c, advance)) in (1u32..).zip(glyphs) {
+            cmap = cmap.u32(u32::from(c)).u32(u32::from(c)).u32(glyph_id);
+            hmtx = hmtx.u16(advance).u16(0);
+        }
+
+        TestFont {
+            tables: vec![
+                (*b"head", head_table(1000)),
+                (*b"hhea", hhea.0),
+                (*b"maxp", maxp_table(num_glyphs)),
+                (*b"cmap", cmap.0),
+                (*b"hmtx", hmtx.0),
+            ],
+        }
+    }
+
+    /// Add a table, or replace the table with the same tag.
+    pub fn table(mut self, tag: &[u8; 4], data: Vec<u8>) -> Self {
+        self.tables.retain(|(other, _)| other != tag);
+        self.tables.push((*tag, data));
+        self
+    }
+
+    pub fn build(&self) -> Vec<u8> {
+        let mut tables: Vec<(&[u8; 4], &[u8])> = self
+            .tables
+            .iter()
+            .map(|(tag, data)| (tag, data.as_slice()))
+            .collect();
+        tables.sort_unstable_by_key(|(tag, _)| *tag);
+        font_file(&tables)
+    }
+}
@@ -0,0 +1,1296 @@
+use std::{fmt, io::Write};
+
+use read_fonts::{
+    tables::cmap::{Cmap, CmapSubtable},
+    FontData, FontRead,
+};
+
+use crate::{
+    report::{count, Severity},
+    settings::tag_to_string,
+    tables::{read_u16, read_u32},
+    technical::web_font_format,
+    Outcome,
+};
+
+const TTCF: u32 = u32::from_be_bytes(*b"ttcf");
+const CMAP: u32 = u32::from_be_bytes(*b"cmap");
+const GLYF: u32 = u32::from_be_bytes(*b"glyf");
+const HEAD: u32 = u32::from_be_bytes(*b"head");
+const HHEA: u32 = u32::from_be_bytes(*b"hhea");
+const HMTX: u32 = u32::from_be_bytes(*b"hmtx");
+const LOCA: u32 = u32::from_be_bytes(*b"loca");
+const MAXP: u32 = u32::from_be_bytes(*b"maxp");
+const NAME: u32 = u32::from_be_bytes(*b"name");
+const OS2: u32 = u32::from_be_bytes(*b"OS/2");
+const POST: u32 = u32::from_be_bytes(*b"post");
+const VHEA: u32 = u32::from_be_bytes(*b"vhea");
+const VMTX: u32 = u32::from_be_bytes(*b"vmtx");
+
+/// The sfnt versions of TrueType outlines, CFF outlines, and Apple's TrueType and Type 1 fonts.
+const SFNT_VERSIONS: [u32; 4] = [
+    0x0001_0000,
+    u32::from_be_bytes(*b"OTTO"),
+    u32::from_be_bytes(*b"true"),
+    u32::from_be_bytes(*b"typ1"),
+];
+
+/// The tables every font must have.
+const REQUIRED_TABLES: [u32; 7] = [CMAP, HEAD, HHEA, HMTX, MAXP, NAME, POST];
+
+const HEAD_MAGIC_NUMBER: u32 = 0x5F0F_3CF5;
+
+/// The tables with glyph outlines or bitmaps.
+const GLYPH_TABLES: [&[u8; 4]; 7] = [
+    b"glyf", b"CFF ", b"CFF2", b"CBDT", b"EBDT", b"sbix", b"SVG ",
+];
+
+/// The maximum number of problems listed for the glyphs of a table, as broken fonts may have
+/// problems with every glyph.
+const MAX_LISTED: usize = 10;
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const WE_HAVE_INSTRUCTIONS: u16 = 0x0100;
+
+const X_SHORT_VECTOR: u8 = 0x02;
+const Y_SHORT_VECTOR: u8 = 0x04;
+const REPEAT_FLAG: u8 = 0x08;
+const X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+
+/// Arguments of the validate subcommand.
+#[derive(clap::Args, Clone, Debug)]
+pub(crate) struct ValidateArgs {
+    #[command(flatten)]
+    pub input: crate::files::PathArgs,
+
+    /// The lowest severity of problems that makes the exit status non-zero.
+    #[arg(long, default_value = "error")]
+    fail_on: Severity,
+}
+
+/// A structural problem of a font file.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Problem {
+    pub severity: Severity,
+    /// The tag of the table with the problem. Not present for problems of the file header or the
+    /// table directory.
+    pub table: Option<String>,
+    pub message: String,
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.table {
+            Some(table) => write!(f, "table '{table}': {}", self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// The problems found so far.
+#[derive(Default)]
+struct Problems(Vec<Problem>);
+
+impl Problems {
+    fn push(&mut self, severity: Severity, table: Option<u32>, message: impl Into<String>) {
+        self.0.push(Problem {
+            severity,
+            table: table.map(tag_to_string),
+            message: message.into(),
+        });
+    }
+
+    fn error(&mut self, table: Option<u32>, message: impl Into<String>) {
+        self.push(Severity::Error, table, message);
+    }
+
+    fn warning(&mut self, table: Option<u32>, message: impl Into<String>) {
+        self.push(Severity::Warning, table, message);
+    }
+
+    /// Add errors for the first of the messages about the glyphs of a table, and an error with
+    /// the number of the remaining messages.
+    fn glyph_errors(&mut self, table: u32, messages: Vec<String>) {
+        let more = messages.len().saturating_sub(MAX_LISTED);
+        for message in messages.into_iter().take(MAX_LISTED) {
+            self.error(Some(table), message);
+        }
+        if more > 0 {
+            self.error(
+                Some(table),
+                format!("and {} more", count(more, "glyph problem")),
+            );
+        }
+    }
+
+    /// Check the table has at least the given length, and add an error if not.
+    fn check_length(&mut self, tag: u32, table: &[u8], length: usize, what: &str) -> bool {
+        if table.len() < length {
+            self.error(
+                Some(tag),
+                format!(
+                    "the table is {}, but {what} needs {}",
+                    count(table.len(), "byte"),
+                    count(length, "byte")
+                ),
+            );
+            return false;
+        }
+        true
+    }
+}
+
+/// The problems of a font of a font file.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ValidatedFont {
+    pub font_index: usize,
+    pub problems: Vec<Problem>,
+}
+
+/// The problems of a font file and its fonts.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct ValidatedFile {
+    pub source: String,
+    /// The problems of the file header, such as a truncated font collection header.
+    pub problems: Vec<Problem>,
+    pub fonts: Vec<ValidatedFont>,
+}
+
+/// Validate the structure of font file data, and of only the font with the given index in font
+/// collections if one is given. This does not assume anything about the data, so it can be used
+/// on files that fail to parse.
+pub(crate) fn validate(
+    data: &[u8],
+    font_index: Option<usize>,
+) -> (Vec<Problem>, Vec<ValidatedFont>) {
+    let mut problems = Problems::default();
+    let directories = directory_offsets(data, &mut problems);
+
+    if let Some(font_index) = font_index {
+        if !directories.is_empty() && font_index >= directories.len() {
+            problems.error(
+                None,
+                format!(
+                    "there is no font with index {font_index}, as the file contains {}",
+                    count(directories.len(), "font")
+                ),
+            );
+        }
+    }
+
+    let fonts = directories
+        .into_iter()
+        .enumerate()
+        .filter(|&(idx, _)| font_index.is_none_or(|font_index| font_index == idx))
+        .map(|(idx, directory)| {
+            let mut font_problems = Problems::default();
+            if let Some(tables) = read_directory(data, directory, &mut font_problems) {
+                check_tables(&tables, &mut font_problems);
+            }
+            ValidatedFont {
+                font_index: idx,
+                problems: font_problems.0,
+            }
+        })
+        .collect();
+
+    (problems.0, fonts)
+}
+
+/// Get the first error of font file data, to explain why it fails to parse.
+pub(crate) fn first_error(data: &[u8]) -> Option<Problem> {
+    let (problems, fonts) = validate(data, None);
+    problems
+        .into_iter()
+        .chain(fonts.into_iter().flat_map(|font| font.problems))
+        .find(|problem| problem.severity == Severity::Error)
+}
+
+/// Check the file header and get the offsets of the table directories of the fonts in the file.
+fn directory_offsets(data: &[u8], problems: &mut Problems) -> Vec<usize> {
+    if let Some(format) = web_font_format(data) {
+        problems.error(None, format!("{format} files are not supported"));
+        return Vec::new();
+    }
+    if data.len() < 12 {
+        problems.error(
+            None,
+            format!(
+                "the file is {}, too short for a font file",
+                count(data.len(), "byte")
+            ),
+        );
+        return Vec::new();
+    }
+    if read_u32(data, 0) != Some(TTCF) {
+        return vec![0];
+    }
+
+    let major_version = read_u16(data, 4).unwrap_or(0);
+    if !(1..=2).contains(&major_version) {
+        problems.warning(
+            None,
+            format!("unknown font collection version {major_version}"),
+        );
+    }
+    let num_fonts = read_u32(data, 8).unwrap_or(0) as usize;
+    if num_fonts == 0 {
+        problems.error(None, "the font collection has no fonts");
+        return Vec::new();
+    }
+    let offsets: Vec<usize> = (0..num_fonts)
+        .map_while(|idx| read_u32(data, 12 + idx * 4).map(|offset| offset as usize))
+        .collect();
+    if offsets.len() < num_fonts {
+        problems.error(
+            None,
+            format!(
+                "the font collection header with {} is truncated",
+                count(num_fonts, "font")
+            ),
+        );
+        return Vec::new();
+    }
+    offsets
+}
+
+/// Check the table directory at the given offset, and get the tags and data of the tables that
+/// are within the bounds of the file. Returns `None` if the directory itself is unusable.
+fn read_directory<'a>(
+    data: &'a [u8],
+    directory: usize,
+    problems: &mut Problems,
+) -> Option<Vec<(u32, &'a [u8])>> {
+    let (Some(sfnt_version), Some(num_tables)) =
+        (read_u32(data, directory), read_u16(data, directory + 4))
+    else {
+        problems.error(
+            None,
+            format!(
+                "the table directory at offset {directory} is past the end of the file ({})",
+                count(data.len(), "byte")
+            ),
+        );
+        return None;
+    };
+    if !SFNT_VERSIONS.contains(&sfnt_version) {
+        problems.error(None, format!("unknown sfnt version 0x{sfnt_version:08X}"));
+        return None;
+    }
+    let num_tables = usize::from(num_tables);
+    if num_tables == 0 {
+        problems.error(None, "the table directory has no tables");
+        return None;
+    }
+    let directory_end = directory + 12 + num_tables * 16;
+    if directory_end > data.len() {
+        problems.error(
+            None,
+            format!(
+                "the table directory with {} ends at offset {directory_end}, past the end of the file ({})",
+                count(num_tables, "table"),
+                count(data.len(), "byte")
+            ),
+        );
+        return None;
+    }
+
+    let entry_selector = num_tables.ilog2() as usize;
+    let search_range = 16 << entry_selector;
+    let range_shift = num_tables * 16 - search_range;
+    let search_parameters = [6, 8, 10]
+        .map(|offset| usize::from(read_u16(data, directory + offset).unwrap_or_default()));
+    if search_parameters != [search_range, entry_selector, range_shift] {
+        let [actual_search_range, actual_entry_selector, actual_range_shift] = search_parameters;
+        problems.warning(
+            None,
+            format!(
+                "the binary search parameters of the table directory are {actual_search_range}, {actual_entry_selector} and {actual_range_shift} instead of {search_range}, {entry_selector} and {range_shift}"
+            ),
+        );
+    }
+
+    let mut records = Vec::new();
+    for idx in 0..num_tables {
+        let record = directory + 12 + idx * 16;
+        let tag = read_u32(data, record)?;
+        let offset = read_u32(data, record + 8)? as usize;
+        let length = read_u32(data, record + 12)? as usize;
+        records.push((tag, offset, length));
+    }
+
+    let mut tags: Vec<u32> = records.iter().map(|&(tag, _, _)| tag).collect();
+    if !tags.is_sorted() {
+        problems.warning(None, "the table records are not sorted by tag");
+    }
+    tags.sort_unstable();
+    let mut duplicates: Vec<u32> = tags
+        .windows(2)
+        .filter(|pair| pair[0] == pair[1])
+        .map(|pair| pair[0])
+        .collect();
+    duplicates.dedup();
+    for tag in duplicates {
+        problems.error(
+            Some(tag),
+            "the table directory has more than one record of the table",
+        );
+    }
+
+    let mut tables: Vec<(u32, usize, &[u8])> = Vec::new();
+    for &(tag, offset, length) in &records {
+        let Some(end) = offset.checked_add(length).filter(|&end| end <= data.len()) else {
+            problems.error(
+                Some(tag),
+                format!(
+                    "the table at offset {offset} with length {length} extends past the end of the file ({})",
+                    count(data.len(), "byte")
+                ),
+            );
+            continue;
+        };
+        if length > 0 && offset < directory_end && end > directory {
+            problems.error(
+                Some(tag),
+                format!(
+                    "the table at offset {offset} overlaps the table directory at offset {directory}"
+                ),
+            );
+            continue;
+        }
+        if offset % 4 != 0 {
+            problems.warning(
+                Some(tag),
+                format!("the table at offset {offset} is not aligned to 4 bytes"),
+            );
+        }
+        if !tables.iter().any(|&(other, _, _)| other == tag) {
+            tables.push((tag, offset, &data[offset..end]));
+        }
+    }
+
+    // tables may share data, but must not partially overlap
+    let mut by_offset: Vec<(u32, usize, usize)> = tables
+        .iter()
+        .filter(|(_, _, table)| !table.is_empty())
+        .map(|&(tag, offset, table)| (tag, offset, offset + table.len()))
+        .collect();
+    by_offset.sort_unstable_by_key(|&(_, offset, end)| (offset, end));
+    let mut furthest: Option<(u32, usize, usize)> = None;
+    for &(tag, offset, end) in &by_offset {
+        if let Some((other, other_offset, other_end)) = furthest {
+            if offset < other_end && (offset, end) != (other_offset, other_end) {
+                problems.error(
+                    Some(tag),
+                    format!(
+                        "the table at offset {offset} overlaps table '{}' at offset {other_offset}",
+                        tag_to_string(other)
+                    ),
+                );
+            }
+        }
+        if furthest.is_none_or(|(_, _, furthest_end)| end > furthest_end) {
+            furthest = Some((tag, offset, end));
+        }
+    }
+
+    Some(
+        tables
+            .into_iter()
+            .map(|(tag, _, table)| (tag, table))
+            .collect(),
+    )
+}
+
+/// Check the tables of a font and the consistency between them.
+fn check_tables(tables: &[(u32, &[u8])], problems: &mut Problems) {
+    let table = |tag: u32| {
+        tables
+            .iter()
+            .find(|&&(other, _)| other == tag)
+            .map(|&(_, table)| table)
+    };
+
+    for tag in REQUIRED_TABLES {
+        if table(tag).is_none() {
+            problems.error(Some(tag), "the font does not have this required table");
+        }
+    }
+    if !GLYPH_TABLES
+        .iter()
+        .any(|tag| table(u32::from_be_bytes(**tag)).is_some())
+    {
+        problems.warning(None, "the font has no outlines or bitmaps");
+    }
+
+    let index_to_loc_format = table(HEAD).and_then(|head| check_head(head, problems));
+    let (num_glyphs, max_component_depth) = match table(MAXP) {
+        Some(maxp) => check_maxp(maxp, problems),
+        None => (None, None),
+    };
+    if let Some(num_glyphs) = num_glyphs {
+        check_metrics(table(HHEA), table(HMTX), HHEA, HMTX, num_glyphs, problems);
+        check_metrics(table(VHEA), table(VMTX), VHEA, VMTX, num_glyphs, problems);
+    }
+    if let Some(os2) = table(OS2) {
+        check_os2(os2, problems);
+    }
+    if let Some(post) = table(POST) {
+        check_post(post, num_glyphs, problems);
+    }
+    if let Some(name) = table(NAME) {
+        check_name(name, problems);
+    }
+    if let (Some(cmap), Some(num_glyphs)) = (table(CMAP), num_glyphs) {
+        check_cmap(cmap, num_glyphs, problems);
+    }
+
+    match (table(LOCA), table(GLYF)) {
+        (Some(loca), Some(glyf)) => {
+            if let (Some(index_to_loc_format), Some(num_glyphs)) = (index_to_loc_format, num_glyphs)
+            {
+                check_glyphs(
+                    loca,
+                    glyf,
+                    index_to_loc_format,
+                    num_glyphs,
+                    max_component_depth,
+                    problems,
+                );
+            }
+        }
+        (Some(_), None) => problems.error(
+            Some(GLYF),
+            "the font has a 'loca' table but no 'glyf' table",
+        ),
+        (None, Some(_)) => problems.error(
+            Some(LOCA),
+            "the font has a 'glyf' table but no 'loca' table",
+        ),
+        (None, None) => {}
+    }
+}
+
+/// Check the `head` table and get its index to loca format.
+fn check_head(head: &[u8], problems: &mut Problems) -> Option<u16> {
+    if !problems.check_length(HEAD, head, 54, "version 1.0") {
+        return None;
+    }
+    let magic_number = read_u32(head, 12)?;
+    if magic_number != HEAD_MAGIC_NUMBER {
+        problems.error(
+            Some(HEAD),
+            format!(
+                "the magic number is 0x{magic_number:08X} instead of 0x{HEAD_MAGIC_NUMBER:08X}"
+            ),
+        );
+    }
+    let units_per_em = read_u16(head, 18)?;
+    if !(16..=16384).contains(&units_per_em) {
+        problems.error(
+            Some(HEAD),
+            format!("the units per em {units_per_em} are not between 16 and 16384"),
+        );
+    }
+    let index_to_loc_format = read_u16(head, 50)?;
+    if index_to_loc_format > 1 {
+        problems.error(
+            Some(HEAD),
+            format!("the index to loca format {index_to_loc_format} is neither 0 nor 1"),
+        );
+        return None;
+    }
+    Some(index_to_loc_format)
+}
+
+/// Check the `maxp` table and get the number of glyphs and the maximum component depth.
+fn check_maxp(maxp: &[u8], problems: &mut Problems) -> (Option<u16>, Option<u16>) {
+    let (length, max_component_depth) = match read_u32(maxp, 0) {
+        Some(0x0000_5000) => (6, None),
+        Some(0x0001_0000) => (32, read_u16(maxp, 30)),
+        Some(version) => {
+            problems.error(Some(MAXP), format!("unknown version 0x{version:08X}"));
+            return (None, None);
+        }
+        None => (6, None),
+    };
+    if !problems.check_length(MAXP, maxp, length, "the version") {
+        return (None, None);
+    }
+    let num_glyphs = read_u16(maxp, 4);
+    if num_glyphs == Some(0) {
+        problems.error(Some(MAXP), "the font has no glyphs");
+        return (None, None);
+    }
+    (num_glyphs, max_component_depth)
+}
+
+/// Check the number of metrics of a `hhea` or `vhea` table against the number of glyphs, and the
+/// length of the `hmtx` or `vmtx` table against the number of metrics.
+fn check_metrics(
+    header: Option<&[u8]>,
+    metrics: Option<&[u8]>,
+    header_tag: u32,
+    metrics_tag: u32,
+    num_glyphs: u16,
+    problems: &mut Problems,
+) {
+    let (header, metrics) = match (header, metrics) {
+        (Some(header), Some(metrics)) => (header, metrics),
+        (Some(_), None) => {
+            let message = format!(
+                "the font has a '{}' table but no '{}' table",
+                tag_to_string(header_tag),
+                tag_to_string(metrics_tag)
+            );
+            problems.error(Some(metrics_tag), message);
+            return;
+        }
+        (None, Some(_)) => {
+            let message = format!(
+                "the font has a '{}' table but no '{}' table",
+                tag_to_string(metrics_tag),
+                tag_to_string(header_tag)
+            );
+            problems.error(Some(header_tag), message);
+            return;
+        }
+        (None, None) => return,
+    };
+    if !problems.check_length(header_tag, header, 36, "version 1.0") {
+        return;
+    }
+
+    let num_metrics = read_u16(header, 34).unwrap_or(0);
+    if num_metrics == 0 {
+        problems.error(Some(header_tag), "the number of long metrics is 0");
+        return;
+    }
+    if num_metrics > num_glyphs {
+        problems.error(
+            Some(header_tag),
+            format!("the number of long metrics {num_metrics} exceeds the number of glyphs {num_glyphs}"),
+        );
+        return;
+    }
+
+    let side_bearings = usize::from(num_glyphs - num_metrics);
+    let expected = usize::from(num_metrics) * 4 + side_bearings * 2;
+    let what = format!(
+        "{} and {}",
+        count(usize::from(num_metrics), "long metric"),
+        count(side_bearings, "side bearing")
+    );
+    if problems.check_length(metrics_tag, metrics, expected, &what) && metrics.len() > expected {
+        problems.warning(
+            Some(metrics_tag),
+            format!(
+                "the table is {}, but {what} only need {}",
+                count(metrics.len(), "byte"),
+                count(expected, "byte")
+            ),
+        );
+    }
+}
+
+/// Check the length of the `OS/2` table against its version.
+fn check_os2(os2: &[u8], problems: &mut Problems) {
+    let Some(version) = read_u16(os2, 0) else {
+        problems.check_length(OS2, os2, 2, "the version");
+        return;
+    };
+    let length = match version {
+        0 => 78,
+        1 => 86,
+        2..=4 => 96,
+        5 => 100,
+        _ => {
+            problems.warning(Some(OS2), format!("unknown version {version}"));
+            100
+        }
+    };
+    problems.check_length(OS2, os2, length, &format!("version {version}"));
+}
+
+/// Check the `post` table and the glyph name indices of version 2.0 against the number of glyphs.
+fn check_post(post: &[u8], num_glyphs: Option<u16>, problems: &mut Problems) {
+    if !problems.check_length(POST, post, 32, "the header") {
+        return;
+    }
+    if read_u32(post, 0) != Some(0x0002_0000) {
+        return;
+    }
+
+    let Some(post_num_glyphs) = read_u16(post, 32) else {
+        problems.check_length(POST, post, 34, "version 2.0");
+        return;
+    };
+    if num_glyphs.is_some_and(|num_glyphs| num_glyphs != post_num_glyphs) {
+        problems.warning(
+            Some(POST),
+            format!(
+                "the number of glyphs {post_num_glyphs} differs from the number of glyphs {} of the 'maxp' table",
+                num_glyphs.unwrap_or_default()
+            ),
+        );
+    }
+    problems.check_length(
+        POST,
+        post,
+        34 + usize::from(post_num_glyphs) * 2,
+        &count(usize::from(post_num_glyphs), "glyph name index"),
+    );
+}
+
+/// Check the name records of the `name` table point into its string storage.
+fn check_name(name: &[u8], problems: &mut Problems) {
+    if !problems.check_length(NAME, name, 6, "the header") {
+        return;
+    }
+    let num_records = usize::from(read_u16(name, 2).unwrap_or(0));
+    let storage_offset = usize::from(read_u16(name, 4).unwrap_or(0));
+    if !problems.check_length(
+        NAME,
+        name,
+        6 + num_records * 12,
+        &count(num_records, "name record"),
+    ) {
+        return;
+    }
+    if storage_offset > name.len() {
+        problems.error(
+            Some(NAME),
+            format!(
+                "the string storage at offset {storage_offset} is past the end of the table ({})",
+                count(name.len(), "byte")
+            ),
+        );
+        return;
+    }
+
+    let mut truncated = Vec::new();
+    for idx in 0..num_records {
+        let record = 6 + idx * 12;
+        let length = usize::from(read_u16(name, record + 8).unwrap_or(0));
+        let offset = usize::from(read_u16(name, record + 10).unwrap_or(0));
+        if storage_offset + offset + length > name.len() {
+            truncated.push(idx.to_string());
+        }
+    }
+    if !truncated.is_empty() {
+        problems.error(
+            Some(NAME),
+            format!(
+                "the strings of name records {} extend past the end of the table",
+                truncated.join(", ")
+            ),
+        );
+    }
+}
+
+/// Check the subtables of the `cmap` table are within its bounds and map characters only to
+/// glyphs of the font.
+fn check_cmap(cmap: &[u8], num_glyphs: u16, problems: &mut Problems) {
+    if Cmap::read(FontData::new(cmap)).is_err() {
+        problems.error(
+            Some(CMAP),
+            "the header or the encoding records are truncated",
+        );
+        return;
+    }
+
+    let num_subtables = usize::from(read_u16(cmap, 2).unwrap_or(0));
+    for idx in 0..num_subtables {
+        let record = 4 + idx * 8;
+        let platform_id = read_u16(cmap, record).unwrap_or(0);
+        let encoding_id = read_u16(cmap, record + 2).unwrap_or(0);
+        let offset = read_u32(cmap, record + 4).unwrap_or(0) as usize;
+        let subtable_name =
+            format!("subtable {idx} (platform {platform_id}, encoding {encoding_id})");
+
+        let Some(subtable_data) = cmap.get(offset..).filter(|data| data.len() >= 4) else {
+            problems.error(
+                Some(CMAP),
+                format!("{subtable_name} at offset {offset} is past the end of the table"),
+            );
+            continue;
+        };
+        let format = read_u16(subtable_data, 0).unwrap_or(0);
+        let length = match format {
+            0 | 2 | 4 | 6 => read_u16(subtable_data, 2).map(usize::from),
+            8 | 10 | 12 | 13 => read_u32(subtable_data, 4).map(|length| length as usize),
+            14 => read_u32(subtable_data, 2).map(|length| length as usize),
+            _ => {
+                problems.error(
+                    Some(CMAP),
+                    format!("{subtable_name} has unknown format {format}"),
+                );
+                continue;
+            }
+        };
+        if length.is_none_or(|length| length > subtable_data.len()) {
+            problems.error(
+                Some(CMAP),
+                format!(
+                    "{subtable_name} of format {format} with length {} extends past the end of the table",
+                    length.unwrap_or_default()
+                ),
+            );
+            continue;
+        }
+        let Ok(subtable) = CmapSubtable::read(FontData::new(subtable_data)) else {
+            problems.error(
+                Some(CMAP),
+                format!("{subtable_name} of format {format} is truncated"),
+            );
+            continue;
+        };
+
+        // the first character mapped to a glyph that does not exist, and the number of such
+        // characters
+        let mut bad_mappings: Option<(u32, u32)> = None;
+        let mut bad_count = 0u64;
+        let mut check = |codepoint: u32, glyph_id: u32, repeat: u64| {
+            if glyph_id >= u32::from(num_glyphs) {
+                bad_mappings.get_or_insert((codepoint, glyph_id));
+                bad_count += repeat;
+            }
+        };
+        match subtable {
+            CmapSubtable::Format0(subtable) => {
+                for (codepoint, &glyph_id) in subtable.glyph_id_array().iter().enumerate() {
+                    check(codepoint as u32, u32::from(glyph_id), 1);
+                }
+            }
+            CmapSubtable::Format4(subtable) => {
+                for (codepoint, glyph_id) in subtable.iter() {
+                    check(codepoint, u32::from(glyph_id.to_u16()), 1);
+                }
+            }
+            CmapSubtable::Format6(subtable) => {
+                for (idx, glyph_id) in subtable.glyph_id_array().iter().enumerate() {
+                    let codepoint = u32::from(subtable.first_code()) + idx as u32;
+                    check(codepoint, u32::from(glyph_id.get()), 1);
+                }
+            }
+            CmapSubtable::Format12(subtable) => {
+                for group in subtable.groups() {
+                    let (start, end) = (group.start_char_code(), group.end_char_code());
+                    let start_glyph_id = group.start_glyph_id();
+                    if end < start {
+                        continue;
+                    }
+                    // the glyph IDs increase over the group, so only the ones past the number of
+                    // glyphs are bad
+                    let last_glyph_id = u64::from(start_glyph_id) + u64::from(end - start);
+                    if last_glyph_id >= u64::from(num_glyphs) {
+                        let first_bad = u32::from(num_glyphs).max(start_glyph_id);
+                        check(
+                            start + (first_bad - start_glyph_id),
+                            first_bad,
+                            last_glyph_id - u64::from(first_bad) + 1,
+                        );
+                    }
+                }
+            }
+            CmapSubtable::Format13(subtable) => {
+                for group in subtable.groups() {
+                    let (start, end) = (group.start_char_code(), group.end_char_code());
+                    if end >= start {
+                        check(start, group.glyph_id(), u64::from(end - start) + 1);
+                    }
+                }
+            }
+            _ => {}
+        }
+        if let Some((codepoint, glyph_id)) = bad_mappings {
+            problems.error(
+                Some(CMAP),
+                format!(
+                    "{subtable_name} maps {} to glyphs that do not exist, e.g., U+{codepoint:04X} to glyph {glyph_id}, but the font has {}",
+                    count(bad_count as usize, "character"),
+                    count(usize::from(num_glyphs), "glyph")
+                ),
+            );
+        }
+    }
+}
+
+/// Check the `loca` offsets are within the `glyf` table, the glyph data is not truncated and
+/// composite glyphs do not refer to themselves.
+fn check_glyphs(
+    loca: &[u8],
+    glyf: &[u8],
+    index_to_loc_format: u16,
+    num_glyphs: u16,
+    max_component_depth: Option<u16>,
+    problems: &mut Problems,
+) {
+    let num_glyphs = usize::from(num_glyphs);
+    let is_long = index_to_loc_format == 1;
+    let offset_size = if is_long { 4 } else { 2 };
+    if !problems.check_length(
+        LOCA,
+        loca,
+        (num_glyphs + 1) * offset_size,
+        &count(num_glyphs + 1, "offset"),
+    ) {
+        return;
+    }
+    let offset = |glyph_id: usize| {
+        if is_long {
+            read_u32(loca, glyph_id * 4).unwrap_or(0) as usize
+        } else {
+            usize::from(read_u16(loca, glyph_id * 2).unwrap_or(0)) * 2
+        }
+    };
+
+    let mut loca_problems = Vec::new();
+    let mut glyf_problems = Vec::new();
+    let mut components = vec![Vec::new(); num_glyphs];
+    for (glyph_id, glyph_components) in components.iter_mut().enumerate() {
+        let (start, end) = (offset(glyph_id), offset(glyph_id + 1));
+        if end < start {
+            loca_problems.push(format!(
+                "glyph {glyph_id} ends at offset {end}, before it starts at offset {start}"
+            ));
+            continue;
+        }
+        let Some(glyph) = glyf.get(start..end) else {
+            loca_problems.push(format!(
+                "glyph {glyph_id} at offset {start} with length {} extends past the end of the 'glyf' table ({})",
+                end - start,
+                count(glyf.len(), "byte")
+            ));
+            continue;
+        };
+        match check_glyph(glyph, num_glyphs) {
+            Ok(ids) => *glyph_components = ids,
+            Err(message) => glyf_problems.push(format!("glyph {glyph_id} {message}")),
+        }
+    }
+
+    let (cycles, depth) = component_cycles(&components);
+    for (glyph_id, component) in cycles {
+        glyf_problems.push(if glyph_id == usize::from(component) {
+            format!("composite glyph {glyph_id} has itself as a component")
+        } else {
+            format!(
+                "composite glyph {glyph_id} has component glyph {component}, which contains glyph {glyph_id} itself"
+            )
+        });
+    }
+    problems.glyph_errors(LOCA, loca_problems);
+    problems.glyph_errors(GLYF, glyf_problems);
+
+    if let Some(max_component_depth) = max_component_depth {
+        if depth > usize::from(max_component_depth) {
+            problems.warning(
+                Some(MAXP),
+                format!(
+                    "the maximum component depth is {max_component_depth}, but composite glyphs have up to {}",
+                    count(depth, "level")
+                ),
+            );
+        }
+    }
+}
+
+/// Check the data of a glyph fits in its length, and get the glyph IDs of its components. Returns
+/// a message describing the problem, to follow the glyph ID, if the glyph data is malformed.
+fn check_glyph(glyph: &[u8], num_glyphs: usize) -> Result<Vec<u16>, String> {
+    if glyph.is_empty() {
+        return Ok(Vec::new());
+    }
+    let truncated = |needed: usize| {
+        format!(
+            "is truncated: it is {}, but its data needs at least {}",
+            count(glyph.len(), "byte"),
+            count(needed, "byte")
+        )
+    };
+    let Some(number_of_contours) = read_u16(glyph, 0).filter(|_| glyph.len() >= 10) else {
+        return Err(truncated(10));
+    };
+    let number_of_contours = number_of_contours as i16;
+
+    if number_of_contours < 0 {
+        let mut components = Vec::new();
+        let mut position = 10;
+        loop {
+            let (Some(flags), Some(component)) =
+                (read_u16(glyph, position), read_u16(glyph, position + 2))
+            else {
+                return Err(truncated(position + 4));
+            };
+            if usize::from(component) >= num_glyphs {
+                return Err(format!(
+                    "has component glyph {component}, but the font has {}",
+                    count(num_glyphs, "glyph")
+                ));
+            }
+            components.push(component);
+
+            position += 4;
+            position += if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+                4
+            } else {
+                2
+            };
+            if flags & WE_HAVE_A_SCALE != 0 {
+                position += 2;
+            } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                position += 4;
+            } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                position += 8;
+            }
+            if position > glyph.len() {
+                return Err(truncated(position));
+            }
+
+            if flags & MORE_COMPONENTS == 0 {
+                if flags & WE_HAVE_INSTRUCTIONS != 0 {
+                    let Some(instruction_length) = read_u16(glyph, position) else {
+                        return Err(truncated(position + 2));
+                    };
+                    position += 2 + usize::from(instruction_length);
+                    if position > glyph.len() {
+                        return Err(truncated(position));
+                    }
+                }
+                return Ok(components);
+            }
+        }
+    }
+
+    let number_of_contours = number_of_contours as usize;
+    let mut num_points = 0;
+    for contour in 0..number_of_contours {
+        let Some(end_point) = read_u16(glyph, 10 + contour * 2) else {
+            return Err(truncated(10 + number_of_contours * 2 + 2));
+        };
+        let end_point = usize::from(end_point);
+        if contour > 0 && end_point < num_points {
+            return Err(format!(
+                "has contour end points that decrease at contour {contour}"
+            ));
+        }
+        num_points = end_point + 1;
+    }
+    let instructions = 10 + number_of_contours * 2;
+    let Some(instruction_length) = read_u16(glyph, instructions) else {
+        return Err(truncated(instructions + 2));
+    };
+    let mut position = instructions + 2 + usize::from(instruction_length);
+
+    let (mut points, mut x_size, mut y_size) = (0, 0, 0);
+    while points < num_points {
+        let Some(&flag) = glyph.get(position) else {
+            return Err(truncated(position + 1));
+        };
+        position += 1;
+        let mut repeat = 1;
+        if flag & REPEAT_FLAG != 0 {
+            let Some(&count) = glyph.get(position) else {
+                return Err(truncated(position + 1));
+            };
+            position += 1;
+            repeat += usize::from(count);
+        }
+        points += repeat;
+        x_size += repeat
+            * match (
+                flag & X_SHORT_VECTOR,
+                flag & X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+            ) {
+                (0, 0) => 2,
+                (0, _) => 0,
+                _ => 1,
+            };
+        y_size += repeat
+            * match (
+                flag & Y_SHORT_VECTOR,
+                flag & Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+            ) {
+                (0, 0) => 2,
+                (0, _) => 0,
+                _ => 1,
+            };
+    }
+    if points > num_points {
+        return Err(format!(
+            "has flags for {}, but only {}",
+            count(points, "point"),
+            count(num_points, "point")
+        ));
+    }
+    let needed = position + x_size + y_size;
+    if needed > glyph.len() {
+        return Err(truncated(needed));
+    }
+    Ok(Vec::new())
+}
+
+/// Find the components that make composite glyphs contain themselves, and get the maximum depth
+/// of the composite glyphs, where glyphs with only simple components have depth 1.
+fn component_cycles(components: &[Vec<u16>]) -> (Vec<(usize, u16)>, usize) {
+    const UNVISITED: u8 = 0;
+    const VISITING: u8 = 1;
+    const VISITED: u8 = 2;
+
+    let mut state = vec![UNVISITED; components.len()];
+    let mut depth = vec![0; components.len()];
+    let mut cycles = Vec::new();
+
+    // an explicit stack, as malicious fonts may nest components very deeply
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for root in 0..components.len() {
+        if state[root] != UNVISITED {
+            continue;
+        }
+        state[root] = VISITING;
+        stack.push((root, 0));
+        while let Some((glyph_id, next)) = stack.last_mut() {
+            let glyph_id = *glyph_id;
+            if let Some(&component) = components[glyph_id].get(*next) {
+                *next += 1;
+                let component_id = usize::from(component);
+                match state[component_id] {
+                    UNVISITED => {
+                        state[component_id] = VISITING;
+                        stack.push((component_id, 0));
+                    }
+                    VISITING => cycles.push((glyph_id, component)),
+                    _ => {}
+                }
+            } else {
+                depth[glyph_id] = components[glyph_id]
+                    .iter()
+                    .filter(|&&component| state[usize::from(component)] == VISITED)
+                    .map(|&component| depth[usize::from(component)] + 1)
+                    .max()
+                    .unwrap_or(0);
+                state[glyph_id] = VISITED;
+                stack.pop();
+            }
+        }
+    }
+
+    (cycles, depth.into_iter().max().unwrap_or(0))
+}
+
+/// Validates font files. The problems are written when finished.
+pub(crate) struct Validator {
+    fail_on: Severity,
+    font_index: Option<usize>,
+    files: Vec<ValidatedFile>,
+}
+
+impl Validator {
+    pub fn new(args: &ValidateArgs, font_index: Option<usize>) -> Self {
+        Validator {
+            fail_on: args.fail_on,
+            font_index,
+            files: Vec::new(),
+        }
+    }
+
+    /// Validate the data of a font file.
+    pub fn push_file(&mut self, source: &str, data: &[u8]) {
+        let (problems, fonts) = validate(data, self.font_index);
+        self.files.push(ValidatedFile {
+            source: source.to_owned(),
+            problems,
+            fonts,
+        });
+    }
+
+    /// Record a font file or directory that could not be read as an error of the file.
+    pub fn push_read_error(&mut self, source: &str, error: &anyhow::Error) {
+        self.files.push(ValidatedFile {
+            source: source.to_owned(),
+            problems: vec![Problem {
+                severity: Severity::Error,
+                table: None,
+                message: format!("failed to read: {error:#}"),
+            }],
+            fonts: Vec::new(),
+        });
+    }
+
+    fn problems(&self) -> impl Iterator<Item = &Problem> {
+        self.files.iter().flat_map(|file| {
+            file.problems
+                .iter()
+                .chain(file.fonts.iter().flat_map(|font| &font.problems))
+        })
+    }
+
+    /// Get whether any problem is at least as severe as the severity to fail on.
+    fn outcome(&self) -> Outcome {
+        if self
+            .problems()
+            .any(|problem| problem.severity >= self.fail_on)
+        {
+            Outcome::Fail
+        } else {
+            Outcome::Pass
+        }
+    }
+
+    /// Write the problems as lines such as "error: font.ttf#0: table 'glyf': glyph 3 is
+    /// truncated: ...", followed by a summary.
+    pub fn write_human_readable(self, mut write: impl Write) -> anyhow::Result<Outcome> {
+        for file in &self.files {
+            for problem in &file.problems {
+                writeln!(
+                    write,
+                    "{}: {}: {problem}",
+                    problem.severity.name(),
+                    file.source
+                )?;
+            }
+            for font in &file.fonts {
+                for problem in &font.problems {
+                    writeln!(
+                        write,
+                        "{}: {}#{}: {problem}",
+                        problem.severity.name(),
+                        file.source,
+                        font.font_index
+                    )?;
+                }
+            }
+        }
+
+        let mut counts = [0; 2];
+        for problem in self.problems() {
+            counts[problem.severity as usize] += 1;
+        }
+        let [warnings, errors] = counts;
+        writeln!(
+            write,
+            "{} and {} in {}",
+            count(errors, "error"),
+            count(warnings, "warning"),
+            count(self.files.len(), "file")
+        )?;
+
+        Ok(self.outcome())
+    }
+
+    /// Write the files and their problems as JSON.
+    pub fn write_json(self, mut write: impl Write) -> anyhow::Result<Outcome> {
+        serde_json::to_writer(&mut write, &self.files)?;
+        writeln!(write)?;
+        Ok(self.outcome())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fonts::font_file;
+
+    fn messages(data: &[u8]) -> Vec<String> {
+        let (problems, fonts) = validate(data, None);
+        problems
+            .iter()
+            .chain(fonts.iter().flat_map(|font| &font.problems))
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_truncated_files() {
+        assert_eq!(
+            messages(b"\0\x01\0\0"),
+            ["the file is 4 bytes, too short for a font file"]
+        );
+        assert_eq!(messages(b"wOF2\0\0\0\0"), ["WOFF2 files are not supported"]);
+
+        let mut data = font_file(&[(b"cmap", &[0; 4])]);
+        data.truncate(20);
+        assert_eq!(
+            messages(&data),
+            ["the table directory with 1 table ends at offset 28, past the end of the file (20 bytes)"]
+        );
+    }
+
+    #[test]
+    fn test_table_bounds() {
+        let mut data = font_file(&[(b"cmap", &[0; 8]), (b"head", &[0; 8])]);
+        // let the cmap table overlap the head table, and the head table extend past the end
+        data[12 + 12..12 + 16].copy_from_slice(&12u32.to_be_bytes());
+        data[28 + 12..28 + 16].copy_from_slice(&100u32.to_be_bytes());
+        let messages = messages(&data);
+        assert!(messages.contains(
+            &"table 'head': the table at offset 52 with length 100 extends past the end of the file (60 bytes)"
+                .to_owned()
+        ));
+        assert!(messages
+            .contains(&"table 'hhea': the font does not have this required table".to_owned()));
+    }
+
+    #[test]
+    fn test_check_glyph() {
+        let mut simple = Vec::new();
+        // one contour with three points, no instructions, three flags with short x and y
+        for value in [1u16, 0, 0, 10, 10, 2, 0] {
+            simple.extend(value.to_be_bytes());
+        }
+        simple.extend([0x37, 0x37, 0x37, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(check_glyph(&simple, 1), Ok(vec![]));
+        assert_eq!(
+            check_glyph(&simple[..simple.len() - 1], 1),
+            Err("is truncated: it is 22 bytes, but its data needs at least 23 bytes".to_owned())
+        );
+
+        let mut composite = Vec::new();
+        for value in [0xFFFFu16, 0, 0, 0, 0, 0, 1, 0] {
+            composite.extend(value.to_be_bytes());
+        }
+        assert_eq!(check_glyph(&composite, 2), Ok(vec![1]));
+        assert_eq!(
+            check_glyph(&composite, 1),
+            Err("has component glyph 1, but the font has 1 glyph".to_owned())
+        );
+    }
+
+    #[test]
+    fn test_component_cycles() {
+        let components = [vec![], vec![0], vec![1, 3], vec![2]];
+        let (cycles, _) = component_cycles(&components);
+        assert_eq!(cycles, [(3, 2)]);
+
+        let components = [vec![], vec![0], vec![1, 0]];
+        assert_eq!(component_cycles(&components), (vec![], 2));
+    }
+
+    #[test]
+    fn test_read_error() {
+        let args = ValidateArgs {
+            input: Default::default(),
+            fail_on: Severity::Error,
+        };
+        let mut validator = Validator::new(&args, None);
+        validator.push_read_error("missing.ttf", &anyhow::anyhow!("No such file or directory"));
+
+        let mut output = Vec::new();
+        let outcome = validator.write_human_readable(&mut output).unwrap();
+        assert_eq!(outcome, Outcome::Fail);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "error: missing.ttf: failed to read: No such file or directory\n\
+             1 error and 0 warnings in 1 file\n"
+        );
+    }
+}
@@ -0,0 +1,358 @@
+use anyhow::Context;
+use read_fonts::tables::fvar::Fvar;
+use swash::{FontRef, Instance, NormalizedCoord, Setting, StringId};
+
+use crate::{names::localized_string, settings::tag_to_string, tables::read_table};
+
+/// A variation axis of a variable font, from the `fvar` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Axis {
+    pub tag: String,
+    pub name: Option<String>,
+    pub min: f32,
+    pub default: f32,
+    pub max: f32,
+    pub hidden: bool,
+}
+
+/// A named instance of a variable font, from the `fvar` table.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct NamedInstance {
+    pub name: Option<String>,
+    pub postscript_name: Option<String>,
+    pub coordinates: Vec<Coordinate>,
+}
+
+/// A user-space coordinate on a single variation axis.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Coordinate {
+    pub axis: String,
+    pub value: f32,
+}
+
+/// The variation axes and named instances of a font. Both are empty if the font is not a variable
+/// font.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Variations {
+    pub axes: Vec<Axis>,
+    pub instances: Vec<NamedInstance>,
+}
+
+impl Variations {
+    /// Read the variation axes and named instances of the font. Names are given in the preferred
+    /// language, if available.
+    pub fn from_font(font: FontRef<'_>, language: Option<&str>) -> Self {
+        let axes = font
+            .variations()
+            .map(|axis| Axis {
+                tag: tag_to_string(axis.tag()),
+                name: localized_string(font, axis.name_id(), language).map(|name| name.to_string()),
+                min: axis.min_value(),
+                default: axis.default_value(),
+                max: axis.max_value(),
+                hidden: axis.is_hidden(),
+            })
+            .collect();
+
+        let instances = font
+            .instances()
+            .zip(postscript_name_ids(font))
+            .map(|(instance, postscript_name_id)| NamedInstance {
+                name: localized_string(font, instance.name_id(), language)
+                    .map(|name| name.to_string()),
+                postscript_name: postscript_name_id
+                    .and_then(|id| localized_string(font, id, language))
+                    .map(|name| name.to_string()),
+                coordinates: font
+                    .variations()
+                    .zip(instance.values())
+                    .map(|(axis, value)| Coordinate {
+                        axis: tag_to_string(axis.tag()),
+                        value,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Variations { axes, instances }
+    }
+}
+
+/// Get the name IDs of the PostScript names of the named instances, one for each instance. swash
+/// reads them from the wrong offset, so they are read from the `fvar` table here.
+fn postscript_name_ids(font: FontRef<'_>) -> Vec<Option<StringId>> {
+    let Some(instances) = read_table::<Fvar>(font).and_then(|fvar| fvar.instances().ok()) else {
+        return Vec::new();
+    };
+    instances
+        .iter()
+        .map(|instance| {
+            instance
+                .ok()
+                .and_then(|instance| instance.post_script_name_id)
+                .map(|id| StringId::from_raw(id.to_u16()))
+        })
+        .collect()
+}
+
+/// Find a named instance of the font by its PostScript name.
+fn find_by_postscript_name<'a>(font: FontRef<'a>, name: &str) -> Option<Instance<'a>> {
+    font.instances()
+        .zip(postscript_name_ids(font))
+        .find(|(_, postscript_name_id)| {
+            postscript_name_id.is_some_and(|id| {
+                font.localized_strings()
+                    .filter(|string| string.id() == id)
+                    .any(|string| string.chars().eq(name.chars()))
+            })
+        })
+        .map(|(instance, _)| instance)
+}
+
+/// Write the variation axes and named instances of a font in the human-readable format.
+pub(crate) fn write_variations(
+    mut write: impl std::io::Write,
+    variations: &Variations,
+) -> std::io::Result<()> {
+    write!(write, "      Variation axes: ")?;
+    for (idx, axis) in variations.axes.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        write!(
+            write,
+            "{}: min {}, default {}, max {}",
+            axis.tag, axis.min, axis.default, axis.max
+        )?;
+        if let Some(name) = &axis.name {
+            write!(write, " \"{name}\"")?;
+        }
+        if axis.hidden {
+            write!(write, " (hidden)")?;
+        }
+    }
+    writeln!(write)?;
+
+    write!(write, "     Named instances: ")?;
+    for (idx, instance) in variations.instances.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        match &instance.name {
+            Some(name) => write!(write, "\"{name}\"")?,
+            None => write!(write, "<unnamed>")?,
+        }
+        for coordinate in &instance.coordinates {
+            write!(write, " {}={}", coordinate.axis, coordinate.value)?;
+        }
+    }
+    writeln!(write)?;
+
+    Ok(())
+}
+
+/// The location in a variable font's design space at which the font is inspected.
+pub(crate) struct Location {
+    /// User-space coordinates, one for each axis in the order the axes appear in the font.
+    pub coordinates: Vec<Coordinate>,
+    /// Normalized coordinates, as used by swash to compute metrics.
+    pub normalized: Vec<NormalizedCoord>,
+}
+
+impl Location {
+    /// Resolve the location of a font. Coordinates start at the axes' defaults, are then set to
+    /// the named instance's coordinates (if any) and are finally overridden by the explicit
+    /// variation settings. Values are clamped to the axes' ranges. It is an error if the font
+    /// has no such named instance or no axis of a variation setting.
+    pub fn from_font(
+        font: FontRef<'_>,
+        instance: Option<&str>,
+        variation_settings: &[Setting<f32>],
+    ) -> anyhow::Result<Self> {
+        let variations = font.variations();
+        let mut values: Vec<f32> = variations.map(|axis| axis.default_value()).collect();
+
+        if let Some(instance_name) = instance {
+            let instances = font.instances();
+            let instance = instances
+                .find_by_name(instance_name)
+                .or_else(|| find_by_postscript_name(font, instance_name))
+                .with_context(|| format!("the font has no named instance '{instance_name}'"))?;
+            for (value, instance_value) in values.iter_mut().zip(instance.values()) {
+                *value = instance_value;
+            }
+        }
+
+        for setting in variation_settings {
+            let axis = variations.find_by_tag(setting.tag).with_context(|| {
+                format!(
+                    "the font has no variation axis '{}'",
+                    tag_to_string(setting.tag)
+                )
+            })?;
+            values[axis.index()] = setting.value;
+        }
+
+        let coordinates: Vec<Coordinate> = variations
+            .zip(values)
+            .map(|(axis, value)| Coordinate {
+                axis: tag_to_string(axis.tag()),
+                // not using `f32::clamp`, as that panics on malformed axes where min > max
+                value: value.max(axis.min_value()).min(axis.max_value()),
+            })
+            .collect();
+
+        let normalized = variations
+            .normalized_coords(
+                variations
+                    .zip(&coordinates)
+                    .map(|(axis, coordinate)| (axis.tag(), coordinate.value)),
+            )
+            .collect();
+
+        Ok(Location {
+            coordinates,
+            normalized,
+        })
+    }
+}
+
+/// Write the variation coordinates a font is inspected at, if it is a variable font.
+pub(crate) fn write_coordinates(
+    mut write: impl std::io::Write,
+    location: &Location,
+) -> std::io::Result<()> {
+    if !location.coordinates.is_empty() {
+        write!(write, "         Coordinates:")?;
+        for coordinate in &location.coordinates {
+            write!(write, " {}={}", coordinate.axis, coordinate.value)?;
+        }
+        writeln!(write)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_fonts::{font_file, name_table, Data};
+
+    /// A font with a weight axis from 100 to 900 and a width axis from 75 to 125, and the named
+    /// instances "Bold" and "Condensed Bold".
+    fn variable_font() -> Vec<u8> {
+        let mut fvar = Data::default()
+            .u16(1)
+            .u16(0)
+            .u16(16)
+            .u16(2)
+            .u16(2)
+            .u16(20)
+            .u16(2)
+            .u16(14);
+        for (tag, min, default, max, name_id) in [
+            (b"wght", 100., 400., 900., 256),
+            (b"wdth", 75., 100., 125., 257),
+        ] {
+            fvar = fvar
+                .tag(tag)
+                .fixed(min)
+                .fixed(default)
+                .fixed(max)
+                .u16(0)
+                .u16(name_id);
+        }
+        for (name_id, weight, width, postscript_name_id) in
+            [(258, 700., 100., 0xFFFF), (259, 700., 75., 260)]
+        {
+            fvar = fvar
+                .u16(name_id)
+                .u16(0)
+                .fixed(weight)
+                .fixed(width)
+                .u16(postscript_name_id);
+        }
+        let name = name_table(&[
+            (0x0409, 256, "Weight"),
+            (0x0409, 257, "Width"),
+            (0x0409, 258, "Bold"),
+            (0x0409, 259, "Condensed Bold"),
+            (0x0409, 260, "Sans-CondensedBold"),
+        ]);
+        font_file(&[(b"fvar", &fvar.0), (b"name", &name)])
+    }
+
+    fn setting(tag: &[u8; 4], value: f32) -> Setting<f32> {
+        Setting {
+            tag: swash::tag_from_bytes(tag),
+            value,
+        }
+    }
+
+    fn values(location: &Location) -> Vec<(&str, f32)> {
+        location
+            .coordinates
+            .iter()
+            .map(|coordinate| (coordinate.axis.as_str(), coordinate.value))
+            .collect()
+    }
+
+    #[test]
+    fn test_variations() {
+        let data = variable_font();
+        let font = FontRef::from_index(&data, 0).unwrap();
+        let variations = Variations::from_font(font, None);
+        assert_eq!(variations.axes.len(), 2);
+        assert_eq!(variations.axes[0].name.as_deref(), Some("Weight"));
+        assert_eq!(
+            variations.instances[1].postscript_name.as_deref(),
+            Some("Sans-CondensedBold")
+        );
+    }
+
+    #[test]
+    fn test_location() {
+        let data = variable_font();
+        let font = FontRef::from_index(&data, 0).unwrap();
+
+        let default = Location::from_font(font, None, &[]).unwrap();
+        assert_eq!(values(&default), [("wght", 400.), ("wdth", 100.)]);
+        assert_eq!(default.normalized, [0, 0]);
+
+        let bold = Location::from_font(font, Some("Bold"), &[]).unwrap();
+        assert_eq!(values(&bold), [("wght", 700.), ("wdth", 100.)]);
+        let condensed = Location::from_font(font, Some("Sans-CondensedBold"), &[]).unwrap();
+        assert_eq!(values(&condensed), [("wght", 700.), ("wdth", 75.)]);
+
+        // explicit settings override the instance's coordinates
+        let location =
+            Location::from_font(font, Some("Condensed Bold"), &[setting(b"wght", 500.)]).unwrap();
+        assert_eq!(values(&location), [("wght", 500.), ("wdth", 75.)]);
+
+        // coordinates are clamped to the axes' ranges
+        let location = Location::from_font(
+            font,
+            None,
+            &[setting(b"wght", 1000.), setting(b"wdth", 50.)],
+        )
+        .unwrap();
+        assert_eq!(values(&location), [("wght", 900.), ("wdth", 75.)]);
+        assert_eq!(location.normalized, [1 << 14, -(1 << 14)]);
+    }
+
+    #[test]
+    fn test_unknown_location() {
+        let data = variable_font();
+        let font = FontRef::from_index(&data, 0).unwrap();
+        let err = Location::from_font(font, Some("Black"), &[]).err().unwrap();
+        assert_eq!(err.to_string(), "the font has no named instance 'Black'");
+        let err = Location::from_font(font, None, &[setting(b"opsz", 12.)])
+            .err()
+            .unwrap();
+        assert_eq!(err.to_string(), "the font has no variation axis 'opsz'");
+    }
+}
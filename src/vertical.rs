@@ -0,0 +1,406 @@
+use read_fonts::tables::{head::Head, hhea::Hhea, os2::Os2, os2::SelectionFlags};
+use swash::FontRef;
+
+use crate::tables::read_table;
+
+/// The fraction of the units per em by which the metric sets may differ before a warning is
+/// given.
+const TOLERANCE: f32 = 0.02;
+
+/// A set of vertical metrics, in font units. The descender is negative below the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct MetricSet {
+    pub ascender: i32,
+    pub descender: i32,
+    pub line_gap: i32,
+}
+
+impl MetricSet {
+    pub fn line_height(&self) -> i32 {
+        self.ascender - self.descender + self.line_gap
+    }
+}
+
+/// The global glyph bounding box of the `head` table, in font units.
+#[derive(Debug, Clone, Copy, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct HeadBoundingBox {
+    pub x_min: i16,
+    pub y_min: i16,
+    pub x_max: i16,
+    pub y_max: i16,
+}
+
+/// The line height a platform computes, and the metric set it uses.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct PlatformLineHeight {
+    pub platform: &'static str,
+    /// "hhea", "typo" or "win".
+    pub metrics: &'static str,
+    /// The leading Windows adds to the win metrics, which is included in the line height.
+    pub external_leading: i32,
+    pub line_height: i32,
+}
+
+/// The `hhea`, `OS/2` typo and `OS/2` win vertical metrics of a font, the line heights platforms
+/// compute from them, and warnings about clipping and inconsistencies.
+#[derive(Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct VerticalMetrics {
+    pub units_per_em: Option<u16>,
+    pub hhea: Option<MetricSet>,
+    pub typo: Option<MetricSet>,
+    /// The win metrics have no line gap, and their descent is stored as a positive value. It is
+    /// negated here to match the other sets.
+    pub win: Option<MetricSet>,
+    pub use_typo_metrics: bool,
+    pub head_bounding_box: Option<HeadBoundingBox>,
+    pub line_heights: Vec<PlatformLineHeight>,
+    pub warnings: Vec<String>,
+}
+
+impl VerticalMetrics {
+    /// The metrics are read at the default coordinates of variable fonts.
+    pub fn from_font(font: FontRef<'_>) -> Self {
+        let hhea = read_table::<Hhea>(font).map(|hhea| MetricSet {
+            ascender: hhea.ascender().to_i16().into(),
+            descender: hhea.descender().to_i16().into(),
+            line_gap: hhea.line_gap().to_i16().into(),
+        });
+        let os2 = read_table::<Os2>(font);
+        let typo = os2.as_ref().map(|os2| MetricSet {
+            ascender: os2.s_typo_ascender().into(),
+            descender: os2.s_typo_descender().into(),
+            line_gap: os2.s_typo_line_gap().into(),
+        });
+        let win = os2.as_ref().map(|os2| MetricSet {
+            ascender: os2.us_win_ascent().into(),
+            descender: -i32::from(os2.us_win_descent()),
+            line_gap: 0,
+        });
+        let use_typo_metrics = os2.as_ref().is_some_and(|os2| {
+            os2.fs_selection()
+                .contains(SelectionFlags::USE_TYPO_METRICS)
+        });
+        let head = read_table::<Head>(font);
+
+        let mut metrics = VerticalMetrics {
+            units_per_em: head.as_ref().map(|head| head.units_per_em()),
+            hhea,
+            typo,
+            win,
+            use_typo_metrics,
+            head_bounding_box: head.map(|head| HeadBoundingBox {
+                x_min: head.x_min(),
+                y_min: head.y_min(),
+                x_max: head.x_max(),
+                y_max: head.y_max(),
+            }),
+            line_heights: Vec::new(),
+            warnings: Vec::new(),
+        };
+        metrics.line_heights = metrics.platform_line_heights();
+        metrics.warnings = metrics.check();
+        metrics
+    }
+
+    /// The metric sets the font has, with their names.
+    fn sets(&self) -> [Option<(&'static str, MetricSet)>; 3] {
+        [
+            self.hhea.map(|set| ("hhea", set)),
+            self.typo.map(|set| ("typo", set)),
+            self.win.map(|set| ("win", set)),
+        ]
+    }
+
+    /// The external leading GDI adds to the win metrics: the part of the hhea line gap that
+    /// does not fit in the difference between the win and hhea ascent plus descent.
+    fn win_external_leading(&self) -> i32 {
+        match (self.hhea, self.win) {
+            (Some(hhea), Some(win)) => {
+                let win_height = win.ascender - win.descender;
+                let hhea_height = hhea.ascender - hhea.descender;
+                (hhea.line_gap - (win_height - hhea_height)).max(0)
+            }
+            _ => 0,
+        }
+    }
+
+    /// The line heights the common platforms compute from the metric sets.
+    fn platform_line_heights(&self) -> Vec<PlatformLineHeight> {
+        let [hhea, typo, win] = self.sets();
+        let typo_if_flagged = typo.filter(|_| self.use_typo_metrics);
+        // FreeType and HarfBuzz fall back to the next set if the ascender and descender are zero
+        let non_zero = |set: Option<(&'static str, MetricSet)>| {
+            set.filter(|(_, set)| set.ascender != 0 || set.descender != 0)
+        };
+        // DirectWrite adds the same external leading as GDI when it uses the win metrics
+        let win_external_leading = self.win_external_leading();
+        let direct_write = match typo_if_flagged {
+            Some(typo) => (Some(typo), 0),
+            None => (win, win_external_leading),
+        };
+
+        [
+            ("macOS (Core Text)", (hhea, 0)),
+            ("Windows (GDI)", (win, win_external_leading)),
+            ("Windows (DirectWrite)", direct_write),
+            (
+                "Linux (FreeType)",
+                (
+                    typo_if_flagged
+                        .or(non_zero(hhea))
+                        .or(non_zero(typo))
+                        .or(win),
+                    0,
+                ),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(platform, (set, external_leading))| {
+            set.map(|(metrics, set)| PlatformLineHeight {
+                platform,
+                metrics,
+                external_leading,
+                line_height: set.line_height() + external_leading,
+            })
+        })
+        .collect()
+    }
+
+    /// Check for glyphs clipping against the win metrics and for metric sets disagreeing.
+    fn check(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if let (Some(win), Some(bbox)) = (self.win, self.head_bounding_box) {
+            if i32::from(bbox.y_max) > win.ascender {
+                warnings.push(format!(
+                    "glyphs extend {} units above the win ascent and may be clipped on Windows",
+                    i32::from(bbox.y_max) - win.ascender
+                ));
+            }
+            if i32::from(bbox.y_min) < win.descender {
+                warnings.push(format!(
+                    "glyphs extend {} units below the win descent and may be clipped on Windows",
+                    win.descender - i32::from(bbox.y_min)
+                ));
+            }
+        }
+
+        let units_per_em = self.units_per_em.unwrap_or(1000);
+        let tolerance = (f32::from(units_per_em) * TOLERANCE).round() as i32;
+        let sets: Vec<(&str, MetricSet)> = self.sets().into_iter().flatten().collect();
+        for (idx, &(name, set)) in sets.iter().enumerate() {
+            for &(other_name, other) in &sets[idx + 1..] {
+                let difference = (set.line_height() - other.line_height()).abs();
+                if difference > tolerance {
+                    warnings.push(format!(
+                        "{name} line height {} differs from {other_name} line height {} by {difference} units",
+                        set.line_height(),
+                        other.line_height()
+                    ));
+                }
+                let difference = (set.ascender - other.ascender).abs();
+                if difference > tolerance {
+                    warnings.push(format!(
+                        "{name} ascender {} differs from {other_name} ascender {} by {difference} units",
+                        set.ascender, other.ascender
+                    ));
+                }
+            }
+        }
+
+        warnings
+    }
+}
+
+/// Write the vertical metrics of a font in the human-readable format, followed by the warnings
+/// about them.
+pub(crate) fn write_vertical_metrics(
+    mut write: impl std::io::Write,
+    metrics: &VerticalMetrics,
+) -> std::io::Result<()> {
+    write!(write, "    Vertical metrics: ")?;
+    let sets = [
+        ("hhea", &metrics.hhea),
+        ("typo", &metrics.typo),
+        ("win", &metrics.win),
+    ];
+    for (idx, (name, set)) in sets.into_iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        match set {
+            Some(set) => write!(
+                write,
+                "{name:>4}: ascender {}, descender {}, line gap {}, line height {}",
+                set.ascender,
+                set.descender,
+                set.line_gap,
+                set.line_height()
+            )?,
+            None => write!(write, "{name:>4}: not present")?,
+        }
+    }
+    writeln!(write)?;
+    writeln!(
+        write,
+        "    USE_TYPO_METRICS: {}",
+        if metrics.use_typo_metrics {
+            "yes"
+        } else {
+            "no"
+        }
+    )?;
+
+    if let Some(bbox) = &metrics.head_bounding_box {
+        writeln!(
+            write,
+            "  Glyph bounding box: ({}, {}) to ({}, {})",
+            bbox.x_min, bbox.y_min, bbox.x_max, bbox.y_max
+        )?;
+    }
+
+    write!(write, "        Line heights: ")?;
+    for (idx, line_height) in metrics.line_heights.iter().enumerate() {
+        if idx > 0 {
+            write!(write, "\n                      ")?;
+        }
+        write!(
+            write,
+            "{}: {} ({}",
+            line_height.platform, line_height.line_height, line_height.metrics
+        )?;
+        if line_height.external_leading != 0 {
+            write!(
+                write,
+                " + {} external leading",
+                line_height.external_leading
+            )?;
+        }
+        write!(write, ")")?;
+    }
+    writeln!(write)?;
+
+    if !metrics.warnings.is_empty() {
+        write!(write, "            Warnings: ")?;
+        for (idx, warning) in metrics.warnings.iter().enumerate() {
+            if idx > 0 {
+                write!(write, "\n                      ")?;
+            }
+            write!(write, "{warning}")?;
+        }
+        writeln!(write)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(hhea: MetricSet, typo: MetricSet, win: MetricSet, flag: bool) -> VerticalMetrics {
+        let mut metrics = VerticalMetrics {
+            units_per_em: Some(1000),
+            hhea: Some(hhea),
+            typo: Some(typo),
+            win: Some(win),
+            use_typo_metrics: flag,
+            head_bounding_box: Some(HeadBoundingBox {
+                x_min: -100,
+                y_min: -300,
+                x_max: 1000,
+                y_max: 1000,
+            }),
+            line_heights: Vec::new(),
+            warnings: Vec::new(),
+        };
+        metrics.line_heights = metrics.platform_line_heights();
+        metrics.warnings = metrics.check();
+        metrics
+    }
+
+    #[test]
+    fn test_vertical_metrics() {
+        let hhea = MetricSet {
+            ascender: 900,
+            descender: -300,
+            line_gap: 0,
+        };
+        let typo = MetricSet {
+            ascender: 800,
+            descender: -200,
+            line_gap: 200,
+        };
+        let win = MetricSet {
+            ascender: 900,
+            descender: -300,
+            line_gap: 0,
+        };
+
+        let unflagged = metrics(hhea, typo, win, false);
+        let line_heights: Vec<_> = unflagged
+            .line_heights
+            .iter()
+            .map(|line_height| (line_height.metrics, line_height.line_height))
+            .collect();
+        assert_eq!(
+            line_heights,
+            [("hhea", 1200), ("win", 1200), ("win", 1200), ("hhea", 1200)]
+        );
+        // glyphs extend 100 units above the win ascent, and the typo ascender is lower
+        assert_eq!(unflagged.warnings.len(), 3);
+
+        let flagged = metrics(hhea, typo, win, true);
+        assert_eq!(flagged.line_heights[2].metrics, "typo");
+        assert_eq!(flagged.line_heights[3].metrics, "typo");
+    }
+
+    #[test]
+    fn test_win_external_leading() {
+        let hhea = MetricSet {
+            ascender: 800,
+            descender: -200,
+            line_gap: 300,
+        };
+        let typo = MetricSet {
+            ascender: 800,
+            descender: -200,
+            line_gap: 300,
+        };
+        let win = MetricSet {
+            ascender: 900,
+            descender: -300,
+            line_gap: 0,
+        };
+
+        // the win metrics take up 200 units of the hhea line gap, GDI adds the other 100
+        let unflagged = metrics(hhea, typo, win, false);
+        let line_heights: Vec<_> = unflagged
+            .line_heights
+            .iter()
+            .map(|line_height| (line_height.external_leading, line_height.line_height))
+            .collect();
+        assert_eq!(
+            line_heights,
+            [(0, 1300), (100, 1300), (100, 1300), (0, 1300)]
+        );
+
+        // DirectWrite uses the typo line gap instead when USE_TYPO_METRICS is set
+        let flagged = metrics(hhea, typo, win, true);
+        assert_eq!(flagged.line_heights[2].external_leading, 0);
+        assert_eq!(flagged.line_heights[2].line_height, 1300);
+
+        // the win metrics cover more than the hhea line gap
+        let hhea = MetricSet {
+            line_gap: 100,
+            ..hhea
+        };
+        let metrics = metrics(hhea, typo, win, false);
+        assert_eq!(metrics.line_heights[1].external_leading, 0);
+        assert_eq!(metrics.line_heights[1].line_height, 1200);
+    }
+}